@@ -0,0 +1,27 @@
+use crate::storage::{get_current_timestamp, AuditLogStorage};
+use crate::types::{AuditAction, AuditEntry};
+
+/// Records a privileged action taken by the caller, call only once the action succeeded
+pub fn record(
+    action: AuditAction,
+    target: impl ToString,
+    before: Option<String>,
+    after: Option<String>,
+) {
+    let id = AuditLogStorage::append(AuditEntry {
+        id: 0, // Assigned by the log
+        actor: ic_cdk::api::msg_caller(),
+        action: action.clone(),
+        target: target.to_string(),
+        before,
+        after,
+        timestamp: get_current_timestamp(),
+    });
+
+    ic_cdk::println!("Audit #{}: {:?}", id, action);
+}
+
+/// Debug representation used for before/after values
+pub fn snapshot<T: std::fmt::Debug>(value: &T) -> Option<String> {
+    Some(format!("{:?}", value))
+}
@@ -0,0 +1,137 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use crate::storage::{get_current_timestamp, DepositRetryStorage, DepositStorage};
+use crate::types::{Deposit, DepositRetryState, DepositStatus};
+
+// How often unfinished deposits are looked at
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+// Attempts before a deposit is handed to the manual-review queue
+const MAX_ATTEMPTS: u32 = 5;
+
+// Backoff doubles per failed attempt: 5 min, 10 min, 20 min, ... capped at 6 hours
+const BASE_BACKOFF_SECONDS: u64 = 5 * 60;
+const MAX_BACKOFF_SECONDS: u64 = 6 * 60 * 60;
+
+// Reserved/Minting deposits younger than this may still belong to a running notify_deposit
+const IN_FLIGHT_GRACE_SECONDS: u64 = 10 * 60;
+
+thread_local! {
+    static RECONCILE_IN_PROGRESS: Cell<bool> = const { Cell::new(false) };
+}
+
+// Clears the in-progress flag even if a run is aborted by a trap
+struct ReconcileGuard;
+
+impl Drop for ReconcileGuard {
+    fn drop(&mut self) {
+        RECONCILE_IN_PROGRESS.with(|in_progress| in_progress.set(false));
+    }
+}
+
+/// Starts the reconciliation timer, must be called from init and post_upgrade
+pub fn start_reconciler() {
+    ic_cdk_timers::set_timer_interval(RECONCILE_INTERVAL, || {
+        ic_cdk::futures::spawn(reconcile_deposits())
+    });
+}
+
+/// Retries every unfinished deposit whose backoff has elapsed
+pub async fn reconcile_deposits() {
+    if RECONCILE_IN_PROGRESS.with(|in_progress| in_progress.replace(true)) {
+        return;
+    }
+    let _guard = ReconcileGuard;
+
+    let now = get_current_timestamp();
+    let candidates = DepositStorage::get_by_status(&[
+        DepositStatus::Pending,
+        DepositStatus::Reserved,
+        DepositStatus::Minting,
+        DepositStatus::Failed,
+    ]);
+
+    for deposit in candidates {
+        if !is_due(&deposit, now) {
+            continue;
+        }
+        reconcile_deposit(deposit.id).await;
+    }
+}
+
+fn is_due(deposit: &Deposit, now: u64) -> bool {
+    let in_flight = matches!(
+        deposit.status,
+        DepositStatus::Reserved | DepositStatus::Minting
+    );
+    if in_flight && now.saturating_sub(deposit.updated_at) < IN_FLIGHT_GRACE_SECONDS {
+        return false;
+    }
+
+    DepositRetryStorage::get(deposit.id).is_none_or(|state| state.next_attempt_at <= now)
+}
+
+async fn reconcile_deposit(deposit_id: u64) {
+    let Ok(deposit) = DepositStorage::get(deposit_id) else {
+        return;
+    };
+
+    // Skip users with a notify_deposit call in flight, the next tick picks the deposit up
+    let Some(_lock) = crate::DepositLock::acquire(deposit.user_principal) else {
+        return;
+    };
+
+    let now = get_current_timestamp();
+    let mut state = DepositRetryStorage::get(deposit_id).unwrap_or(DepositRetryState {
+        deposit_id,
+        attempts: 0,
+        next_attempt_at: now,
+        last_attempt_at: None,
+    });
+    state.attempts += 1;
+    state.last_attempt_at = Some(now);
+
+    // A deposit with a refund in flight is only ever finished by refunding it
+    let result = if deposit.refund_created_at.is_some() {
+        crate::refund_deposit(deposit)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    } else {
+        crate::process_deposit(deposit).await.map(|_| ())
+    };
+
+    // A rejected deposit reports an error even though it was settled by refunding it
+    let settled = DepositStorage::get(deposit_id)
+        .is_ok_and(|deposit| deposit.is_complete() || deposit.status == DepositStatus::Refunded);
+
+    let error = match result {
+        Err(e) if !settled => e,
+        _ => {
+            DepositRetryStorage::remove(deposit_id);
+            return;
+        }
+    };
+    ic_cdk::println!(
+        "Deposit reconciler: attempt {} for deposit {} failed: {}",
+        state.attempts,
+        deposit_id,
+        error
+    );
+
+    if state.attempts >= MAX_ATTEMPTS {
+        if let Ok(mut deposit) = DepositStorage::get(deposit_id) {
+            deposit.mark_manual_review();
+            let _ = DepositStorage::update(deposit);
+        }
+    } else {
+        state.next_attempt_at = get_current_timestamp() + backoff_seconds(state.attempts);
+    }
+    let _ = DepositRetryStorage::upsert(state);
+}
+
+fn backoff_seconds(attempts: u32) -> u64 {
+    let exponent = attempts.saturating_sub(1).min(16);
+    (BASE_BACKOFF_SECONDS << exponent).min(MAX_BACKOFF_SECONDS)
+}
@@ -0,0 +1,255 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use candid::Principal;
+
+use crate::ledger_blocks;
+use crate::ousg_ledger::{Account, Transaction};
+use crate::storage::{get_current_timestamp, DepositScanStorage, DepositStorage};
+
+// How often new ckBTC ledger blocks are scanned
+const SCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+// Blocks read per scan, a backlog is worked off over several ticks
+const SCAN_BATCH_SIZE: u64 = 1_000;
+
+// Deposits swept per scan, each is one ledger transfer
+const SWEEP_BATCH_SIZE: usize = 50;
+
+thread_local! {
+    static SCAN_IN_PROGRESS: Cell<bool> = const { Cell::new(false) };
+
+    // Last deposit id the previous sweep batch reached, the next batch continues after it.
+    // Deposits that stay queued (too small to sweep, failed) cannot block the ones behind them.
+    static SWEEP_CURSOR: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+// Clears the in-progress flag even if a scan is aborted by a trap
+struct ScanGuard;
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        SCAN_IN_PROGRESS.with(|in_progress| in_progress.set(false));
+    }
+}
+
+/// The user's deposit subaccount of the backend canister
+///
+/// Encodes the principal as length byte followed by its bytes, zero padded to 32 bytes, so the
+/// owner of a subaccount can be read back from the ledger without any lookup.
+pub fn deposit_subaccount(principal: &Principal) -> Vec<u8> {
+    let bytes = principal.as_slice();
+    let mut subaccount = vec![0u8; 32];
+    subaccount[0] = bytes.len() as u8;
+    subaccount[1..=bytes.len()].copy_from_slice(bytes);
+    subaccount
+}
+
+/// The all-zero subaccount is the default account, both are returned as None
+pub fn normalize_subaccount(subaccount: Option<&[u8]>) -> Option<Vec<u8>> {
+    subaccount
+        .filter(|subaccount| subaccount.iter().any(|byte| *byte != 0))
+        .map(|subaccount| subaccount.to_vec())
+}
+
+fn subaccount_owner(subaccount: &[u8]) -> Option<Principal> {
+    let length = *subaccount.first()? as usize;
+    if subaccount.len() != 32 || length == 0 || length > Principal::MAX_LENGTH_IN_BYTES {
+        return None;
+    }
+    let principal = Principal::try_from_slice(&subaccount[1..=length]).ok()?;
+
+    // Only the exact encoding counts, anything else was not handed out by this canister
+    (deposit_subaccount(&principal) == subaccount).then_some(principal)
+}
+
+/// Starts the ledger scan timer, must be called from init and post_upgrade
+///
+/// The first run right away records the ledger tip as the scan start, deposit accounts are
+/// only handed out once it is known.
+pub fn start_scanner() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || ic_cdk::futures::spawn(scan_ledger()));
+    ic_cdk_timers::set_timer_interval(SCAN_INTERVAL, || ic_cdk::futures::spawn(scan_ledger()));
+}
+
+/// Whether the block the scan starts at is recorded
+pub fn is_started() -> bool {
+    DepositScanStorage::get().next_block.is_some()
+}
+
+/// Credits new transfers to deposit subaccounts, then sweeps credited ckBTC to the default account
+pub async fn scan_ledger() {
+    if SCAN_IN_PROGRESS.with(|in_progress| in_progress.replace(true)) {
+        return;
+    }
+    let _guard = ScanGuard;
+
+    if let Err(e) = scan_new_blocks().await {
+        ic_cdk::println!("Deposit scanner: {}", e);
+    }
+    sweep_credited_deposits().await;
+}
+
+async fn scan_new_blocks() -> std::result::Result<(), String> {
+    let mut state = DepositScanStorage::get();
+
+    // No deposit account was handed out before the scan start, so the history can be skipped
+    let Some(next_block) = state.next_block else {
+        let tip = ledger_blocks::get_transactions(0, 0).await?.log_length;
+        state.next_block = Some(tip);
+        state.last_scan_at = Some(get_current_timestamp());
+        return DepositScanStorage::update(state).map_err(|e| e.to_string());
+    };
+
+    let batch = ledger_blocks::get_transactions(next_block, SCAN_BATCH_SIZE).await?;
+    let canister = ic_cdk::api::canister_self();
+
+    let mut cursor = next_block;
+    for (block_index, transaction) in batch.transactions {
+        // A gap means an archive returned less than asked for, continue from it next tick
+        if block_index != cursor {
+            break;
+        }
+
+        if let Some(user) = subaccount_deposit_owner(&transaction, canister) {
+            if !credit_deposit(user, block_index).await {
+                break;
+            }
+            state.deposits_found += 1;
+        }
+
+        // Saved after every block, a trap never rescans more than the block it stopped at
+        cursor = block_index + 1;
+        state.next_block = Some(cursor);
+        DepositScanStorage::update(state.clone()).map_err(|e| e.to_string())?;
+    }
+
+    state.last_scan_at = Some(get_current_timestamp());
+    DepositScanStorage::update(state).map_err(|e| e.to_string())
+}
+
+fn subaccount_deposit_owner(transaction: &Transaction, canister: Principal) -> Option<Principal> {
+    let transfer = transaction.transfer.as_ref()?;
+    if transfer.to.owner != canister {
+        return None;
+    }
+    subaccount_owner(transfer.to.subaccount.as_deref()?)
+}
+
+// Returns false if the user has a deposit in flight, the block is then retried next tick
+async fn credit_deposit(user: Principal, block_index: u64) -> bool {
+    let Some(_lock) = crate::DepositLock::acquire(user) else {
+        return false;
+    };
+
+    // Same reservation as notify_deposit, so a block is credited once however it is reported.
+    // Deposits for principals that are not registered are rejected and refunded by it.
+    let deposit = match crate::reserve_deposit(user, block_index) {
+        Ok(deposit) => deposit,
+        Err(e) => {
+            ic_cdk::println!("Deposit scanner: block {} skipped: {}", block_index, e);
+            return true;
+        }
+    };
+
+    // Failures stay with the deposit and are picked up by the reconciler
+    if let Err(e) = crate::process_deposit(deposit).await {
+        ic_cdk::println!(
+            "Deposit scanner: block {} not credited yet: {}",
+            block_index,
+            e
+        );
+    }
+    true
+}
+
+// Moves minted deposits out of their subaccounts so payouts can use the ckBTC
+async fn sweep_credited_deposits() {
+    let deposits =
+        DepositStorage::get_awaiting_sweep(SWEEP_CURSOR.with(Cell::get), SWEEP_BATCH_SIZE);
+    // A short batch reached the end of the queue, the next one starts over
+    let cursor = match deposits.last() {
+        Some(last) if deposits.len() == SWEEP_BATCH_SIZE => Some(last.id),
+        _ => None,
+    };
+    SWEEP_CURSOR.with(|sweep_cursor| sweep_cursor.set(cursor));
+    if deposits.is_empty() {
+        return;
+    }
+
+    let fee = match crate::get_ckbtc_fee().await {
+        Ok(fee) => fee,
+        Err(e) => {
+            ic_cdk::println!("Deposit scanner: cannot sweep, {}", e);
+            return;
+        }
+    };
+
+    let default_account = Account {
+        owner: ic_cdk::api::canister_self(),
+        subaccount: None,
+    };
+    for mut deposit in deposits {
+        if deposit.ckbtc_amount <= fee {
+            continue;
+        }
+
+        // The ledger rejects a key past its dedup window as too old. It is replaced with a
+        // fresh one only once the ledger shows the earlier sweep did not go through.
+        if let Some(created_at) = deposit.sweep_created_at.filter(|created_at| {
+            ic_cdk::api::time().saturating_sub(*created_at) >= crate::CKBTC_DEDUP_WINDOW_NANOS
+        }) {
+            match ledger_blocks::find_sent_transfer(
+                deposit.subaccount.as_deref(),
+                &default_account,
+                None,
+                created_at,
+            )
+            .await
+            {
+                Ok(Some(sweep_block_index)) => {
+                    deposit.sweep_block_index = Some(sweep_block_index);
+                    let _ = DepositStorage::update(deposit);
+                    continue;
+                }
+                Ok(None) => deposit.sweep_created_at = None,
+                Err(e) => {
+                    ic_cdk::println!(
+                        "Deposit scanner: cannot check the earlier sweep of deposit {}: {}",
+                        deposit.id,
+                        e
+                    );
+                    continue;
+                }
+            }
+        }
+
+        // Stored before the await, a retried sweep reuses the dedup key and cannot move twice
+        let created_at = *deposit
+            .sweep_created_at
+            .get_or_insert_with(ic_cdk::api::time);
+        if DepositStorage::update(deposit.clone()).is_err() {
+            continue;
+        }
+
+        match crate::transfer_ckbtc(
+            deposit.subaccount.clone(),
+            default_account.clone(),
+            deposit.ckbtc_amount - fee,
+            created_at,
+            None,
+        )
+        .await
+        {
+            Ok(sweep_block_index) => {
+                deposit.sweep_block_index = Some(sweep_block_index);
+                let _ = DepositStorage::update(deposit);
+            }
+            Err(e) => ic_cdk::println!(
+                "Deposit scanner: sweep of deposit {} failed: {}",
+                deposit.id,
+                e
+            ),
+        }
+    }
+}
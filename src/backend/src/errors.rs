@@ -24,6 +24,12 @@ pub enum BitcoinUSTBillsError {
 
     // File store bucket errors
     FileStoreBucketError(String),
+
+    // Ledger errors
+    LedgerError(String),
+
+    // Price oracle errors
+    PriceUnavailable(String),
 }
 
 impl std::fmt::Display for BitcoinUSTBillsError {
@@ -52,6 +58,14 @@ impl std::fmt::Display for BitcoinUSTBillsError {
             BitcoinUSTBillsError::FileStoreBucketError(msg) => {
                 write!(f, "File store bucket error: {}", msg)
             }
+
+            // Ledger errors
+            BitcoinUSTBillsError::LedgerError(msg) => write!(f, "Ledger error: {}", msg),
+
+            // Price oracle errors
+            BitcoinUSTBillsError::PriceUnavailable(msg) => {
+                write!(f, "No acceptable BTC price: {}", msg)
+            }
         }
     }
 }
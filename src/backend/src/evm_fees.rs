@@ -0,0 +1,263 @@
+use ethers_core::types::U256;
+
+use crate::evm_rpc::{
+    BlockTag, FeeHistory, FeeHistoryArgs, FeeHistoryResult, MultiFeeHistoryResult, RequestResult,
+};
+use crate::types::{EvmConfig, GasPolicy};
+
+// Upper bound for the size of an eth_estimateGas JSON-RPC response
+const ESTIMATE_GAS_MAX_RESPONSE_BYTES: u64 = 1_000;
+
+/// Fee parameters for an EIP-1559 transaction
+pub struct Eip1559Fees {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+/// Derive EIP-1559 fees from recent base fees and priority fee rewards
+pub async fn estimate_eip1559_fees(config: &EvmConfig) -> Result<Eip1559Fees, String> {
+    let policy = &config.gas_policy;
+    let args = FeeHistoryArgs {
+        blockCount: candid::Nat::from(policy.fee_history_blocks),
+        newestBlock: BlockTag::Latest,
+        rewardPercentiles: Some(serde_bytes::ByteBuf::from(vec![
+            policy.priority_fee_percentile,
+        ])),
+    };
+
+    let (result,) = crate::evm_rpc_service()
+        .eth_fee_history(
+            config.rpc_services.clone(),
+            config.rpc_config(),
+            args,
+            crate::EVM_RPC_CYCLES,
+        )
+        .await
+        .map_err(|e| format!("eth_feeHistory call failed: {:?}", e))?;
+
+    let history = match result {
+        MultiFeeHistoryResult::Consistent(FeeHistoryResult::Ok(history)) => history,
+        MultiFeeHistoryResult::Consistent(FeeHistoryResult::Err(e)) => {
+            return Err(format!("eth_feeHistory failed: {:?}", e));
+        }
+        // Providers a block apart disagree; price off the most expensive view
+        MultiFeeHistoryResult::Inconsistent(results) => results
+            .into_iter()
+            .filter_map(|(_, result)| match result {
+                FeeHistoryResult::Ok(history) => Some(history),
+                FeeHistoryResult::Err(_) => None,
+            })
+            .max_by_key(next_base_fee)
+            .ok_or("No RPC provider returned a fee history")?,
+    };
+
+    compute_fees(&history, policy)
+}
+
+/// Apply the gas policy to a fee history
+///
+/// The priority fee is the median of the sampled reward percentile, the max fee leaves
+/// `base_fee_multiplier_percent` headroom over the next block's base fee.
+fn compute_fees(history: &FeeHistory, policy: &GasPolicy) -> Result<Eip1559Fees, String> {
+    if history.baseFeePerGas.is_empty() {
+        return Err("Fee history contains no base fees".to_string());
+    }
+    let base_fee = next_base_fee(history);
+
+    let mut rewards: Vec<U256> = history
+        .reward
+        .iter()
+        .filter_map(|block_rewards| block_rewards.first())
+        .map(nat_to_u256)
+        .collect();
+    rewards.sort();
+    let median_reward = rewards.get(rewards.len() / 2).copied().unwrap_or_default();
+
+    let max_priority_fee_per_gas = median_reward
+        .max(U256::from(policy.min_priority_fee_per_gas))
+        .min(U256::from(policy.max_priority_fee_per_gas_cap));
+
+    let max_fee_cap = U256::from(policy.max_fee_per_gas_cap);
+    if base_fee + max_priority_fee_per_gas > max_fee_cap {
+        return Err(format!(
+            "Network base fee {} wei exceeds the configured max fee cap of {} wei",
+            base_fee, max_fee_cap
+        ));
+    }
+
+    let max_fee_per_gas =
+        base_fee * U256::from(policy.base_fee_multiplier_percent) / 100 + max_priority_fee_per_gas;
+
+    Ok(Eip1559Fees {
+        max_fee_per_gas: max_fee_per_gas.min(max_fee_cap),
+        max_priority_fee_per_gas,
+    })
+}
+
+// The last entry of baseFeePerGas is the base fee of the upcoming block
+fn next_base_fee(history: &FeeHistory) -> U256 {
+    history
+        .baseFeePerGas
+        .last()
+        .map(nat_to_u256)
+        .unwrap_or_default()
+}
+
+fn nat_to_u256(value: &candid::Nat) -> U256 {
+    let bytes = value.0.to_bytes_be();
+    if bytes.len() > 32 {
+        U256::MAX
+    } else {
+        U256::from_big_endian(&bytes)
+    }
+}
+
+/// Estimate the gas limit of a call with eth_estimateGas, plus the policy's safety buffer
+pub async fn estimate_gas_limit(
+    config: &EvmConfig,
+    from: &str,
+    to: &str,
+    data: &str,
+) -> Result<U256, String> {
+    let service = config
+        .primary_rpc_service()
+        .ok_or("No RPC provider configured for eth_estimateGas")?;
+
+    let payload = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_estimateGas",
+        "params": [{ "from": from, "to": to, "data": data, "value": "0x0" }],
+    })
+    .to_string();
+
+    let (result,) = crate::evm_rpc_service()
+        .request(
+            service,
+            payload,
+            ESTIMATE_GAS_MAX_RESPONSE_BYTES,
+            crate::EVM_RPC_CYCLES,
+        )
+        .await
+        .map_err(|e| format!("eth_estimateGas call failed: {:?}", e))?;
+
+    let body = match result {
+        RequestResult::Ok(body) => body,
+        RequestResult::Err(e) => return Err(format!("eth_estimateGas failed: {:?}", e)),
+    };
+
+    let response: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| format!("Invalid eth_estimateGas response: {}", e))?;
+    if let Some(error) = response.get("error") {
+        // Usually means the transfer itself would revert (e.g. insufficient token balance)
+        return Err(format!("eth_estimateGas rejected the transfer: {}", error));
+    }
+    let estimate = response["result"]
+        .as_str()
+        .ok_or("eth_estimateGas response has no result")
+        .and_then(|hex| {
+            U256::from_str_radix(hex.trim_start_matches("0x"), 16)
+                .map_err(|_| "eth_estimateGas returned an invalid quantity")
+        })?;
+
+    let policy = &config.gas_policy;
+    let gas_limit_cap = U256::from(policy.gas_limit_cap);
+    if estimate > gas_limit_cap {
+        return Err(format!(
+            "Estimated gas {} exceeds the configured gas limit cap of {}",
+            estimate, gas_limit_cap
+        ));
+    }
+
+    let buffered = estimate * U256::from(100 + policy.gas_limit_buffer_percent) / 100;
+    Ok(buffered.min(gas_limit_cap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn fee_history(base_fees: &[u64], rewards: &[u64]) -> FeeHistory {
+        FeeHistory {
+            reward: rewards
+                .iter()
+                .map(|&reward| vec![candid::Nat::from(reward)])
+                .collect(),
+            gasUsedRatio: vec![0.5; rewards.len()],
+            oldestBlock: candid::Nat::from(100u64),
+            baseFeePerGas: base_fees
+                .iter()
+                .map(|&fee| candid::Nat::from(fee))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn uses_the_median_reward_and_the_next_base_fee() {
+        let history = fee_history(
+            &[50 * GWEI, 10 * GWEI, 20 * GWEI],
+            &[3 * GWEI, GWEI, 2 * GWEI],
+        );
+
+        let fees = compute_fees(&history, &GasPolicy::default()).unwrap();
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(2 * GWEI));
+        // 2x the next base fee of 20 gwei plus the priority fee
+        assert_eq!(fees.max_fee_per_gas, U256::from(42 * GWEI));
+    }
+
+    #[test]
+    fn clamps_the_priority_fee_to_the_policy() {
+        let policy = GasPolicy::default();
+
+        let quiet = compute_fees(&fee_history(&[GWEI], &[1, 2, 3]), &policy).unwrap();
+        assert_eq!(
+            quiet.max_priority_fee_per_gas,
+            U256::from(policy.min_priority_fee_per_gas)
+        );
+
+        let no_rewards = compute_fees(&fee_history(&[GWEI], &[]), &policy).unwrap();
+        assert_eq!(
+            no_rewards.max_priority_fee_per_gas,
+            U256::from(policy.min_priority_fee_per_gas)
+        );
+
+        let busy = compute_fees(&fee_history(&[GWEI], &[50 * GWEI]), &policy).unwrap();
+        assert_eq!(
+            busy.max_priority_fee_per_gas,
+            U256::from(policy.max_priority_fee_per_gas_cap)
+        );
+    }
+
+    #[test]
+    fn caps_the_max_fee() {
+        let policy = GasPolicy::default();
+        let history = fee_history(&[150 * GWEI], &[2 * GWEI]);
+
+        let fees = compute_fees(&history, &policy).unwrap();
+        assert_eq!(fees.max_fee_per_gas, U256::from(policy.max_fee_per_gas_cap));
+    }
+
+    #[test]
+    fn refuses_fees_above_the_cap() {
+        let history = fee_history(&[199 * GWEI], &[2 * GWEI]);
+
+        assert!(compute_fees(&history, &GasPolicy::default()).is_err());
+    }
+
+    #[test]
+    fn requires_a_base_fee() {
+        let history = fee_history(&[], &[2 * GWEI]);
+
+        assert!(compute_fees(&history, &GasPolicy::default()).is_err());
+    }
+
+    #[test]
+    fn saturates_oversized_quantities() {
+        let oversized = candid::Nat::from(2u8).0.pow(300);
+
+        assert_eq!(nat_to_u256(&candid::Nat(oversized)), U256::MAX);
+        assert_eq!(nat_to_u256(&candid::Nat::from(GWEI)), U256::from(GWEI));
+    }
+}
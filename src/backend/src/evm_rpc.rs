@@ -1,5 +1,7 @@
 // This is an experimental feature to generate Rust binding from Candid.
 // You may want to manually adjust some of the types.
+// Manual adjustments: methods that reach RPC providers take a `cycles` argument, since the
+// EVM RPC canister charges every call for its HTTPS outcalls.
 #![allow(dead_code, unused_imports)]
 use candid::{self, CandidType, Decode, Deserialize, Encode, Principal};
 use ic_cdk::api::call::CallResult as Result;
@@ -33,7 +35,7 @@ pub struct InstallArgs {
     pub nodesInSubnet: Option<u32>,
 }
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub enum EthSepoliaService {
     Alchemy,
     BlockPi,
@@ -42,7 +44,7 @@ pub enum EthSepoliaService {
     Sepolia,
 }
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub enum L2MainnetService {
     Alchemy,
     Llama,
@@ -52,19 +54,19 @@ pub enum L2MainnetService {
 }
 
 pub type ChainId = u64;
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct HttpHeader {
     pub value: String,
     pub name: String,
 }
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct RpcApi {
     pub url: String,
     pub headers: Option<Vec<HttpHeader>>,
 }
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub enum EthMainnetService {
     Alchemy,
     Llama,
@@ -74,7 +76,7 @@ pub enum EthMainnetService {
     Ankr,
 }
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub enum RpcServices {
     EthSepolia(Option<Vec<EthSepoliaService>>),
     BaseMainnet(Option<Vec<L2MainnetService>>),
@@ -87,13 +89,13 @@ pub enum RpcServices {
     EthMainnet(Option<Vec<EthMainnetService>>),
 }
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub enum ConsensusStrategy {
     Equality,
     Threshold { min: u8, total: Option<u8> },
 }
 
-#[derive(CandidType, Deserialize)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct RpcConfig {
     pub responseConsensus: Option<ConsensusStrategy>,
     pub responseSizeEstimate: Option<u64>,
@@ -453,16 +455,30 @@ impl Service {
         arg0: RpcServices,
         arg1: Option<RpcConfig>,
         arg2: FeeHistoryArgs,
+        cycles: u128,
     ) -> Result<(MultiFeeHistoryResult,)> {
-        ic_cdk::call(self.0, "eth_feeHistory", (arg0, arg1, arg2)).await
+        ic_cdk::api::call::call_with_payment128(
+            self.0,
+            "eth_feeHistory",
+            (arg0, arg1, arg2),
+            cycles,
+        )
+        .await
     }
     pub async fn eth_get_block_by_number(
         &self,
         arg0: RpcServices,
         arg1: Option<RpcConfig>,
         arg2: BlockTag,
+        cycles: u128,
     ) -> Result<(MultiGetBlockByNumberResult,)> {
-        ic_cdk::call(self.0, "eth_getBlockByNumber", (arg0, arg1, arg2)).await
+        ic_cdk::api::call::call_with_payment128(
+            self.0,
+            "eth_getBlockByNumber",
+            (arg0, arg1, arg2),
+            cycles,
+        )
+        .await
     }
     pub async fn eth_get_logs(
         &self,
@@ -477,24 +493,45 @@ impl Service {
         arg0: RpcServices,
         arg1: Option<RpcConfig>,
         arg2: GetTransactionCountArgs,
+        cycles: u128,
     ) -> Result<(MultiGetTransactionCountResult,)> {
-        ic_cdk::call(self.0, "eth_getTransactionCount", (arg0, arg1, arg2)).await
+        ic_cdk::api::call::call_with_payment128(
+            self.0,
+            "eth_getTransactionCount",
+            (arg0, arg1, arg2),
+            cycles,
+        )
+        .await
     }
     pub async fn eth_get_transaction_receipt(
         &self,
         arg0: RpcServices,
         arg1: Option<RpcConfig>,
         arg2: String,
+        cycles: u128,
     ) -> Result<(MultiGetTransactionReceiptResult,)> {
-        ic_cdk::call(self.0, "eth_getTransactionReceipt", (arg0, arg1, arg2)).await
+        ic_cdk::api::call::call_with_payment128(
+            self.0,
+            "eth_getTransactionReceipt",
+            (arg0, arg1, arg2),
+            cycles,
+        )
+        .await
     }
     pub async fn eth_send_raw_transaction(
         &self,
         arg0: RpcServices,
         arg1: Option<RpcConfig>,
         arg2: String,
+        cycles: u128,
     ) -> Result<(MultiSendRawTransactionResult,)> {
-        ic_cdk::call(self.0, "eth_sendRawTransaction", (arg0, arg1, arg2)).await
+        ic_cdk::api::call::call_with_payment128(
+            self.0,
+            "eth_sendRawTransaction",
+            (arg0, arg1, arg2),
+            cycles,
+        )
+        .await
     }
     pub async fn get_metrics(&self) -> Result<(Metrics,)> {
         ic_cdk::call(self.0, "getMetrics", ()).await
@@ -513,8 +550,9 @@ impl Service {
         arg0: RpcService,
         arg1: String,
         arg2: u64,
+        cycles: u128,
     ) -> Result<(RequestResult,)> {
-        ic_cdk::call(self.0, "request", (arg0, arg1, arg2)).await
+        ic_cdk::api::call::call_with_payment128(self.0, "request", (arg0, arg1, arg2), cycles).await
     }
     pub async fn request_cost(
         &self,
@@ -0,0 +1,204 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use crate::evm_rpc::{
+    BlockTag, GetBlockByNumberResult, GetTransactionReceiptResult, MultiGetBlockByNumberResult,
+    MultiGetTransactionReceiptResult, TransactionReceipt,
+};
+use crate::storage::{get_current_timestamp, EvmConfigStorage, EvmTransactionStorage};
+use crate::types::{EvmConfig, EvmTransaction, EvmTransactionStatus};
+use crate::utils::nat_to_u64;
+
+// How often pending outbound transactions are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+// A pending transaction whose nonce was consumed by another transaction is dropped after this grace period
+const NONCE_REPLACED_GRACE_SECONDS: u64 = 15 * 60;
+
+thread_local! {
+    static POLL_IN_PROGRESS: Cell<bool> = const { Cell::new(false) };
+}
+
+// Clears the in-progress flag even if a poll is aborted by a trap
+struct PollGuard;
+
+impl Drop for PollGuard {
+    fn drop(&mut self) {
+        POLL_IN_PROGRESS.with(|in_progress| in_progress.set(false));
+    }
+}
+
+/// Starts the receipt polling timer, must be called from init and post_upgrade
+pub fn start_polling() {
+    ic_cdk_timers::set_timer_interval(POLL_INTERVAL, || {
+        ic_cdk::futures::spawn(poll_pending_transactions())
+    });
+}
+
+/// Checks every pending transaction once
+pub async fn poll_pending_transactions() {
+    let pending = EvmTransactionStorage::get_by_status(Some(EvmTransactionStatus::Pending));
+    if pending.is_empty() {
+        return;
+    }
+
+    // Skip this tick if the previous poll is still waiting on RPC responses
+    if POLL_IN_PROGRESS.with(|in_progress| in_progress.replace(true)) {
+        return;
+    }
+    let _guard = PollGuard;
+
+    let config = EvmConfigStorage::get();
+    let latest_block = match get_latest_block_number(&config).await {
+        Ok(block_number) => block_number,
+        Err(e) => {
+            ic_cdk::println!("EVM tracker: failed to get latest block: {}", e);
+            return;
+        }
+    };
+
+    for transaction in pending {
+        let transaction_id = transaction.id;
+        if let Err(e) = poll_transaction(transaction, latest_block, &config).await {
+            ic_cdk::println!(
+                "EVM tracker: failed to poll transaction {}: {}",
+                transaction_id,
+                e
+            );
+        }
+    }
+}
+
+async fn poll_transaction(
+    mut transaction: EvmTransaction,
+    latest_block: u64,
+    config: &EvmConfig,
+) -> Result<(), String> {
+    let now = get_current_timestamp();
+
+    match get_transaction_receipt(&transaction.transaction_hash, config).await? {
+        Some(receipt) => {
+            let block_number = nat_to_u64(&receipt.blockNumber).map_err(|e| e.to_string())?;
+            transaction.block_number = Some(block_number);
+            transaction.confirmations = if latest_block >= block_number {
+                latest_block - block_number + 1
+            } else {
+                0
+            };
+            transaction.gas_used = Some(receipt.gasUsed.0.to_string());
+            transaction.effective_gas_price = Some(receipt.effectiveGasPrice.0.to_string());
+
+            if transaction.confirmations >= config.required_confirmations {
+                transaction.status = if receipt_succeeded(&receipt) {
+                    EvmTransactionStatus::Confirmed
+                } else {
+                    EvmTransactionStatus::Reverted
+                };
+            }
+        }
+        None => {
+            // No receipt (anymore) - a reorg can remove a previously seen inclusion
+            transaction.block_number = None;
+            transaction.confirmations = 0;
+
+            // However long it has been pending, a transaction whose nonce is still free can be
+            // mined, so it is only dropped once another transaction took the nonce for good
+            let pending_for = now.saturating_sub(transaction.submitted_at);
+            if pending_for >= NONCE_REPLACED_GRACE_SECONDS
+                && nonce_consumed(&transaction, config).await?
+            {
+                transaction.status = EvmTransactionStatus::Dropped;
+            }
+        }
+    }
+
+    transaction.updated_at = now;
+    if transaction.is_final() {
+        ic_cdk::println!(
+            "EVM transaction {} ({}) finalized as {:?}",
+            transaction.id,
+            transaction.transaction_hash,
+            transaction.status
+        );
+    }
+    EvmTransactionStorage::update(transaction).map_err(|e| e.to_string())
+}
+
+// Receipts without a status field predate Byzantium and always succeeded
+fn receipt_succeeded(receipt: &TransactionReceipt) -> bool {
+    receipt
+        .status
+        .as_ref()
+        .is_none_or(|status| status.0 == 1u8.into())
+}
+
+// True if a finalized transaction already used this transaction's nonce, which a reorg
+// cannot undo
+async fn nonce_consumed(transaction: &EvmTransaction, config: &EvmConfig) -> Result<bool, String> {
+    let mined_count = crate::get_transaction_count(
+        &transaction.from_address,
+        BlockTag::Finalized,
+        config,
+        &crate::evm_rpc_service(),
+    )
+    .await?;
+    Ok(mined_count > transaction.nonce.into())
+}
+
+async fn get_transaction_receipt(
+    transaction_hash: &str,
+    config: &EvmConfig,
+) -> Result<Option<TransactionReceipt>, String> {
+    let (result,) = crate::evm_rpc_service()
+        .eth_get_transaction_receipt(
+            config.rpc_services.clone(),
+            config.rpc_config(),
+            transaction_hash.to_string(),
+            crate::EVM_RPC_CYCLES,
+        )
+        .await
+        .map_err(|e| format!("eth_getTransactionReceipt call failed: {:?}", e))?;
+
+    match result {
+        MultiGetTransactionReceiptResult::Consistent(GetTransactionReceiptResult::Ok(receipt)) => {
+            Ok(receipt)
+        }
+        MultiGetTransactionReceiptResult::Consistent(GetTransactionReceiptResult::Err(e)) => {
+            Err(format!("eth_getTransactionReceipt failed: {:?}", e))
+        }
+        // Providers see the inclusion at slightly different times, try again next tick
+        MultiGetTransactionReceiptResult::Inconsistent(_) => {
+            Err("RPC providers returned inconsistent receipts".to_string())
+        }
+    }
+}
+
+pub(crate) async fn get_latest_block_number(config: &EvmConfig) -> Result<u64, String> {
+    let (result,) = crate::evm_rpc_service()
+        .eth_get_block_by_number(
+            config.rpc_services.clone(),
+            config.rpc_config(),
+            BlockTag::Latest,
+            crate::EVM_RPC_CYCLES,
+        )
+        .await
+        .map_err(|e| format!("eth_getBlockByNumber call failed: {:?}", e))?;
+
+    match result {
+        MultiGetBlockByNumberResult::Consistent(GetBlockByNumberResult::Ok(block)) => {
+            nat_to_u64(&block.number).map_err(|e| e.to_string())
+        }
+        MultiGetBlockByNumberResult::Consistent(GetBlockByNumberResult::Err(e)) => {
+            Err(format!("eth_getBlockByNumber failed: {:?}", e))
+        }
+        // Providers are usually a block or two apart, the lowest head undercounts confirmations
+        MultiGetBlockByNumberResult::Inconsistent(results) => results
+            .iter()
+            .filter_map(|(_, result)| match result {
+                GetBlockByNumberResult::Ok(block) => nat_to_u64(&block.number).ok(),
+                GetBlockByNumberResult::Err(_) => None,
+            })
+            .min()
+            .ok_or_else(|| "No RPC provider returned the latest block".to_string()),
+    }
+}
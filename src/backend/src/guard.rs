@@ -1,63 +1,264 @@
 use crate::errors::{BitcoinUSTBillsError, Result};
+use crate::storage::{get_current_timestamp, RoleStorage};
+use crate::types::{Role, RoleAssignment};
 use candid::Principal;
-use std::{cell::RefCell, collections::BTreeSet};
 
 const INITIAL_AUTHORIZED_PRINCIPAL: &str =
     "6lzil-lzkgm-twmv5-rz5xg-a5nnm-togvj-mlu6s-p4xyl-5j3zi-6a6jy-yqe";
 
-thread_local! {
-    pub static GUARD: RefCell<BTreeSet<Principal>> = RefCell::new({
-        let mut set = BTreeSet::new();
-        let initial_principal = Principal::from_text(INITIAL_AUTHORIZED_PRINCIPAL)
-        .expect("Invalid initial principal");
+// The frontend's identity was an admin before roles existed, it gets no role
+const FRONTEND_PRINCIPAL: &str = "tdlcm-qf6xy-gwvm5-uil6h-ygjte-6e3nq-ni63i-cqbeh-ho4p2-7ga6x-6ae";
 
-        let frontend_principal = Principal::from_text("tdlcm-qf6xy-gwvm5-uil6h-ygjte-6e3nq-ni63i-cqbeh-ho4p2-7ga6x-6ae")
-        .expect("Invalid initial principal");
-
-        set.insert(initial_principal);
-        set.insert(frontend_principal);
-        set
-    });
+/// Seeds the initial SuperAdmins when no roles are assigned yet (fresh install)
+///
+/// These are the built-in admin and the admins passed in the init arguments.
+pub fn seed_initial_admins(initial_admins: &[Principal]) {
+    if RoleStorage::count() > 0 {
+        return;
+    }
 
+    let built_in =
+        Principal::from_text(INITIAL_AUTHORIZED_PRINCIPAL).expect("Invalid initial principal");
+    for principal in std::iter::once(&built_in).chain(initial_admins) {
+        grant_role(*principal, Role::SuperAdmin).expect("failed to store the initial admin");
+    }
 }
 
+/// Whether the principal is the frontend's identity
+pub fn is_frontend(principal: &Principal) -> bool {
+    principal.to_text() == FRONTEND_PRINCIPAL
+}
 
-/// Gets the list of authorized principals
+/// Gets the list of principals holding any role
 pub fn get_list() -> Vec<Principal> {
-    GUARD.with(|guard| {
-        let guard_ref = guard.borrow();
-        guard_ref.iter().cloned().collect()
-    })
+    RoleStorage::get_all()
+        .into_iter()
+        .map(|assignment| assignment.principal)
+        .collect()
 }
 
-/// Assert that the caller is an admin, returning BitcoinUSTBillsError
+/// Assert that the caller is a SuperAdmin, returning BitcoinUSTBillsError
 pub fn assert_admin() -> Result<()> {
-    let caller = ic_cdk::api::msg_caller();
-    let anonymous = Principal::anonymous();
+    assert_role(Role::SuperAdmin)
+}
+
+/// Assert that the caller holds the role (or is a SuperAdmin)
+pub fn assert_role(role: Role) -> Result<()> {
+    assert_any_role(&[role])
+}
+
+/// Assert that the caller holds at least one of the roles (or is a SuperAdmin)
+pub fn assert_any_role(roles: &[Role]) -> Result<()> {
+    authorize(&ic_cdk::api::msg_caller(), roles)
+}
 
-    if caller == anonymous {
+fn authorize(caller: &Principal, roles: &[Role]) -> Result<()> {
+    if *caller == Principal::anonymous() {
         return Err(BitcoinUSTBillsError::AnonymousCaller);
     }
 
-    GUARD.with(|guard| {
-        let guard_ref = guard.borrow();
-        if !guard_ref.contains(&caller) {
-            Err(BitcoinUSTBillsError::Unauthorized)
-        } else {
-            Ok(())
-        }
-    })
+    let granted = get_roles(caller);
+    if granted.contains(&Role::SuperAdmin) || roles.iter().any(|role| granted.contains(role)) {
+        Ok(())
+    } else {
+        Err(BitcoinUSTBillsError::Unauthorized)
+    }
 }
 
-/// Checks if a principal is in the authorized list
+/// Checks if a principal holds any role
 pub fn is_authorized(principal: &Principal) -> bool {
-    GUARD.with(|guard| {
-        let guard_ref = guard.borrow();
-        guard_ref.contains(principal)
-    })
+    !get_roles(principal).is_empty()
 }
 
-/// Gets all authorized principals
+/// Gets all principals holding any role
 pub fn get_authorized_principals() -> Vec<Principal> {
     get_list()
 }
+
+/// Gets the roles held by a principal
+pub fn get_roles(principal: &Principal) -> Vec<Role> {
+    RoleStorage::get(principal)
+        .map(|assignment| assignment.roles)
+        .unwrap_or_default()
+}
+
+/// Grants a role, granting a role the principal already holds is a no-op
+pub fn grant_role(principal: Principal, role: Role) -> Result<()> {
+    grant_role_at(principal, role, get_current_timestamp())
+}
+
+fn grant_role_at(principal: Principal, role: Role, now: u64) -> Result<()> {
+    if principal == Principal::anonymous() {
+        return Err(BitcoinUSTBillsError::AnonymousCaller);
+    }
+
+    let mut assignment = RoleStorage::get(&principal).unwrap_or(RoleAssignment {
+        principal,
+        roles: Vec::new(),
+        granted_at: now,
+        updated_at: now,
+    });
+
+    if !assignment.roles.contains(&role) {
+        assignment.roles.push(role);
+        assignment.roles.sort();
+        assignment.updated_at = now;
+    }
+
+    RoleStorage::upsert(assignment)
+}
+
+/// Revokes a role, refusing to remove the last SuperAdmin
+pub fn revoke_role(principal: Principal, role: Role) -> Result<()> {
+    revoke_role_at(principal, role, get_current_timestamp())
+}
+
+fn revoke_role_at(principal: Principal, role: Role, now: u64) -> Result<()> {
+    let mut assignment = match RoleStorage::get(&principal) {
+        Some(assignment) if assignment.roles.contains(&role) => assignment,
+        _ => {
+            return Err(BitcoinUSTBillsError::ValidationError(format!(
+                "{} does not hold the {:?} role",
+                principal, role
+            )))
+        }
+    };
+
+    if role == Role::SuperAdmin && RoleStorage::count_with_role(Role::SuperAdmin) <= 1 {
+        return Err(BitcoinUSTBillsError::ValidationError(
+            "Cannot revoke the last SuperAdmin".to_string(),
+        ));
+    }
+
+    assignment.roles.retain(|granted| *granted != role);
+    if assignment.roles.is_empty() {
+        RoleStorage::remove(&principal)
+    } else {
+        assignment.updated_at = now;
+        RoleStorage::upsert(assignment)
+    }
+}
+
+/// Checks if a principal may approve and execute treasury proposals
+pub fn is_treasury_approver(principal: &Principal) -> bool {
+    get_roles(principal).iter().any(Role::approves_treasury)
+}
+
+/// Number of principals that may approve treasury proposals (the N of M-of-N)
+pub fn treasury_approver_count() -> u64 {
+    get_list()
+        .iter()
+        .filter(|principal| is_treasury_approver(principal))
+        .count() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id; 29])
+    }
+
+    #[test]
+    fn super_admins_pass_every_role_check() {
+        let admin = principal(1);
+        grant_role_at(admin, Role::SuperAdmin, NOW).unwrap();
+
+        assert!(authorize(&admin, &[Role::KycReviewer]).is_ok());
+        assert!(authorize(&admin, &[Role::TreasuryOperator, Role::Auditor]).is_ok());
+    }
+
+    #[test]
+    fn other_roles_only_pass_their_own_checks() {
+        let reviewer = principal(1);
+        grant_role_at(reviewer, Role::KycReviewer, NOW).unwrap();
+
+        assert!(authorize(&reviewer, &[Role::KycReviewer]).is_ok());
+        assert!(authorize(&reviewer, &[Role::Auditor, Role::KycReviewer]).is_ok());
+        assert!(matches!(
+            authorize(&reviewer, &[Role::TreasuryOperator]),
+            Err(BitcoinUSTBillsError::Unauthorized)
+        ));
+        assert!(matches!(
+            authorize(&reviewer, &[Role::SuperAdmin]),
+            Err(BitcoinUSTBillsError::Unauthorized)
+        ));
+        assert!(matches!(
+            authorize(&principal(2), &[Role::KycReviewer]),
+            Err(BitcoinUSTBillsError::Unauthorized)
+        ));
+    }
+
+    #[test]
+    fn anonymous_callers_are_refused() {
+        assert!(matches!(
+            authorize(&Principal::anonymous(), &[Role::Auditor]),
+            Err(BitcoinUSTBillsError::AnonymousCaller)
+        ));
+        assert!(matches!(
+            grant_role_at(Principal::anonymous(), Role::Auditor, NOW),
+            Err(BitcoinUSTBillsError::AnonymousCaller)
+        ));
+    }
+
+    #[test]
+    fn grants_accumulate_without_duplicates() {
+        let operator = principal(1);
+        grant_role_at(operator, Role::TreasuryOperator, NOW).unwrap();
+        grant_role_at(operator, Role::Auditor, NOW + 1).unwrap();
+        grant_role_at(operator, Role::TreasuryOperator, NOW + 2).unwrap();
+
+        let assignment = RoleStorage::get(&operator).unwrap();
+        assert_eq!(
+            assignment.roles,
+            vec![Role::TreasuryOperator, Role::Auditor]
+        );
+        assert_eq!(assignment.granted_at, NOW);
+        assert_eq!(assignment.updated_at, NOW + 1);
+        assert!(is_authorized(&operator));
+    }
+
+    #[test]
+    fn revoking_the_last_role_removes_the_assignment() {
+        let auditor = principal(1);
+        grant_role_at(auditor, Role::Auditor, NOW).unwrap();
+
+        assert!(revoke_role_at(auditor, Role::KycReviewer, NOW).is_err());
+        revoke_role_at(auditor, Role::Auditor, NOW).unwrap();
+        assert!(RoleStorage::get(&auditor).is_none());
+        assert!(!is_authorized(&auditor));
+        assert!(revoke_role_at(auditor, Role::Auditor, NOW).is_err());
+    }
+
+    #[test]
+    fn keeps_the_last_super_admin() {
+        let (first, second) = (principal(1), principal(2));
+        grant_role_at(first, Role::SuperAdmin, NOW).unwrap();
+        grant_role_at(second, Role::SuperAdmin, NOW).unwrap();
+
+        revoke_role_at(first, Role::SuperAdmin, NOW).unwrap();
+        assert!(revoke_role_at(second, Role::SuperAdmin, NOW).is_err());
+        assert_eq!(get_roles(&second), vec![Role::SuperAdmin]);
+    }
+
+    #[test]
+    fn counts_treasury_approvers() {
+        grant_role_at(principal(1), Role::SuperAdmin, NOW).unwrap();
+        grant_role_at(principal(2), Role::TreasuryOperator, NOW).unwrap();
+        grant_role_at(principal(3), Role::KycReviewer, NOW).unwrap();
+        grant_role_at(principal(4), Role::Auditor, NOW).unwrap();
+        grant_role_at(principal(4), Role::TreasuryOperator, NOW).unwrap();
+
+        assert!(is_treasury_approver(&principal(1)));
+        assert!(is_treasury_approver(&principal(2)));
+        assert!(!is_treasury_approver(&principal(3)));
+        assert!(is_treasury_approver(&principal(4)));
+        assert_eq!(treasury_approver_count(), 3);
+
+        revoke_role_at(principal(4), Role::TreasuryOperator, NOW).unwrap();
+        assert_eq!(treasury_approver_count(), 2);
+    }
+}
@@ -1,32 +1,300 @@
-use crate::guard::GUARD;
+use crate::audit_log;
+use crate::storage::{
+    CanisterIdsStorage, DepositStorage, EvmConfigStorage, PriceOracleStorage, SchemaVersionStorage,
+};
+use crate::types::{AuditAction, BackendInitArgs, Role};
 use candid::{CandidType, Principal};
-use ic_cdk::{post_upgrade, pre_upgrade, storage};
+use ic_cdk::{init, post_upgrade};
+use ic_stable_structures::{DefaultMemoryImpl, Memory};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
 
-// StableStore struct is used for serializing and deserializing the data during upgrades
+// Layout written by the old pre_upgrade hook through stable_save, only read by the migration
 #[derive(Debug, Clone, Serialize, Deserialize, CandidType)]
 pub struct StableStore {
     pub guard: Vec<Principal>,
 }
 
-#[pre_upgrade]
-pub fn pre_upgrade_handler() {
-    let stable_store = GUARD.with(|guard| {
-        let guard_ref = guard.borrow().iter().cloned().collect();
-        StableStore {
-            guard: guard_ref,
-        }
-    });
-    storage::stable_save((stable_store,)).unwrap();
+// All state lives in MemoryManager-backed structures, so there is no pre_upgrade hook
+
+// Bumped with every migration added to run_migrations
+const SCHEMA_VERSION: u32 = 2;
+
+#[init]
+pub fn init_handler(args: Option<BackendInitArgs>) {
+    // A fresh install starts in the current layout
+    SchemaVersionStorage::set(SCHEMA_VERSION);
+    crate::guard::seed_initial_admins(&initial_admins(&args));
+    apply_init_args(args);
+    crate::evm_tx_tracker::start_polling();
+    crate::deposit_reconciler::start_reconciler();
+    crate::deposit_scanner::start_scanner();
+    crate::price_history::start_sampler();
 }
 
-// Post-upgrade hook: Restores the state from stable storage after an upgrade
 #[post_upgrade]
-pub fn post_upgrade_handler() {
-    let (stable_store,): (StableStore,) = storage::stable_restore().unwrap();
-    
-    GUARD.with(|guard| {
-        *guard.borrow_mut() = BTreeSet::from_iter(stable_store.guard);
-    });
+pub fn post_upgrade_handler(args: Option<BackendInitArgs>) {
+    // Must run before anything touches the MemoryManager, which would reformat the region
+    migrate_legacy_stable_store();
+    crate::guard::seed_initial_admins(&initial_admins(&args));
+    run_migrations();
+    crate::storage::AuditLogStorage::backfill_timestamp_index();
+
+    apply_init_args(args);
+
+    // Timers don't survive upgrades
+    crate::evm_tx_tracker::start_polling();
+    crate::deposit_reconciler::start_reconciler();
+    crate::deposit_scanner::start_scanner();
+    crate::price_history::start_sampler();
+}
+
+// Migrations run once, on the first upgrade past the version that needs them, instead of
+// rewriting every record on each upgrade
+fn run_migrations() {
+    let version = SchemaVersionStorage::get();
+
+    // 1: deposits indexed by block, money amounts stored as fixed-point values
+    if version < 1 {
+        DepositStorage::backfill_block_index();
+        DepositStorage::migrate_legacy_records();
+    }
+
+    // 2: minted deposits that still have to be swept are queued
+    if version < 2 {
+        DepositStorage::backfill_sweep_queue();
+    }
+
+    if version < SCHEMA_VERSION {
+        SchemaVersionStorage::set(SCHEMA_VERSION);
+    }
+}
+
+// Upgrading from a release that still had the stable_save pre_upgrade hook: stable memory
+// then starts with the Candid-encoded StableStore instead of the MemoryManager header.
+fn migrate_legacy_stable_store() {
+    let Some(stable_store) = take_legacy_stable_store(&DefaultMemoryImpl::default()) else {
+        return;
+    };
+
+    for principal in stable_store.guard {
+        if crate::guard::is_frontend(&principal) {
+            continue;
+        }
+        // The flat admin set predates roles, every legacy admin had full access
+        crate::guard::grant_role(principal, Role::SuperAdmin).expect("failed to migrate admin");
+    }
+}
+
+// Layout of the MemoryManager (v1) header that stable_save overwrote
+const MANAGER_HEADER_SIZE: usize = 2080;
+const MANAGER_SIZES_OFFSET: usize = 40;
+const MANAGER_MAX_BUCKETS: usize = 32768;
+const MANAGER_BUCKET_SIZE_IN_PAGES: u64 = 128;
+const UNALLOCATED_BUCKET: u8 = 255;
+const WASM_PAGE_SIZE: usize = 64 * 1024;
+
+// Decodes the legacy StableStore and rebuilds the MemoryManager header under it
+//
+// stable_save only wrote the encoded store, the bucket table behind the header and every
+// bucket from page 1 on still hold the maps of the old release. The header is rebuilt from
+// the bucket table, memory sizes whose slot was overwritten or that no longer match their
+// buckets are rounded up to whole buckets.
+fn take_legacy_stable_store<M: Memory>(memory: &M) -> Option<StableStore> {
+    if memory.size() == 0 {
+        return None;
+    }
+
+    let mut first_page = vec![0u8; WASM_PAGE_SIZE];
+    memory.read(0, &mut first_page);
+    if &first_page[..4] != b"DIDL" {
+        return None;
+    }
+
+    // Whatever follows the encoded store is MemoryManager data, so it is not checked
+    let stable_store: StableStore = candid::de::IDLDeserialize::new(&first_page)
+        .and_then(|mut de| de.get_value())
+        .expect("failed to decode the legacy stable store");
+
+    // A first page only held the header, the old release never allocated a bucket
+    if memory.size() <= 1 {
+        return Some(stable_store);
+    }
+
+    let legacy_size = candid::encode_one(&stable_store)
+        .expect("failed to encode the legacy stable store")
+        .len();
+    if legacy_size > MANAGER_HEADER_SIZE {
+        ic_cdk::trap("The legacy stable store overwrote the MemoryManager bucket table");
+    }
+
+    let buckets = &first_page[MANAGER_HEADER_SIZE..MANAGER_HEADER_SIZE + MANAGER_MAX_BUCKETS];
+    let mut buckets_per_memory = [0u64; UNALLOCATED_BUCKET as usize];
+    for &owner in buckets.iter().filter(|&&owner| owner != UNALLOCATED_BUCKET) {
+        buckets_per_memory[owner as usize] += 1;
+    }
+    let allocated_buckets: u64 = buckets_per_memory.iter().sum();
+
+    let mut header = Vec::with_capacity(MANAGER_HEADER_SIZE);
+    header.extend_from_slice(b"MGR");
+    header.push(1); // Layout version
+    header.extend_from_slice(&(allocated_buckets as u16).to_le_bytes());
+    header.extend_from_slice(&(MANAGER_BUCKET_SIZE_IN_PAGES as u16).to_le_bytes());
+    header.resize(MANAGER_SIZES_OFFSET, 0);
+
+    for (id, &bucket_count) in buckets_per_memory.iter().enumerate() {
+        let offset = MANAGER_SIZES_OFFSET + id * 8;
+        let stored = u64::from_le_bytes(first_page[offset..offset + 8].try_into().unwrap());
+        let size_in_pages = if offset + 8 > legacy_size
+            && stored.div_ceil(MANAGER_BUCKET_SIZE_IN_PAGES) == bucket_count
+        {
+            stored
+        } else {
+            bucket_count * MANAGER_BUCKET_SIZE_IN_PAGES
+        };
+        header.extend_from_slice(&size_in_pages.to_le_bytes());
+    }
+
+    memory.write(0, &header);
+    Some(stable_store)
+}
+
+// Invalid arguments trap so a misconfigured install or upgrade is rolled back
+fn initial_admins(args: &Option<BackendInitArgs>) -> Vec<Principal> {
+    args.as_ref()
+        .and_then(|args| args.initial_admins.clone())
+        .unwrap_or_default()
+}
+
+fn apply_init_args(args: Option<BackendInitArgs>) {
+    let Some(args) = args else { return };
+
+    // Recorded with the installing controller as the actor
+    if let Some(canister_ids_args) = args.canister_ids {
+        let before = CanisterIdsStorage::get();
+        let mut canister_ids = before.clone();
+        canister_ids.apply(canister_ids_args);
+        CanisterIdsStorage::update(canister_ids.clone()).expect("failed to store the canister ids");
+        audit_log::record(
+            AuditAction::CanisterIdsUpdated,
+            "canister_ids",
+            audit_log::snapshot(&before),
+            audit_log::snapshot(&canister_ids),
+        );
+    }
+
+    if let Some(evm_args) = args.evm {
+        let before = EvmConfigStorage::get();
+        let mut config = before.clone();
+        if let Err(e) = config.apply_network_args(evm_args) {
+            ic_cdk::trap(format!("Invalid EVM init arguments: {}", e));
+        }
+        EvmConfigStorage::update(config.clone()).expect("failed to store the EVM config");
+        audit_log::record(
+            AuditAction::EvmNetworkUpdated,
+            "evm_config",
+            audit_log::snapshot(&before),
+            audit_log::snapshot(&config),
+        );
+    }
+
+    // Test mode can only be switched on here, by the controller installing the canister
+    if let Some(oracle_config) = args.price_oracle {
+        if let Err(e) = oracle_config.validate() {
+            ic_cdk::trap(format!("Invalid price oracle init arguments: {}", e));
+        }
+        let before = PriceOracleStorage::get_config();
+        PriceOracleStorage::update_config(oracle_config.clone())
+            .expect("failed to store the price oracle config");
+        audit_log::record(
+            AuditAction::PriceOracleConfigUpdated,
+            "price_oracle",
+            audit_log::snapshot(&before),
+            audit_log::snapshot(&oracle_config),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_stable_structures::memory_manager::{MemoryId, MemoryManager};
+    use ic_stable_structures::{Cell, StableBTreeMap};
+
+    fn admin(id: u8) -> Principal {
+        Principal::from_slice(&[id; 29])
+    }
+
+    fn write_legacy_store(memory: &DefaultMemoryImpl, guard: Vec<Principal>) {
+        let bytes = candid::encode_args((StableStore { guard },)).unwrap();
+        if memory.size() == 0 {
+            memory.grow(1);
+        }
+        memory.write(0, &bytes);
+    }
+
+    #[test]
+    fn legacy_image_keeps_memory_manager_data() {
+        let memory = DefaultMemoryImpl::default();
+        {
+            let manager = MemoryManager::init(memory.clone());
+            let mut users: StableBTreeMap<u64, String, _> =
+                StableBTreeMap::init(manager.get(MemoryId::new(1)));
+            for id in 0..500 {
+                users.insert(id, format!("user {}", id));
+            }
+            let mut roles: StableBTreeMap<u64, u64, _> =
+                StableBTreeMap::init(manager.get(MemoryId::new(19)));
+            roles.insert(7, 42);
+            Cell::init(manager.get(MemoryId::new(7)), 1234u64);
+        }
+
+        write_legacy_store(&memory, vec![admin(1), admin(2)]);
+        let stable_store = take_legacy_stable_store(&memory).expect("legacy store not found");
+        assert_eq!(stable_store.guard, vec![admin(1), admin(2)]);
+
+        let manager = MemoryManager::init(memory.clone());
+        let users: StableBTreeMap<u64, String, _> =
+            StableBTreeMap::init(manager.get(MemoryId::new(1)));
+        assert_eq!(users.len(), 500);
+        assert_eq!(users.get(&499), Some("user 499".to_string()));
+        let roles: StableBTreeMap<u64, u64, _> =
+            StableBTreeMap::init(manager.get(MemoryId::new(19)));
+        assert_eq!(roles.get(&7), Some(42));
+        let counter = Cell::init(manager.get(MemoryId::new(7)), 0u64);
+        assert_eq!(*counter.get(), 1234);
+
+        // New allocations must not land in buckets that already hold data
+        let mut proposals: StableBTreeMap<u64, u64, _> =
+            StableBTreeMap::init(manager.get(MemoryId::new(21)));
+        proposals.insert(1, 1);
+        assert_eq!(users.get(&0), Some("user 0".to_string()));
+        assert_eq!(roles.get(&7), Some(42));
+    }
+
+    #[test]
+    fn legacy_store_without_memory_manager_is_decoded() {
+        let memory = DefaultMemoryImpl::default();
+        write_legacy_store(&memory, vec![admin(3)]);
+
+        let stable_store = take_legacy_stable_store(&memory).expect("legacy store not found");
+        assert_eq!(stable_store.guard, vec![admin(3)]);
+    }
+
+    #[test]
+    fn memory_manager_image_is_left_alone() {
+        let memory = DefaultMemoryImpl::default();
+        assert!(take_legacy_stable_store(&memory).is_none());
+
+        let manager = MemoryManager::init(memory.clone());
+        let mut users: StableBTreeMap<u64, u64, _> =
+            StableBTreeMap::init(manager.get(MemoryId::new(1)));
+        users.insert(1, 2);
+        let mut before = vec![0u8; MANAGER_HEADER_SIZE];
+        memory.read(0, &mut before);
+
+        assert!(take_legacy_stable_store(&memory).is_none());
+        let mut after = vec![0u8; MANAGER_HEADER_SIZE];
+        memory.read(0, &mut after);
+        assert_eq!(before, after);
+    }
 }
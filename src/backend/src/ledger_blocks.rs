@@ -0,0 +1,559 @@
+use candid::Principal;
+use ic_cdk::call::Call;
+
+use crate::deposit_scanner::normalize_subaccount;
+use crate::ousg_ledger::{
+    Account, GetBlocksArgs, GetBlocksResult, GetTransactionsRequest, Icrc3Value, Transaction,
+    TransactionRange, Transfer,
+};
+
+/// Ledger transactions of a block range, tagged with their block index
+pub struct LedgerTransactions {
+    pub log_length: u64,
+    pub transactions: Vec<(u64, Transaction)>,
+}
+
+/// Reads up to `length` ckBTC transactions starting at `start`, in block order
+///
+/// Blocks that moved to an archive canister are fetched through the archive callbacks the
+/// ledger returns, so old and recent blocks look the same to the caller. An archive may
+/// answer with fewer blocks than asked for, callers must not assume the range is complete.
+pub async fn get_transactions(
+    start: u64,
+    length: u64,
+) -> std::result::Result<LedgerTransactions, String> {
+    let request = GetTransactionsRequest {
+        start: candid::Nat::from(start),
+        length: candid::Nat::from(length),
+    };
+    let (response,) = crate::ckbtc_ledger_service()
+        .get_transactions(request)
+        .await
+        .map_err(|e| format!("Failed to get transactions: {:?}", e))?;
+
+    let log_length = nat_to_u64(&response.log_length)?;
+    let mut transactions = Vec::new();
+
+    for archived in response.archived_transactions {
+        let archived_start = nat_to_u64(&archived.start)?;
+        let archive_request = GetTransactionsRequest {
+            start: archived.start,
+            length: archived.length,
+        };
+        let range =
+            Call::unbounded_wait(archived.callback.0.principal, &archived.callback.0.method)
+                .with_arg(archive_request)
+                .await
+                .map_err(|e| format!("Archive call failed: {}", e))?
+                .candid::<TransactionRange>()
+                .map_err(|e| format!("Failed to decode archived transactions: {}", e))?;
+
+        transactions.extend((archived_start..).zip(range.transactions));
+    }
+
+    let first_index = nat_to_u64(&response.first_index)?;
+    transactions.extend((first_index..).zip(response.transactions));
+    transactions.sort_by_key(|(index, _)| *index);
+
+    Ok(LedgerTransactions {
+        log_length,
+        transactions,
+    })
+}
+
+// A transfer is only accepted inside the ledger's 24 hour window, give or take clock drift
+const TX_WINDOW_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+const PERMITTED_DRIFT_NANOS: u64 = 5 * 60 * 1_000_000_000;
+
+// Blocks read per get_transactions call while searching
+const SEARCH_BATCH_SIZE: u64 = 1000;
+
+/// Finds the block of a transfer the canister sent from `from_subaccount` to `to` with `memo`
+/// and `created_at_time`
+///
+/// The ledger only accepts a transfer inside its transaction window, so only blocks from that
+/// time span are read. Fails instead of answering `None` if part of the span was unreadable.
+pub async fn find_sent_transfer(
+    from_subaccount: Option<&[u8]>,
+    to: &Account,
+    memo: Option<&[u8]>,
+    created_at_time: u64,
+) -> std::result::Result<Option<u64>, String> {
+    let from = Account {
+        owner: ic_cdk::api::canister_self(),
+        subaccount: from_subaccount.map(|subaccount| subaccount.to_vec().into()),
+    };
+    let earliest = created_at_time.saturating_sub(PERMITTED_DRIFT_NANOS);
+    let latest = created_at_time + TX_WINDOW_NANOS + PERMITTED_DRIFT_NANOS;
+    let log_length = get_transactions(0, 0).await?.log_length;
+
+    // Blocks are in time order, binary search for the first one that is not too early
+    let (mut low, mut high) = (0, log_length);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if block_timestamp(middle).await? < earliest {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+
+    let mut cursor = low;
+    while cursor < log_length {
+        let batch = get_transactions(cursor, SEARCH_BATCH_SIZE).await?;
+        let mut next = cursor;
+        for (index, transaction) in batch.transactions {
+            if index != next {
+                return Err(format!("block {} could not be read", next));
+            }
+            next += 1;
+            if transaction.timestamp > latest {
+                return Ok(None);
+            }
+            let Some(transfer) = transaction.transfer else {
+                continue;
+            };
+            if is_sent_transfer(&transfer, &from, to, memo, created_at_time) {
+                return Ok(Some(index));
+            }
+        }
+        if next == cursor {
+            return Err(format!("block {} could not be read", cursor));
+        }
+        cursor = next;
+    }
+    Ok(None)
+}
+
+fn is_sent_transfer(
+    transfer: &Transfer,
+    from: &Account,
+    to: &Account,
+    memo: Option<&[u8]>,
+    created_at_time: u64,
+) -> bool {
+    transfer.created_at_time == Some(created_at_time)
+        && same_account(&transfer.from, from)
+        && same_account(&transfer.to, to)
+        && transfer.memo.as_deref().map(|m| m.as_slice()) == memo
+}
+
+// The default account may be written with or without the all-zero subaccount
+fn same_account(a: &Account, b: &Account) -> bool {
+    a.owner == b.owner
+        && normalize_subaccount(a.subaccount.as_deref().map(|s| s.as_slice()))
+            == normalize_subaccount(b.subaccount.as_deref().map(|s| s.as_slice()))
+}
+
+async fn block_timestamp(block_index: u64) -> std::result::Result<u64, String> {
+    get_transactions(block_index, 1)
+        .await?
+        .transactions
+        .into_iter()
+        .find(|(index, _)| *index == block_index)
+        .map(|(_, transaction)| transaction.timestamp)
+        .ok_or_else(|| format!("block {} could not be read", block_index))
+}
+
+/// A ckBTC transfer as recorded on the ledger, decoded from either block format
+pub struct LedgerTransfer {
+    pub from: Account,
+    pub to: Account,
+    pub amount: u64,
+    pub memo: Option<Vec<u8>>,
+}
+
+/// Reads the transfer at `block_index`, wherever the ledger keeps it
+///
+/// Tries the ledger's `get_transactions` first and falls back to ICRC-3 `icrc3_get_blocks`,
+/// both following archive callbacks. Returns `None` for blocks that are not transfers.
+pub async fn get_transfer(block_index: u64) -> std::result::Result<Option<LedgerTransfer>, String> {
+    let legacy_error = match get_transactions(block_index, 1).await {
+        Ok(batch) => {
+            if block_index >= batch.log_length {
+                return Err(format!(
+                    "Invalid block index: {} (log_length: {})",
+                    block_index, batch.log_length
+                ));
+            }
+            match batch
+                .transactions
+                .into_iter()
+                .find(|(index, _)| *index == block_index)
+            {
+                Some((_, transaction)) => return decode_transaction(transaction),
+                None => "block not returned by get_transactions".to_string(),
+            }
+        }
+        Err(e) => e,
+    };
+
+    let block = get_icrc3_block(block_index).await.map_err(|e| {
+        format!(
+            "Block {} not found ({}; icrc3_get_blocks: {})",
+            block_index, legacy_error, e
+        )
+    })?;
+    decode_icrc3_transfer(&block)
+}
+
+fn decode_transaction(
+    transaction: Transaction,
+) -> std::result::Result<Option<LedgerTransfer>, String> {
+    let Some(transfer) = transaction.transfer else {
+        return Ok(None);
+    };
+    Ok(Some(LedgerTransfer {
+        amount: nat_to_u64(&transfer.amount)?,
+        from: transfer.from,
+        to: transfer.to,
+        memo: transfer.memo.map(|memo| memo.into_vec()),
+    }))
+}
+
+async fn get_icrc3_block(block_index: u64) -> std::result::Result<Icrc3Value, String> {
+    let args = vec![GetBlocksArgs {
+        start: candid::Nat::from(block_index),
+        length: candid::Nat::from(1u64),
+    }];
+    let (result,) = crate::ckbtc_ledger_service()
+        .icrc_3_get_blocks(args)
+        .await
+        .map_err(|e| format!("Failed to get blocks: {:?}", e))?;
+
+    if let Some(block) = take_block(result.blocks, block_index) {
+        return Ok(block);
+    }
+
+    for archived in result.archived_blocks {
+        let archived_result =
+            Call::unbounded_wait(archived.callback.0.principal, &archived.callback.0.method)
+                .with_arg(archived.args)
+                .await
+                .map_err(|e| format!("Archive call failed: {}", e))?
+                .candid::<GetBlocksResult>()
+                .map_err(|e| format!("Failed to decode archived blocks: {}", e))?;
+
+        if let Some(block) = take_block(archived_result.blocks, block_index) {
+            return Ok(block);
+        }
+    }
+
+    Err(format!(
+        "block not returned (log_length: {})",
+        result.log_length
+    ))
+}
+
+fn take_block(
+    blocks: Vec<crate::ousg_ledger::GetBlocksResultBlocksItem>,
+    block_index: u64,
+) -> Option<Icrc3Value> {
+    blocks
+        .into_iter()
+        .find(|item| nat_to_u64(&item.id).is_ok_and(|id| id == block_index))
+        .map(|item| *item.block)
+}
+
+// ICRC-3 blocks of an ICRC-1 ledger: {tx: {op, from, to, amt, memo, ...}, btype?, ...}
+fn decode_icrc3_transfer(
+    block: &Icrc3Value,
+) -> std::result::Result<Option<LedgerTransfer>, String> {
+    let block = as_map(block).ok_or("Block is not a map")?;
+    let tx = field(block, "tx")
+        .and_then(as_map)
+        .ok_or("Block has no tx")?;
+
+    let op = field(tx, "op")
+        .or_else(|| field(block, "btype"))
+        .and_then(as_text);
+    if !matches!(op, Some("xfer" | "1xfer" | "2xfer")) {
+        return Ok(None);
+    }
+
+    let from = field(tx, "from")
+        .and_then(decode_account)
+        .ok_or("Transfer has no valid from account")?;
+    let to = field(tx, "to")
+        .and_then(decode_account)
+        .ok_or("Transfer has no valid to account")?;
+    let amount = match field(tx, "amt") {
+        Some(Icrc3Value::Nat(amount)) => nat_to_u64(amount)?,
+        _ => return Err("Transfer has no amount".to_string()),
+    };
+    let memo = match field(tx, "memo") {
+        Some(Icrc3Value::Blob(memo)) => Some(memo.to_vec()),
+        Some(_) => return Err("Transfer memo is not a blob".to_string()),
+        None => None,
+    };
+
+    Ok(Some(LedgerTransfer {
+        from,
+        to,
+        amount,
+        memo,
+    }))
+}
+
+// Accounts are encoded as [owner] or [owner, subaccount]
+fn decode_account(value: &Icrc3Value) -> Option<Account> {
+    let Icrc3Value::Array(parts) = value else {
+        return None;
+    };
+    let owner = match parts.first().map(|part| part.as_ref()) {
+        Some(Icrc3Value::Blob(owner)) => Principal::try_from_slice(owner).ok()?,
+        _ => return None,
+    };
+    let subaccount = match parts.get(1).map(|part| part.as_ref()) {
+        Some(Icrc3Value::Blob(subaccount)) if subaccount.len() == 32 => Some(subaccount.clone()),
+        Some(_) => return None,
+        None => None,
+    };
+    Some(Account { owner, subaccount })
+}
+
+fn field<'a>(map: &'a [(String, Box<Icrc3Value>)], name: &str) -> Option<&'a Icrc3Value> {
+    map.iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_ref())
+}
+
+fn as_map(value: &Icrc3Value) -> Option<&[(String, Box<Icrc3Value>)]> {
+    match value {
+        Icrc3Value::Map(map) => Some(map),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Icrc3Value) -> Option<&str> {
+    match value {
+        Icrc3Value::Text(text) => Some(text),
+        _ => None,
+    }
+}
+
+fn nat_to_u64(value: &candid::Nat) -> std::result::Result<u64, String> {
+    crate::utils::nat_to_u64(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_bytes::ByteBuf;
+
+    fn blob(bytes: &[u8]) -> Box<Icrc3Value> {
+        Box::new(Icrc3Value::Blob(ByteBuf::from(bytes.to_vec())))
+    }
+
+    fn text(text: &str) -> Box<Icrc3Value> {
+        Box::new(Icrc3Value::Text(text.to_string()))
+    }
+
+    fn nat(value: u128) -> Box<Icrc3Value> {
+        Box::new(Icrc3Value::Nat(candid::Nat::from(value)))
+    }
+
+    fn map(fields: Vec<(&str, Box<Icrc3Value>)>) -> Icrc3Value {
+        Icrc3Value::Map(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    fn account(owner: &Principal, subaccount: Option<&[u8]>) -> Box<Icrc3Value> {
+        let mut parts = vec![blob(owner.as_slice())];
+        parts.extend(subaccount.map(blob));
+        Box::new(Icrc3Value::Array(parts))
+    }
+
+    fn alice() -> Principal {
+        Principal::from_slice(&[1; 29])
+    }
+
+    fn bob() -> Principal {
+        Principal::from_slice(&[2; 29])
+    }
+
+    fn transfer_block(tx: Vec<(&str, Box<Icrc3Value>)>) -> Icrc3Value {
+        map(vec![
+            ("phash", blob(&[0; 32])),
+            ("ts", nat(1_700_000_000_000_000_000)),
+            ("tx", Box::new(map(tx))),
+        ])
+    }
+
+    #[test]
+    fn decodes_a_transfer() {
+        let subaccount = [7u8; 32];
+        let block = transfer_block(vec![
+            ("op", text("xfer")),
+            ("from", account(&alice(), None)),
+            ("to", account(&bob(), Some(&subaccount))),
+            ("amt", nat(150_000)),
+            ("memo", blob(&[2, 1, 0, 3])),
+        ]);
+
+        let transfer = decode_icrc3_transfer(&block).unwrap().unwrap();
+        assert_eq!(transfer.from.owner, alice());
+        assert_eq!(transfer.from.subaccount, None);
+        assert_eq!(transfer.to.owner, bob());
+        assert_eq!(
+            transfer.to.subaccount.as_ref().map(|s| s.as_slice()),
+            Some(subaccount.as_slice())
+        );
+        assert_eq!(transfer.amount, 150_000);
+        assert_eq!(transfer.memo, Some(vec![2, 1, 0, 3]));
+    }
+
+    #[test]
+    fn reads_the_operation_from_btype() {
+        let block = map(vec![
+            ("btype", text("1xfer")),
+            (
+                "tx",
+                Box::new(map(vec![
+                    ("from", account(&alice(), None)),
+                    ("to", account(&bob(), None)),
+                    ("amt", nat(1)),
+                ])),
+            ),
+        ]);
+
+        let transfer = decode_icrc3_transfer(&block).unwrap().unwrap();
+        assert_eq!(transfer.amount, 1);
+        assert_eq!(transfer.memo, None);
+    }
+
+    #[test]
+    fn other_operations_are_not_transfers() {
+        for op in ["mint", "burn", "approve"] {
+            let block = transfer_block(vec![
+                ("op", text(op)),
+                ("to", account(&bob(), None)),
+                ("amt", nat(1)),
+            ]);
+            assert!(decode_icrc3_transfer(&block).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn malformed_transfers_are_errors() {
+        assert!(decode_icrc3_transfer(&Icrc3Value::Text("block".to_string())).is_err());
+        assert!(decode_icrc3_transfer(&map(vec![("ts", nat(1))])).is_err());
+
+        let without_from = transfer_block(vec![
+            ("op", text("xfer")),
+            ("to", account(&bob(), None)),
+            ("amt", nat(1)),
+        ]);
+        assert!(decode_icrc3_transfer(&without_from).is_err());
+
+        let text_memo = transfer_block(vec![
+            ("op", text("xfer")),
+            ("from", account(&alice(), None)),
+            ("to", account(&bob(), None)),
+            ("amt", nat(1)),
+            ("memo", text("memo")),
+        ]);
+        assert!(decode_icrc3_transfer(&text_memo).is_err());
+
+        // Amounts beyond u64 are rejected rather than truncated
+        let huge_amount = transfer_block(vec![
+            ("op", text("xfer")),
+            ("from", account(&alice(), None)),
+            ("to", account(&bob(), None)),
+            ("amt", nat(u64::MAX as u128 + 1)),
+        ]);
+        assert!(decode_icrc3_transfer(&huge_amount).is_err());
+    }
+
+    #[test]
+    fn decodes_accounts() {
+        let plain = decode_account(&account(&alice(), None)).unwrap();
+        assert_eq!(plain.owner, alice());
+        assert_eq!(plain.subaccount, None);
+
+        let with_subaccount = decode_account(&account(&alice(), Some(&[9; 32]))).unwrap();
+        assert_eq!(
+            with_subaccount.subaccount.as_ref().map(|s| s.as_slice()),
+            Some([9u8; 32].as_slice())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_accounts() {
+        // Subaccounts must be exactly 32 bytes
+        assert!(decode_account(&account(&alice(), Some(&[9; 31]))).is_none());
+        assert!(decode_account(&Icrc3Value::Array(vec![])).is_none());
+        assert!(decode_account(&Icrc3Value::Array(vec![text("owner")])).is_none());
+        assert!(decode_account(&Icrc3Value::Array(vec![blob(&[0; 30])])).is_none());
+        assert!(decode_account(&blob(alice().as_slice())).is_none());
+        assert!(
+            decode_account(&Icrc3Value::Array(vec![blob(alice().as_slice()), nat(1)])).is_none()
+        );
+    }
+
+    fn plain_account(owner: Principal, subaccount: Option<[u8; 32]>) -> Account {
+        Account {
+            owner,
+            subaccount: subaccount.map(|subaccount| ByteBuf::from(subaccount.to_vec())),
+        }
+    }
+
+    fn sent_transfer(from_subaccount: Option<[u8; 32]>, memo: Option<&[u8]>) -> Transfer {
+        Transfer {
+            to: plain_account(alice(), None),
+            fee: None,
+            from: plain_account(bob(), from_subaccount),
+            memo: memo.map(|memo| ByteBuf::from(memo.to_vec())),
+            created_at_time: Some(42),
+            amount: candid::Nat::from(1_000u64),
+            spender: None,
+        }
+    }
+
+    #[test]
+    fn matches_sent_transfers_by_sender_recipient_memo_and_key() {
+        let sweep = sent_transfer(Some([7; 32]), None);
+        let from = plain_account(bob(), Some([7; 32]));
+        // The default account matches with or without the all-zero subaccount
+        let to = plain_account(alice(), Some([0; 32]));
+
+        assert!(is_sent_transfer(&sweep, &from, &to, None, 42));
+        assert!(!is_sent_transfer(&sweep, &from, &to, None, 43));
+        assert!(!is_sent_transfer(
+            &sweep,
+            &from,
+            &to,
+            Some(&[2, 1, 0, 3]),
+            42
+        ));
+        assert!(!is_sent_transfer(
+            &sweep,
+            &plain_account(bob(), Some([8; 32])),
+            &to,
+            None,
+            42
+        ));
+        assert!(!is_sent_transfer(
+            &sweep,
+            &from,
+            &plain_account(bob(), None),
+            None,
+            42
+        ));
+
+        let payout = sent_transfer(None, Some(&[2, 1, 0, 3]));
+        let from = plain_account(bob(), None);
+        assert!(is_sent_transfer(
+            &payout,
+            &from,
+            &to,
+            Some(&[2, 1, 0, 3]),
+            42
+        ));
+        assert!(!is_sent_transfer(&payout, &from, &to, None, 42));
+    }
+}
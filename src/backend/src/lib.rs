@@ -27,12 +27,22 @@ fn custom_getrandom(buf: &mut [u8]) -> std::result::Result<(), getrandom::Error>
 register_custom_getrandom!(custom_getrandom);
 
 // Module declarations
+mod audit_log;
+mod deposit_reconciler;
+mod deposit_scanner;
 mod errors;
+mod evm_fees;
 mod evm_rpc;
+mod evm_tx_tracker;
 mod exchange_rate_canister;
 mod guard;
 mod handlers;
+mod ledger_blocks;
+mod multisig;
 mod ousg_ledger;
+mod price_history;
+mod price_oracle;
+mod quotes;
 mod storage;
 mod types;
 mod utils;
@@ -46,25 +56,31 @@ pub use types::*;
 // get_current_timestamp is available through storage::* re-export
 use candid::Principal;
 use evm_rpc::{
-    BlockTag, EthMainnetService, GetBlockByNumberResult, MultiGetBlockByNumberResult, RpcConfig,
-    RpcServices, Service as EvmRpcService,
+    BlockTag, GetTransactionCountArgs, GetTransactionCountResult, MultiGetTransactionCountResult,
+    MultiSendRawTransactionResult, SendRawTransactionResult, SendRawTransactionStatus,
+    Service as EvmRpcService,
 };
-use exchange_rate_canister::{Service as ExchangeRateService, *};
-use ic_cdk::api::call::call_with_payment;
+use exchange_rate_canister::Service as ExchangeRateService;
 use ic_cdk::call::Call;
 use ic_cdk::{query, update};
-use ousg_ledger::{Service as OusgLedgerService, *};
+use ousg_ledger::{Service as CkbtcLedgerService, Service as OusgLedgerService, *};
 
-const FILE_STORE_BUCKET_CANISTER_ID: &str = "uzt4z-lp777-77774-qaabq-cai";
+// External canister clients, ids come from the install arguments (see CanisterIdsStorage)
+fn ousg_ledger_service() -> OusgLedgerService {
+    OusgLedgerService(CanisterIdsStorage::get().ousg_ledger)
+}
 
-// EVM RPC service instance - using lazy_static for runtime initialization
-use std::sync::LazyLock;
+fn ckbtc_ledger_service() -> CkbtcLedgerService {
+    CkbtcLedgerService(CanisterIdsStorage::get().ckbtc_ledger)
+}
 
-static EVM_RPC: LazyLock<EvmRpcService> = LazyLock::new(|| {
-    EvmRpcService(
-        Principal::from_text("uxrrr-q7777-77774-qaaaq-cai").expect("Invalid EVM RPC principal"),
-    )
-});
+fn xrc_service() -> ExchangeRateService {
+    ExchangeRateService(CanisterIdsStorage::get().xrc)
+}
+
+pub(crate) fn evm_rpc_service() -> EvmRpcService {
+    EvmRpcService(CanisterIdsStorage::get().evm_rpc)
+}
 
 // get_active_ustbills removed - USTBill functionality not implemented
 
@@ -110,7 +126,7 @@ pub async fn register_user(user_data: UserRegistrationRequest) -> Result<User> {
 
     // Register user to the file store bucket, so user can upload kyc documents to it
     let _ = Call::unbounded_wait(
-        Principal::from_text(FILE_STORE_BUCKET_CANISTER_ID).expect("Invalid initial principal"),
+        CanisterIdsStorage::get().file_store_bucket,
         "register_user_by_backend",
     )
     .with_arg(user.principal)
@@ -202,6 +218,91 @@ pub fn get_authorized_principals() -> Vec<Principal> {
     guard::get_list()
 }
 
+/// Gets the roles held by the caller
+#[query]
+pub fn get_my_roles() -> Vec<Role> {
+    guard::get_roles(&ic_cdk::api::msg_caller())
+}
+
+/// Lists every principal with its roles
+#[query]
+pub fn admin_get_role_assignments() -> Result<Vec<RoleAssignment>> {
+    guard::assert_role(Role::Auditor)?;
+    Ok(RoleStorage::get_all())
+}
+
+/// Grant a role to a principal, approver roles need a GrantRole treasury proposal
+#[update]
+pub fn admin_grant_role(principal: Principal, role: Role) -> Result<()> {
+    guard::assert_admin()?;
+    assert_not_approver_role(role)?;
+    let before = guard::get_roles(&principal);
+    guard::grant_role(principal, role)?;
+    audit_log::record(
+        AuditAction::RoleGranted,
+        principal,
+        audit_log::snapshot(&before),
+        audit_log::snapshot(&guard::get_roles(&principal)),
+    );
+    Ok(())
+}
+
+/// Revoke a role from a principal, approver roles need a RevokeRole treasury proposal
+#[update]
+pub fn admin_revoke_role(principal: Principal, role: Role) -> Result<()> {
+    guard::assert_admin()?;
+    assert_not_approver_role(role)?;
+    let before = guard::get_roles(&principal);
+    guard::revoke_role(principal, role)?;
+    audit_log::record(
+        AuditAction::RoleRevoked,
+        principal,
+        audit_log::snapshot(&before),
+        audit_log::snapshot(&guard::get_roles(&principal)),
+    );
+    Ok(())
+}
+
+// A single SuperAdmin must not be able to change who approves treasury proposals
+fn assert_not_approver_role(role: Role) -> Result<()> {
+    if role.approves_treasury() {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "The {:?} role can only be changed through a treasury proposal",
+            role
+        )));
+    }
+    Ok(())
+}
+
+/// Page through the admin audit log, newest first
+///
+/// Start without `before_id` and pass the page's `next_before_id` for the next one. A page can
+/// hold fewer than `limit` entries and still have a next page when the filter is selective.
+#[query]
+pub fn admin_get_audit_log(
+    filter: Option<AuditLogFilter>,
+    before_id: Option<u64>,
+    limit: u64,
+) -> Result<AuditLogPage> {
+    guard::assert_role(Role::Auditor)?;
+    let limit = limit.clamp(1, MAX_AUDIT_LOG_PAGE_SIZE);
+    Ok(AuditLogStorage::query(
+        &filter.unwrap_or_default(),
+        before_id,
+        limit,
+    ))
+}
+
+/// Get a single audit log entry
+#[query]
+pub fn admin_get_audit_entry(id: u64) -> Result<AuditEntry> {
+    guard::assert_role(Role::Auditor)?;
+    AuditLogStorage::get(id)
+}
+
+// Keeps audit log responses well below the query reply size limit
+const MAX_AUDIT_LOG_PAGE_SIZE: u64 = 100;
+
 /// Manual review functions for admins
 #[update]
 pub async fn admin_review_free_kyc(
@@ -209,9 +310,10 @@ pub async fn admin_review_free_kyc(
     approved: bool,
     notes: Option<String>,
 ) -> Result<()> {
-    // guard::assert_admin()?; // Temporarily disabled for testing
+    guard::assert_role(Role::KycReviewer)?;
 
     let mut kyc_session = FreeKYCStorage::get(&upload_id)?;
+    let previous_status = kyc_session.status.clone();
 
     kyc_session.status = if approved {
         FreeKYCStatus::ManualApproved
@@ -238,14 +340,26 @@ pub async fn admin_review_free_kyc(
         );
     }
 
-    FreeKYCStorage::update(upload_id, kyc_session)?;
+    let action = if approved {
+        AuditAction::KycApproved
+    } else {
+        AuditAction::KycRejected
+    };
+    let after = audit_log::snapshot(&kyc_session.status);
+    FreeKYCStorage::update(upload_id.clone(), kyc_session)?;
+    audit_log::record(
+        action,
+        upload_id,
+        audit_log::snapshot(&previous_status),
+        after,
+    );
     Ok(())
 }
 
 /// Get pending manual reviews for admins
 #[query]
 pub fn admin_get_pending_reviews() -> Result<Vec<UserAndFreeKYCSession>> {
-    // guard::assert_admin()?; // Temporarily disabled for testing
+    guard::assert_role(Role::KycReviewer)?;
 
     let all_sessions = FreeKYCStorage::get_all();
     let pending: Vec<UserAndFreeKYCSession> = all_sessions
@@ -272,7 +386,7 @@ pub fn get_free_kyc_status(upload_id: String) -> Result<FreeKYCSession> {
     // Only allow user or admin to check status
     let caller = ic_cdk::api::msg_caller();
     if caller != session.user_principal {
-        guard::assert_admin()?;
+        guard::assert_any_role(&[Role::KycReviewer, Role::Auditor])?;
     }
 
     Ok(session)
@@ -283,8 +397,7 @@ pub fn get_free_kyc_status(upload_id: String) -> Result<FreeKYCSession> {
 /// //////////////////////////////////////////////////////////////
 /// //////////////////////////////////////////////////////////////
 use candid::CandidType;
-#[allow(deprecated)]
-use ic_cdk::api::management_canister::ecdsa::{EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgument};
+use ic_cdk::management_canister::{EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgs, SignWithEcdsaArgs};
 use serde::{Deserialize, Serialize};
 
 #[derive(CandidType, Serialize, Deserialize)]
@@ -294,38 +407,85 @@ pub struct PublicKeyReply {
 }
 
 #[update]
-#[allow(deprecated)]
 async fn get_eth_address() -> std::result::Result<PublicKeyReply, String> {
-    let request = EcdsaPublicKeyArgument {
-        canister_id: None,
-        derivation_path: vec![], // Use [] for canister root key
-        key_id: EcdsaKeyId {
-            curve: EcdsaCurve::Secp256k1,
-            name: "key_1".to_string(), // Use "key_1" for mainnet, "test_key_1" for testnet
-        },
-    };
-
-    let (response,) = ic_cdk::api::call::call::<
-        _,
-        (ic_cdk::api::management_canister::ecdsa::EcdsaPublicKeyResponse,),
-    >(
-        Principal::from_text("aaaaa-aa").expect("Invalid management canister principal"),
-        "ecdsa_public_key",
-        (request,),
-    )
-    .await
-    .map_err(|e| format!("ecdsa_public_key failed: {:?}", e.1))?;
+    // Use [] for canister root key
+    let pubkey_bytes = ecdsa_public_key(ecdsa_key_id(&EvmConfigStorage::get()), vec![]).await?;
 
     // Convert SEC1 public key to Ethereum address
-    let pubkey_bytes = &response.public_key;
-    let eth_address = pubkey_bytes_to_address(pubkey_bytes);
+    let eth_address = pubkey_bytes_to_address(&pubkey_bytes);
 
     Ok(PublicKeyReply {
-        public_key_hex: hex::encode(pubkey_bytes),
+        public_key_hex: hex::encode(&pubkey_bytes),
         eth_address,
     })
 }
 
+/// Derive (and remember) the caller's own Ethereum address
+#[update]
+pub async fn derive_my_eth_address() -> Result<String> {
+    let caller = ic_cdk::api::msg_caller();
+    if caller == Principal::anonymous() {
+        return Err(BitcoinUSTBillsError::AnonymousCaller);
+    }
+
+    if let Some(address) = EthAddressStorage::get(&caller) {
+        return Ok(address);
+    }
+
+    let key_id = ecdsa_key_id(&EvmConfigStorage::get());
+    let key_name = key_id.name.clone();
+    let pubkey_bytes = ecdsa_public_key(key_id, user_derivation_path(&caller))
+        .await
+        .map_err(BitcoinUSTBillsError::StorageError)?;
+    let eth_address = pubkey_bytes_to_address(&pubkey_bytes);
+
+    // Not cached if the key was switched while the public key was being fetched
+    if EvmConfigStorage::get().ecdsa_key_name == key_name {
+        EthAddressStorage::insert(caller, eth_address.clone())?;
+    }
+    Ok(eth_address)
+}
+
+/// Get the caller's Ethereum deposit and withdrawal address
+#[query]
+pub fn get_my_eth_address() -> Result<String> {
+    let caller = ic_cdk::api::msg_caller();
+    EthAddressStorage::get(&caller).ok_or(BitcoinUSTBillsError::StorageError(
+        "Ethereum address not derived yet, call derive_my_eth_address first".to_string(),
+    ))
+}
+
+// Each principal signs with its own key: derivation path = principal bytes
+fn user_derivation_path(principal: &Principal) -> Vec<Vec<u8>> {
+    vec![principal.as_slice().to_vec()]
+}
+
+// Threshold ECDSA key used for all Ethereum signing, selected by the EVM config
+fn ecdsa_key_id(config: &EvmConfig) -> EcdsaKeyId {
+    EcdsaKeyId {
+        curve: EcdsaCurve::Secp256k1,
+        name: config.ecdsa_key_name.clone(),
+    }
+}
+
+/// Fetch the SEC1 encoded threshold ECDSA public key for a derivation path
+async fn ecdsa_public_key(
+    key_id: EcdsaKeyId,
+    derivation_path: Vec<Vec<u8>>,
+) -> std::result::Result<Vec<u8>, String> {
+    let request = EcdsaPublicKeyArgs {
+        canister_id: None,
+        derivation_path,
+        key_id,
+    };
+
+    let response = ic_cdk::management_canister::ecdsa_public_key(&request)
+        .await
+        .map_err(|e| format!("ecdsa_public_key failed: {:?}", e))?;
+
+    Ok(response.public_key)
+}
+
 // Helper function: convert SEC1 public key to Ethereum address
 fn pubkey_bytes_to_address(pubkey_bytes: &[u8]) -> String {
     use ethers_core::k256::elliptic_curve::sec1::ToEncodedPoint;
@@ -346,39 +506,16 @@ fn pubkey_bytes_to_address(pubkey_bytes: &[u8]) -> String {
 /// Get latest block number
 #[update]
 pub async fn get_latest_block_number() -> String {
-    let rpc_providers = RpcServices::EthMainnet(Some(vec![EthMainnetService::Alchemy]));
-
-    // Use ic_cdk::call with cycles
-    let cycles: u64 = 2_000_000_000_000; // 2T cycles
-    match call_with_payment::<_, (MultiGetBlockByNumberResult,)>(
-        EVM_RPC.0,
-        "eth_getBlockByNumber",
-        (rpc_providers, None::<RpcConfig>, BlockTag::Latest),
-        cycles,
-    )
-    .await
-    {
-        Ok((result,)) => match result {
-            MultiGetBlockByNumberResult::Consistent(r) => match r {
-                GetBlockByNumberResult::Ok(block) => {
-                    format!("Latest block: {}", block.number)
-                }
-                GetBlockByNumberResult::Err(err) => {
-                    format!("Failed to get block: {:?}", err)
-                }
-            },
-            MultiGetBlockByNumberResult::Inconsistent(_) => {
-                "RPC providers gave inconsistent results".to_string()
-            }
-        },
-        Err(err) => {
-            format!("Call failed: {:?}", err)
-        }
+    let evm_config = EvmConfigStorage::get();
+    match evm_tx_tracker::get_latest_block_number(&evm_config).await {
+        Ok(block_number) => format!("Latest block: {}", block_number),
+        Err(e) => format!("Failed to get block: {}", e),
     }
 }
 
 // ERC-20 Transfer Implementation
-use ethers_core::types::{Address, Bytes, Eip1559TransactionRequest, U256};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{Address, Bytes, Eip1559TransactionRequest, Signature, U256};
 use std::str::FromStr;
 
 #[derive(CandidType, Serialize, Deserialize, Clone)]
@@ -386,6 +523,7 @@ pub struct TransferRequest {
     pub contract_address: String,
     pub recipient: String,
     pub amount: String, // Amount as string to avoid precision issues
+    pub from_caller_address: Option<bool>, // Sign from the caller's derived address instead of the canister's
 }
 
 #[derive(CandidType, Serialize, Deserialize)]
@@ -393,16 +531,16 @@ pub struct TransferResponse {
     pub success: bool,
     pub transaction_hash: Option<String>,
     pub error_message: Option<String>,
+    pub evm_transaction_id: Option<u64>, // Tracking record, see get_evm_transaction
 }
 
 /// Encode ERC-20 transfer function call
-fn encode_transfer(to: &str, value: U256) -> String {
-    let mut data = hex::decode("a9059cbb").unwrap(); // transfer(address,uint256) selector
+fn encode_transfer(to: Address, value: U256) -> Vec<u8> {
+    let mut data = vec![0xa9, 0x05, 0x9c, 0xbb]; // transfer(address,uint256) selector
 
     // Pad recipient address to 32 bytes
     let mut to_bytes = [0u8; 32];
-    let addr = hex::decode(&to[2..]).unwrap(); // Remove "0x" prefix
-    to_bytes[12..].copy_from_slice(&addr); // Address goes in last 20 bytes
+    to_bytes[12..].copy_from_slice(to.as_bytes()); // Address goes in last 20 bytes
 
     data.extend_from_slice(&to_bytes);
 
@@ -411,453 +549,1195 @@ fn encode_transfer(to: &str, value: U256) -> String {
     value.to_big_endian(&mut value_bytes);
     data.extend_from_slice(&value_bytes);
 
-    format!("0x{}", hex::encode(data))
+    data
 }
 
 /// Sign EIP-1559 transaction using threshold ECDSA
+///
+/// Returns the broadcastable raw transaction: `0x02 || rlp([...fields, y_parity, r, s])`.
 async fn sign_eip1559_transaction(
-    _tx: Eip1559TransactionRequest,
-    _key_id: EcdsaKeyId,
-    _derivation_path: Vec<Vec<u8>>,
+    tx: Eip1559TransactionRequest,
+    key_id: EcdsaKeyId,
+    derivation_path: Vec<Vec<u8>>,
 ) -> std::result::Result<Bytes, String> {
-    // This is a simplified implementation
-    // In production, you would use the full signing logic from the starter project
+    let typed_tx = TypedTransaction::Eip1559(tx);
+    let sighash = typed_tx.sighash();
+
+    // Public key of the signer, needed to recover the y-parity of the signature
+    let public_key = ecdsa_public_key(key_id.clone(), derivation_path.clone()).await?;
+
+    let request = SignWithEcdsaArgs {
+        message_hash: sighash.as_bytes().to_vec(),
+        derivation_path,
+        key_id,
+    };
+    let response = ic_cdk::management_canister::sign_with_ecdsa(&request)
+        .await
+        .map_err(|e| format!("sign_with_ecdsa failed: {:?}", e))?;
+
+    let signature = to_eth_signature(&response.signature, sighash.as_bytes(), &public_key)?;
+    Ok(typed_tx.rlp_signed(&signature))
+}
+
+/// Convert a 64-byte `r || s` threshold ECDSA signature into an Ethereum signature
+///
+/// The management canister does not return a recovery id, so both candidates are tried
+/// and the one recovering the signer's public key becomes the y-parity.
+fn to_eth_signature(
+    signature_bytes: &[u8],
+    message_hash: &[u8],
+    public_key: &[u8],
+) -> std::result::Result<Signature, String> {
+    use ethers_core::k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, VerifyingKey};
+
+    let mut signature = EcdsaSignature::from_slice(signature_bytes)
+        .map_err(|e| format!("Invalid ECDSA signature: {}", e))?;
+    // Ethereum only accepts low-s signatures
+    if let Some(normalized) = signature.normalize_s() {
+        signature = normalized;
+    }
+
+    let expected_key = VerifyingKey::from_sec1_bytes(public_key)
+        .map_err(|e| format!("Invalid ECDSA public key: {}", e))?;
+
+    let y_parity = (0u8..=1)
+        .find(|parity| {
+            RecoveryId::from_byte(*parity)
+                .and_then(|recovery_id| {
+                    VerifyingKey::recover_from_prehash(message_hash, &signature, recovery_id).ok()
+                })
+                .is_some_and(|recovered| recovered == expected_key)
+        })
+        .ok_or("Failed to recover y-parity: signature does not match public key")?;
+
+    let (r, s) = signature.split_bytes();
+    Ok(Signature {
+        r: U256::from_big_endian(&r),
+        s: U256::from_big_endian(&s),
+        v: y_parity as u64,
+    })
+}
+
+// Cycles attached to EVM RPC canister calls; unused cycles are refunded
+const EVM_RPC_CYCLES: u128 = 50_000_000_000;
+
+/// Fetch the transaction count (nonce) of an Ethereum address at the given block
+async fn get_transaction_count(
+    address: &str,
+    block: BlockTag,
+    evm_config: &EvmConfig,
+    evm_rpc: &EvmRpcService,
+) -> std::result::Result<U256, String> {
+    let args = GetTransactionCountArgs {
+        address: address.to_string(),
+        block,
+    };
+
+    let (result,) = evm_rpc
+        .eth_get_transaction_count(
+            evm_config.rpc_services.clone(),
+            evm_config.rpc_config(),
+            args,
+            EVM_RPC_CYCLES,
+        )
+        .await
+        .map_err(|e| format!("eth_getTransactionCount call failed: {:?}", e))?;
 
-    // For now, return a placeholder
-    Ok(Bytes::from(vec![0u8; 32]))
+    match result {
+        MultiGetTransactionCountResult::Consistent(GetTransactionCountResult::Ok(count)) => {
+            U256::from_dec_str(&count.0.to_string())
+                .map_err(|e| format!("Invalid transaction count {}: {}", count, e))
+        }
+        MultiGetTransactionCountResult::Consistent(GetTransactionCountResult::Err(e)) => {
+            Err(format!("eth_getTransactionCount failed: {:?}", e))
+        }
+        MultiGetTransactionCountResult::Inconsistent(_) => {
+            Err("RPC providers returned inconsistent nonces".to_string())
+        }
+    }
 }
 
 /// Send raw transaction to Ethereum network
 async fn send_raw_transaction(
-    _signed_tx: Bytes,
-    _rpc_services: RpcServices,
-    _evm_rpc: &EvmRpcService,
-) -> std::result::Result<String, String> {
-    // This is a simplified implementation
-    // In production, you would use the full EVM RPC call
+    signed_tx: Bytes,
+    evm_config: &EvmConfig,
+    evm_rpc: &EvmRpcService,
+) -> std::result::Result<RawTransactionSubmission, String> {
+    // Hash is known locally, providers may return `Ok(None)` instead of echoing it
+    let local_hash = format!(
+        "0x{}",
+        hex::encode(ethers_core::utils::keccak256(&signed_tx))
+    );
+
+    let (result,) = evm_rpc
+        .eth_send_raw_transaction(
+            evm_config.rpc_services.clone(),
+            evm_config.rpc_config(),
+            format!("0x{}", hex::encode(&signed_tx)),
+            EVM_RPC_CYCLES,
+        )
+        .await
+        .map_err(|e| format!("eth_sendRawTransaction call failed: {:?}", e))?;
+
+    interpret_send_raw_transaction_result(result, local_hash)
+}
+
+fn interpret_send_raw_transaction_result(
+    result: MultiSendRawTransactionResult,
+    local_hash: String,
+) -> std::result::Result<RawTransactionSubmission, String> {
+    match result {
+        MultiSendRawTransactionResult::Consistent(SendRawTransactionResult::Ok(status)) => {
+            Ok(map_send_raw_transaction_status(status, local_hash))
+        }
+        MultiSendRawTransactionResult::Consistent(SendRawTransactionResult::Err(e)) => {
+            Err(format!("eth_sendRawTransaction failed: {:?}", e))
+        }
+        MultiSendRawTransactionResult::Inconsistent(results) => {
+            // Once one provider accepted the transaction the others typically report
+            // NonceTooLow, so any acceptance means the transaction is in the mempool
+            let accepted = results.iter().any(|(_, result)| {
+                matches!(
+                    result,
+                    SendRawTransactionResult::Ok(SendRawTransactionStatus::Ok(_))
+                )
+            });
+            if accepted {
+                Ok(RawTransactionSubmission::Submitted {
+                    transaction_hash: local_hash,
+                })
+            } else {
+                Err(format!(
+                    "RPC providers gave inconsistent results ({} responses, none accepted)",
+                    results.len()
+                ))
+            }
+        }
+    }
+}
+
+fn map_send_raw_transaction_status(
+    status: SendRawTransactionStatus,
+    local_hash: String,
+) -> RawTransactionSubmission {
+    match status {
+        SendRawTransactionStatus::Ok(hash) => RawTransactionSubmission::Submitted {
+            transaction_hash: hash.unwrap_or(local_hash),
+        },
+        SendRawTransactionStatus::NonceTooLow => RawTransactionSubmission::NonceTooLow,
+        SendRawTransactionStatus::NonceTooHigh => RawTransactionSubmission::NonceTooHigh,
+        SendRawTransactionStatus::InsufficientFunds => RawTransactionSubmission::InsufficientFunds,
+    }
+}
+
+/// Get the EVM RPC providers configuration
+#[query]
+pub fn get_evm_config() -> EvmConfig {
+    EvmConfigStorage::get()
+}
+
+// Update the EVM RPC providers configuration, runs from an approved treasury proposal
+fn set_evm_config(config: EvmConfig) -> Result<()> {
+    config
+        .validate()
+        .map_err(BitcoinUSTBillsError::ValidationError)?;
+    let before = EvmConfigStorage::get();
+    EvmConfigStorage::update(config.clone())?;
+    audit_log::record(
+        AuditAction::EvmConfigUpdated,
+        "evm_config",
+        audit_log::snapshot(&before),
+        audit_log::snapshot(&config),
+    );
+    Ok(())
+}
 
-    // For now, return a placeholder transaction hash
-    Ok("0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string())
+// Select the ECDSA key, chain id and RPC providers used for every EVM call,
+// runs from an approved treasury proposal
+fn set_evm_network(args: EvmNetworkArgs) -> Result<EvmConfig> {
+    let before = EvmConfigStorage::get();
+    let mut config = before.clone();
+    config
+        .apply_network_args(args)
+        .map_err(BitcoinUSTBillsError::ValidationError)?;
+    EvmConfigStorage::update(config.clone())?;
+    audit_log::record(
+        AuditAction::EvmNetworkUpdated,
+        "evm_config",
+        audit_log::snapshot(&before),
+        audit_log::snapshot(&config),
+    );
+    Ok(config)
 }
 
-/// Transfer ERC-20 tokens
+/// Transfer ERC-20 tokens from the caller's derived address
 #[update]
 pub async fn transfer_erc20_tokens(request: TransferRequest) -> TransferResponse {
-    // Validate inputs
-    if request.contract_address.is_empty()
-        || request.recipient.is_empty()
-        || request.amount.is_empty()
-    {
-        return TransferResponse {
-            success: false,
-            transaction_hash: None,
-            error_message: Some("Invalid input parameters".to_string()),
-        };
-    }
+    let caller = ic_cdk::api::msg_caller();
 
-    // Parse amount
-    let amount = match U256::from_dec_str(&request.amount) {
-        Ok(amount) => amount,
-        Err(_) => {
+    let transfer = match validate_transfer_request(&request) {
+        Ok(transfer) => transfer,
+        Err(e) => {
             return TransferResponse {
                 success: false,
                 transaction_hash: None,
-                error_message: Some("Invalid amount format".to_string()),
+                evm_transaction_id: None,
+                error_message: Some(e),
             };
         }
     };
 
-    // Validate contract address format
-    if !request.contract_address.starts_with("0x") || request.contract_address.len() != 42 {
+    // Spending from the canister's own address is a treasury operation
+    if !request.from_caller_address.unwrap_or(false) {
         return TransferResponse {
             success: false,
             transaction_hash: None,
-            error_message: Some("Invalid contract address format".to_string()),
+            evm_transaction_id: None,
+            error_message: Some(
+                "Transfers from the canister address need an approved treasury proposal"
+                    .to_string(),
+            ),
         };
     }
 
-    // Validate recipient address format
-    if !request.recipient.starts_with("0x") || request.recipient.len() != 42 {
+    if caller == Principal::anonymous() {
         return TransferResponse {
             success: false,
             transaction_hash: None,
-            error_message: Some("Invalid recipient address format".to_string()),
+            evm_transaction_id: None,
+            error_message: Some("Anonymous caller has no derived address".to_string()),
         };
     }
 
+    submit_erc20_transfer(caller, request, transfer, user_derivation_path(&caller)).await
+}
+
+/// A transfer request with its addresses and amount parsed
+struct ParsedTransfer {
+    contract: Address,
+    recipient: Address,
+    amount: U256,
+}
+
+/// Validate a transfer request and parse its addresses and amount
+fn validate_transfer_request(
+    request: &TransferRequest,
+) -> std::result::Result<ParsedTransfer, String> {
+    // Validate inputs
+    if request.contract_address.is_empty()
+        || request.recipient.is_empty()
+        || request.amount.is_empty()
+    {
+        return Err("Invalid input parameters".to_string());
+    }
+
+    // Parse amount
+    let amount =
+        U256::from_dec_str(&request.amount).map_err(|_| "Invalid amount format".to_string())?;
+
+    // Validate contract address format
+    let contract = parse_address(&request.contract_address)
+        .ok_or_else(|| "Invalid contract address format".to_string())?;
+
+    // Validate recipient address format
+    let recipient = parse_address(&request.recipient)
+        .ok_or_else(|| "Invalid recipient address format".to_string())?;
+
+    Ok(ParsedTransfer {
+        contract,
+        recipient,
+        amount,
+    })
+}
+
+// A 0x-prefixed address of 40 hex digits
+fn parse_address(address: &str) -> Option<Address> {
+    if !address.starts_with("0x") || address.len() != 42 {
+        return None;
+    }
+    Address::from_str(address).ok()
+}
+
+thread_local! {
+    // EVM addresses with a transfer between nonce fetch and broadcast
+    static NONCE_LOCKS: std::cell::RefCell<std::collections::BTreeSet<String>> =
+        const { std::cell::RefCell::new(std::collections::BTreeSet::new()) };
+}
+
+// Held from fetching an address's pending nonce until its transaction is broadcast, so two
+// transfers cannot be signed with the same nonce. Released on drop.
+struct NonceLock(String);
+
+impl NonceLock {
+    fn acquire(address: &str) -> Option<Self> {
+        NONCE_LOCKS
+            .with(|locks| locks.borrow_mut().insert(address.to_lowercase()))
+            .then(|| NonceLock(address.to_lowercase()))
+    }
+}
+
+impl Drop for NonceLock {
+    fn drop(&mut self) {
+        NONCE_LOCKS.with(|locks| locks.borrow_mut().remove(&self.0));
+    }
+}
+
+/// Sign and broadcast a validated transfer, an empty derivation path signs from the canister address
+async fn submit_erc20_transfer(
+    caller: Principal,
+    request: TransferRequest,
+    transfer: ParsedTransfer,
+    derivation_path: Vec<Vec<u8>>,
+) -> TransferResponse {
+    let from_treasury = derivation_path.is_empty();
+
     // Encode the transfer function call
-    let data = encode_transfer(&request.recipient, amount);
+    let data = encode_transfer(transfer.recipient, transfer.amount);
 
-    // Build EIP-1559 transaction
-    let tx = Eip1559TransactionRequest {
-        from: None,
-        to: Some(ethers_core::types::NameOrAddress::Address(
-            Address::from_str(&request.contract_address).unwrap(),
-        )),
-        value: Some(U256::zero()), // No ETH sent, just token transfer
-        max_fee_per_gas: Some(U256::from(100_000_000_000u64)), // 100 gwei
-        max_priority_fee_per_gas: Some(U256::from(2_000_000_000u64)), // 2 gwei
-        gas: Some(U256::from(100_000u64)), // Gas limit
-        nonce: Some(U256::from(0u64)), // Nonce (in production, fetch from network)
-        chain_id: Some(1u64.into()), // Ethereum mainnet
-        data: Some(hex::decode(&data[2..]).unwrap().into()), // Remove "0x" prefix
-        access_list: Default::default(),
-    };
+    let evm_config = EvmConfigStorage::get();
 
-    // Sign transaction using threshold ECDSA
-    let key_id = EcdsaKeyId {
-        curve: EcdsaCurve::Secp256k1,
-        name: "key_1".to_string(), // Use "key_1" for mainnet
+    // Fetch the pending nonce of the signing address
+    let from_address =
+        match ecdsa_public_key(ecdsa_key_id(&evm_config), derivation_path.clone()).await {
+            Ok(public_key) => pubkey_bytes_to_address(&public_key),
+            Err(e) => {
+                return TransferResponse {
+                    success: false,
+                    transaction_hash: None,
+                    evm_transaction_id: None,
+                    error_message: Some(format!("Failed to derive signing address: {}", e)),
+                };
+            }
+        };
+    let Some(_nonce_lock) = NonceLock::acquire(&from_address) else {
+        return TransferResponse {
+            success: false,
+            transaction_hash: None,
+            evm_transaction_id: None,
+            error_message: Some(format!(
+                "Another transfer from {} is in progress, try again shortly",
+                from_address
+            )),
+        };
+    };
+    let nonce = match get_transaction_count(
+        &from_address,
+        BlockTag::Pending,
+        &evm_config,
+        &evm_rpc_service(),
+    )
+    .await
+    {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            return TransferResponse {
+                success: false,
+                transaction_hash: None,
+                evm_transaction_id: None,
+                error_message: Some(format!("Failed to fetch nonce: {}", e)),
+            };
+        }
     };
 
-    let derivation_path = vec![]; // Use canister root key
-
-    let signed_tx = match sign_eip1559_transaction(tx, key_id, derivation_path).await {
-        Ok(signed) => signed,
+    // Price the transaction from current network conditions
+    let fees = match evm_fees::estimate_eip1559_fees(&evm_config).await {
+        Ok(fees) => fees,
+        Err(e) => {
+            return TransferResponse {
+                success: false,
+                transaction_hash: None,
+                evm_transaction_id: None,
+                error_message: Some(format!("Failed to estimate fees: {}", e)),
+            };
+        }
+    };
+    let max_fee_per_gas = fees.max_fee_per_gas;
+    let max_priority_fee_per_gas = fees.max_priority_fee_per_gas;
+
+    let gas_limit = match evm_fees::estimate_gas_limit(
+        &evm_config,
+        &from_address,
+        &request.contract_address,
+        &format!("0x{}", hex::encode(&data)),
+    )
+    .await
+    {
+        Ok(gas_limit) => gas_limit,
         Err(e) => {
             return TransferResponse {
                 success: false,
                 transaction_hash: None,
-                error_message: Some(format!("Transaction signing failed: {}", e)),
+                evm_transaction_id: None,
+                error_message: Some(format!("Failed to estimate gas: {}", e)),
             };
         }
     };
 
+    // Build EIP-1559 transaction
+    let tx = Eip1559TransactionRequest {
+        from: None,
+        to: Some(ethers_core::types::NameOrAddress::Address(
+            transfer.contract,
+        )),
+        value: Some(U256::zero()), // No ETH sent, just token transfer
+        max_fee_per_gas: Some(max_fee_per_gas),
+        max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+        gas: Some(gas_limit),
+        nonce: Some(nonce),
+        chain_id: Some(evm_config.chain_id.into()),
+        data: Some(data.into()),
+        access_list: Default::default(),
+    };
+
+    // Sign transaction using threshold ECDSA
+    let signed_tx =
+        match sign_eip1559_transaction(tx, ecdsa_key_id(&evm_config), derivation_path).await {
+            Ok(signed) => signed,
+            Err(e) => {
+                return TransferResponse {
+                    success: false,
+                    transaction_hash: None,
+                    evm_transaction_id: None,
+                    error_message: Some(format!("Transaction signing failed: {}", e)),
+                };
+            }
+        };
+
     // Send transaction to Ethereum network
-    let rpc_services = RpcServices::EthMainnet(Some(vec![EthMainnetService::Alchemy]));
+    match send_raw_transaction(signed_tx, &evm_config, &evm_rpc_service()).await {
+        Ok(RawTransactionSubmission::Submitted { transaction_hash }) => {
+            // Track the transaction until it is confirmed, reverted or dropped
+            let now = crate::storage::get_current_timestamp();
+            let evm_transaction = EvmTransaction {
+                id: generate_evm_transaction_id(),
+                requested_by: caller,
+                transaction_hash: transaction_hash.clone(),
+                from_address,
+                contract_address: request.contract_address,
+                recipient: request.recipient,
+                amount: request.amount,
+                nonce: nonce.as_u64(),
+                gas_limit: gas_limit.to_string(),
+                max_fee_per_gas: max_fee_per_gas.to_string(),
+                max_priority_fee_per_gas: max_priority_fee_per_gas.to_string(),
+                status: EvmTransactionStatus::Pending,
+                block_number: None,
+                confirmations: 0,
+                gas_used: None,
+                effective_gas_price: None,
+                submitted_at: now,
+                updated_at: now,
+            };
+            let evm_transaction_id = evm_transaction.id;
+            if from_treasury {
+                audit_log::record(
+                    AuditAction::TreasuryErc20Transfer,
+                    &transaction_hash,
+                    None,
+                    audit_log::snapshot(&evm_transaction),
+                );
+            }
+            if let Err(e) = EvmTransactionStorage::insert(evm_transaction) {
+                ic_cdk::println!(
+                    "Failed to record EVM transaction {}: {:?}",
+                    transaction_hash,
+                    e
+                );
+            }
 
-    match send_raw_transaction(signed_tx, rpc_services, &*EVM_RPC).await {
-        Ok(tx_hash) => TransferResponse {
-            success: true,
-            transaction_hash: Some(tx_hash),
-            error_message: None,
+            TransferResponse {
+                success: true,
+                transaction_hash: Some(transaction_hash),
+                error_message: None,
+                evm_transaction_id: Some(evm_transaction_id),
+            }
+        }
+        Ok(rejected) => TransferResponse {
+            success: false,
+            transaction_hash: None,
+            evm_transaction_id: None,
+            error_message: Some(format!("Transaction rejected: {:?}", rejected)),
         },
         Err(e) => TransferResponse {
             success: false,
             transaction_hash: None,
+            evm_transaction_id: None,
             error_message: Some(format!("Transaction submission failed: {}", e)),
         },
     }
 }
 
-/// Test ERC-20 transfer with hardcoded values
-#[update]
-pub async fn test_erc20_transfer() -> TransferResponse {
-    let request = TransferRequest {
-        contract_address: "0x1B19C19393e2d034D8Ff31ff34c81252FcBbee92".to_string(),
-        recipient: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
-        amount: "1000000000000000000".to_string(), // 1 token (assuming 18 decimals)
-    };
+// Update the gas pricing policy used for outbound transfers, runs from an approved treasury proposal
+fn set_gas_policy(gas_policy: GasPolicy) -> Result<()> {
+    validate_gas_policy(&gas_policy)?;
+
+    let mut config = EvmConfigStorage::get();
+    let before = config.gas_policy.clone();
+    config.gas_policy = gas_policy.clone();
+    EvmConfigStorage::update(config)?;
+    audit_log::record(
+        AuditAction::GasPolicyUpdated,
+        "gas_policy",
+        audit_log::snapshot(&before),
+        audit_log::snapshot(&gas_policy),
+    );
+    Ok(())
+}
+
+fn validate_gas_policy(gas_policy: &GasPolicy) -> Result<()> {
+    if gas_policy.priority_fee_percentile > 100 || gas_policy.fee_history_blocks == 0 {
+        return Err(BitcoinUSTBillsError::validation_error(
+            "Percentile must be 0-100 and at least one fee history block is required",
+        ));
+    }
+    Ok(())
+}
+
+/// Get the caller's outbound ERC-20 transfers
+#[query]
+pub fn get_my_evm_transactions() -> Vec<EvmTransaction> {
+    let caller = ic_cdk::api::msg_caller();
+    EvmTransactionStorage::get_by_user(&caller)
+}
+
+/// Get an outbound EVM transaction by ID
+#[query]
+pub fn get_evm_transaction(transaction_id: u64) -> Result<EvmTransaction> {
+    let transaction = EvmTransactionStorage::get(transaction_id)?;
+
+    // Only allow requester or admin to follow the transaction
+    if ic_cdk::api::msg_caller() != transaction.requested_by {
+        guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    }
+
+    Ok(transaction)
+}
+
+/// Get all outbound EVM transactions, optionally filtered by status
+#[query]
+pub fn admin_get_evm_transactions(
+    status: Option<EvmTransactionStatus>,
+) -> Result<Vec<EvmTransaction>> {
+    guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    Ok(EvmTransactionStorage::get_by_status(status))
+}
+
+/// Test ERC-20 transfer with hardcoded values
+#[update]
+pub async fn test_erc20_transfer() -> TransferResponse {
+    let request = TransferRequest {
+        contract_address: "0x1B19C19393e2d034D8Ff31ff34c81252FcBbee92".to_string(),
+        recipient: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
+        amount: "1000000000000000000".to_string(), // 1 token (assuming 18 decimals)
+        from_caller_address: Some(true),           // Treasury transfers go through proposals
+    };
 
     transfer_erc20_tokens(request).await
 }
 
-/// //////////////////////////////////////////////////////////////
-/// //////////////////////////////////////////////////////////////  OUSG DEPOSIT AND MINTING SYSTEM
-/// //////////////////////////////////////////////////////////////
-/// //////////////////////////////////////////////////////////////
+// //////////////////////////////////////////////////////////////
+// //////////////////////////////////////////////////////////////  MULTI-SIGNATURE TREASURY PROPOSALS
+// //////////////////////////////////////////////////////////////
+// //////////////////////////////////////////////////////////////
+
+/// Propose a mint, ckBTC withdrawal, treasury ERC-20 transfer or config change
+#[update]
+pub fn propose_treasury_operation(operation: TreasuryOperation) -> Result<TreasuryProposal> {
+    multisig::propose(operation)
+}
+
+/// Approve an open treasury proposal
+#[update]
+pub fn approve_treasury_proposal(proposal_id: u64) -> Result<TreasuryProposal> {
+    multisig::approve(proposal_id)
+}
 
-// Canister IDs
-const OUSG_LEDGER_CANISTER_ID: &str = "ucwa4-rx777-77774-qaada-cai";
-const CKBTC_LEDGER_CANISTER_ID: &str = "mxzaz-hqaaa-aaaar-qaada-cai"; // ckBTC ledger
-const XRC_CANISTER_ID: &str = "uf6dk-hyaaa-aaaaq-qaaaq-cai";
+/// Execute a treasury proposal once it has enough approvals, or retry a failed mint or withdrawal
+#[update]
+pub async fn execute_treasury_proposal(proposal_id: u64) -> Result<TreasuryProposal> {
+    multisig::execute(proposal_id).await
+}
+
+/// Cancel an open treasury proposal
+#[update]
+pub fn cancel_treasury_proposal(proposal_id: u64) -> Result<TreasuryProposal> {
+    multisig::cancel(proposal_id)
+}
+
+/// Get a treasury proposal by ID
+#[query]
+pub fn get_treasury_proposal(proposal_id: u64) -> Result<TreasuryProposal> {
+    guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    TreasuryProposalStorage::get(proposal_id)
+}
+
+/// Get all treasury proposals, optionally filtered by status
+#[query]
+pub fn get_treasury_proposals(status: Option<ProposalStatus>) -> Result<Vec<TreasuryProposal>> {
+    guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    Ok(TreasuryProposalStorage::get_by_status(status))
+}
+
+/// Get the approval threshold and proposal lifetime
+#[query]
+pub fn get_multisig_policy() -> MultisigPolicy {
+    MultisigPolicyStorage::get()
+}
+
+// //////////////////////////////////////////////////////////////
+// //////////////////////////////////////////////////////////////  OUSG DEPOSIT AND MINTING SYSTEM
+// //////////////////////////////////////////////////////////////
+// //////////////////////////////////////////////////////////////
+
+/// Get the ledger, exchange rate, file store and EVM RPC canister ids in use
+#[query]
+pub fn get_canister_ids() -> CanisterIds {
+    CanisterIdsStorage::get()
+}
+
+// Point the backend at different external canisters, runs from an approved treasury proposal
+fn set_canister_ids(args: CanisterIdsArgs) -> Result<CanisterIds> {
+    let before = CanisterIdsStorage::get();
+    let mut canister_ids = before.clone();
+    canister_ids.apply(args);
+    CanisterIdsStorage::update(canister_ids.clone())?;
+    audit_log::record(
+        AuditAction::CanisterIdsUpdated,
+        "canister_ids",
+        audit_log::snapshot(&before),
+        audit_log::snapshot(&canister_ids),
+    );
+    Ok(canister_ids)
+}
 
 // Minimum deposit amount ($5000 USD worth of ckBTC)
-const MINIMUM_DEPOSIT_USD: f64 = 5000.0; // $5000 USD
+const MINIMUM_DEPOSIT_USD: UsdCents = UsdCents::from_dollars(5000); // $5000 USD
 
 /// User deposits ckBTC and gets OUSG minted (similar to DoxaV3 notifyStake)
+///
+/// Safe to call again with the same block index: a completed deposit is returned as is,
+/// and a deposit interrupted after the mint was sent resumes with the same ledger dedup key
 #[update]
 pub async fn notify_deposit(request: DepositRequest) -> DepositResponse {
     let caller = ic_cdk::api::msg_caller();
 
-    // Check if user is registered and eligible
-    let user = match UserStorage::get(&caller) {
-        Ok(user) => user,
-        Err(_) => {
+    // Check if user is registered, eligibility is checked on the validated on-ledger amount
+    if UserStorage::get(&caller).is_err() {
+        return DepositResponse {
+            success: false,
+            deposit_id: None,
+            ousg_minted: None,
+            error_message: Some("User not registered".to_string()),
+        };
+    }
+
+    // One deposit flow per principal at a time
+    let _lock = match DepositLock::acquire(caller) {
+        Some(lock) => lock,
+        None => {
             return DepositResponse {
                 success: false,
                 deposit_id: None,
                 ousg_minted: None,
-                error_message: Some("User not registered".to_string()),
+                error_message: Some("Another deposit is being processed".to_string()),
             };
         }
     };
 
-    // Check if user can make deposit
-    if !user.can_make_deposit(request.ckbtc_amount) {
-        return DepositResponse {
-            success: false,
-            deposit_id: None,
-            ousg_minted: None,
-            error_message: Some(
-                "User not eligible for deposit or amount exceeds limit".to_string(),
-            ),
-        };
-    }
+    let reservation =
+        reserve_notified_deposit(caller, request.block_index, request.quote_id.as_deref()).await;
+    let deposit = match reservation {
+        Ok(deposit) => deposit,
+        Err(e) => {
+            return DepositResponse {
+                success: false,
+                deposit_id: None,
+                ousg_minted: None,
+                error_message: Some(e),
+            };
+        }
+    };
+    let deposit_id = deposit.id;
 
-    // Check if this block index has already been processed
-    if ProcessedDepositsStorage::contains(request.block_index) {
-        return DepositResponse {
+    match process_deposit(deposit).await {
+        Ok(deposit) => DepositResponse {
+            success: true,
+            deposit_id: Some(deposit_id),
+            ousg_minted: Some(deposit.ousg_minted),
+            error_message: None,
+        },
+        Err(e) => DepositResponse {
             success: false,
-            deposit_id: None,
+            deposit_id: Some(deposit_id),
             ousg_minted: None,
-            error_message: Some("Deposit already processed".to_string()),
-        };
+            error_message: Some(e),
+        },
     }
+}
 
-    // Get BTC price from XRC (or use hardcoded for testing)
-    let btc_price = match get_btc_price().await {
-        Ok(price) => price,
-        Err(e) => {
-            ic_cdk::println!(
-                "Failed to get BTC price from XRC: {:?}, using hardcoded price for testing",
-                e
-            );
-            // Use hardcoded BTC price for testing (around $100,000)
-            100000.0
-        }
+/// User approves the backend on the ckBTC ledger and the backend pulls the deposit itself
+///
+/// The pricing, minimum and limit checks run before the pull, so a deposit that would be
+/// rejected leaves the user's ckBTC untouched. The allowance must cover `amount` plus the fee.
+#[update]
+pub async fn deposit_with_allowance(amount: u64) -> DepositResponse {
+    let caller = ic_cdk::api::msg_caller();
+    let failure = |error: String| DepositResponse {
+        success: false,
+        deposit_id: None,
+        ousg_minted: None,
+        error_message: Some(error),
     };
 
-    // Calculate USD value of ckBTC deposit
-    let usd_value = convert_ckbtc_to_usd(request.ckbtc_amount, btc_price);
+    if UserStorage::get(&caller).is_err() {
+        return failure("User not registered".to_string());
+    }
 
-    // Check minimum deposit amount ($5000 USD)
-    if usd_value < MINIMUM_DEPOSIT_USD {
-        return DepositResponse {
-            success: false,
-            deposit_id: None,
-            ousg_minted: None,
-            error_message: Some(format!(
-                "Deposit value ${:.2} below minimum ${:.2}",
-                usd_value, MINIMUM_DEPOSIT_USD
-            )),
-        };
+    // One deposit flow per principal at a time
+    let _lock = match DepositLock::acquire(caller) {
+        Some(lock) => lock,
+        None => return failure("Another deposit is being processed".to_string()),
+    };
+
+    let observation = match price_oracle::observe_pricing_rate().await {
+        Ok(observation) => observation,
+        Err(e) => return failure(e.to_string()),
+    };
+    if let Err(DepositCheckError::Rejected(e) | DepositCheckError::Retryable(e)) =
+        check_deposit_amount(&caller, amount, observation.rate)
+    {
+        return failure(e);
     }
 
-    // Validate the deposit transaction
-    match validate_ckbtc_deposit_transaction(request.block_index, caller).await {
-        Ok(validated_amount) => {
-            // Create new deposit record
-            let deposit_id = generate_deposit_id();
-            let mut deposit = Deposit::new(
-                deposit_id,
-                caller,
-                validated_amount,
-                usd_value,
-                btc_price,
-                request.block_index,
-            );
+    let block_index = match pull_ckbtc_from_user(caller, amount).await {
+        Ok(block_index) => block_index,
+        Err(e) => return failure(e.to_string()),
+    };
 
-            // Store deposit
-            if let Err(e) = DepositStorage::insert(deposit.clone()) {
-                return DepositResponse {
-                    success: false,
-                    deposit_id: None,
-                    ousg_minted: None,
-                    error_message: Some(format!("Failed to store deposit: {:?}", e)),
-                };
-            }
+    // From here on the deposit runs the same pipeline as notify_deposit, including refunds.
+    // It is minted at the price checked above, like a quoted deposit.
+    let mut deposit = match reserve_deposit(caller, block_index) {
+        Ok(deposit) => deposit,
+        Err(e) => return failure(e),
+    };
+    deposit.locked_price = Some(observation);
+    if let Err(e) = DepositStorage::update(deposit.clone()) {
+        return failure(format!("Failed to store deposit: {:?}", e));
+    }
+    let deposit_id = deposit.id;
 
-            // Calculate OUSG tokens to mint
-            let ousg_to_mint = deposit.calculate_ousg_to_mint();
-
-            // Mint OUSG tokens
-            match mint_ousg_tokens(caller, ousg_to_mint).await {
-                Ok(_) => {
-                    // Mark deposit as validated
-                    deposit.mark_validated(ousg_to_mint);
-                    if let Err(e) = DepositStorage::update(deposit.clone()) {
-                        return DepositResponse {
-                            success: false,
-                            deposit_id: Some(deposit_id),
-                            ousg_minted: None,
-                            error_message: Some(format!("Failed to update deposit: {:?}", e)),
-                        };
-                    }
-
-                    // Update user balance
-                    let mut updated_user = user;
-                    updated_user.update_after_deposit(ousg_to_mint);
-                    if let Err(e) = UserStorage::update(updated_user) {
-                        return DepositResponse {
-                            success: false,
-                            deposit_id: Some(deposit_id),
-                            ousg_minted: None,
-                            error_message: Some(format!("Failed to update user: {:?}", e)),
-                        };
-                    }
-
-                    // Mark block as processed
-                    if let Err(e) = ProcessedDepositsStorage::insert(request.block_index, caller) {
-                        return DepositResponse {
-                            success: false,
-                            deposit_id: Some(deposit_id),
-                            ousg_minted: None,
-                            error_message: Some(format!(
-                                "Failed to mark block as processed: {:?}",
-                                e
-                            )),
-                        };
-                    }
-
-                    DepositResponse {
-                        success: true,
-                        deposit_id: Some(deposit_id),
-                        ousg_minted: Some(ousg_to_mint),
-                        error_message: None,
-                    }
-                }
-                Err(e) => {
-                    // Mark deposit as failed
-                    deposit.mark_failed();
-                    if let Err(update_err) = DepositStorage::update(deposit) {
-                        return DepositResponse {
-                            success: false,
-                            deposit_id: Some(deposit_id),
-                            ousg_minted: None,
-                            error_message: Some(format!(
-                                "Failed to update deposit and mint OUSG: {:?}, update error: {:?}",
-                                e, update_err
-                            )),
-                        };
-                    }
-
-                    DepositResponse {
-                        success: false,
-                        deposit_id: Some(deposit_id),
-                        ousg_minted: None,
-                        error_message: Some(format!("Failed to mint OUSG: {:?}", e)),
-                    }
-                }
-            }
-        }
+    match process_deposit(deposit).await {
+        Ok(deposit) => DepositResponse {
+            success: true,
+            deposit_id: Some(deposit_id),
+            ousg_minted: Some(deposit.ousg_minted),
+            error_message: None,
+        },
         Err(e) => DepositResponse {
             success: false,
-            deposit_id: None,
+            deposit_id: Some(deposit_id),
             ousg_minted: None,
-            error_message: Some(format!("Deposit validation failed: {:?}", e)),
+            error_message: Some(e),
         },
     }
 }
 
-/// Get BTC price from XRC canister
-async fn get_btc_price() -> Result<f64> {
-    // Create BTC to USD price request
-    let request = GetExchangeRateRequest {
-        base_asset: Asset {
-            symbol: "BTC".to_string(),
-            class: AssetClass::Cryptocurrency,
+// Pulls approved ckBTC from the user into the canister's deposit account
+async fn pull_ckbtc_from_user(user: Principal, amount: u64) -> Result<u64> {
+    let transfer_args = TransferFromArgs {
+        spender_subaccount: None,
+        from: Account {
+            owner: user,
+            subaccount: None,
         },
-        quote_asset: Asset {
-            symbol: "USD".to_string(),
-            class: AssetClass::FiatCurrency,
+        to: Account {
+            owner: ic_cdk::api::canister_self(),
+            subaccount: None,
         },
-        timestamp: None,
+        amount: candid::Nat::from(amount),
+        fee: None,
+        memo: None,
+        created_at_time: Some(ic_cdk::api::time()),
     };
 
-    // Call XRC canister using generated binding
-    let principal = Principal::from_text(XRC_CANISTER_ID).map_err(|e| {
-        BitcoinUSTBillsError::StorageError(format!("Invalid XRC principal: {:?}", e))
-    })?;
-    let service = ExchangeRateService(principal);
-    let result = service.get_exchange_rate(request).await;
-
-    match result {
-        Ok((GetExchangeRateResult::Ok(response),)) => {
-            let price = response.rate as f64 / 100_000_000.0; // Convert from XRC format
-            Ok(price)
+    let service = ckbtc_ledger_service();
+    match service.icrc_2_transfer_from(transfer_args).await {
+        Ok((TransferFromResult::Ok(block_index),)) => utils::nat_to_u64(&block_index),
+        Ok((TransferFromResult::Err(TransferFromError::InsufficientAllowance { allowance }),)) => {
+            Err(BitcoinUSTBillsError::LedgerError(format!(
+                "Insufficient ckBTC allowance: {}, approve the amount plus the ledger fee",
+                allowance
+            )))
         }
-        Ok((GetExchangeRateResult::Err(e),)) => Err(BitcoinUSTBillsError::StorageError(format!(
-            "XRC error: {:?}",
+        Ok((TransferFromResult::Err(e),)) => Err(BitcoinUSTBillsError::LedgerError(format!(
+            "ckBTC transfer_from failed: {:?}",
             e
         ))),
-        Err(e) => Err(BitcoinUSTBillsError::StorageError(format!(
-            "Failed to call XRC: {:?}",
+        Err(e) => Err(BitcoinUSTBillsError::LedgerError(format!(
+            "ckBTC transfer_from call failed: {:?}",
             e
         ))),
     }
 }
 
-/// Validate ckBTC deposit transaction
-async fn validate_ckbtc_deposit_transaction(block_index: u64, caller: Principal) -> Result<u64> {
-    // Get transaction details from ckBTC ledger using generated binding
-    let request = GetTransactionsRequest {
-        start: candid::Nat::from(block_index),
-        length: candid::Nat::from(1u64),
-    };
+/// Drives a reserved deposit to Processed, the caller must hold the user's DepositLock
+pub(crate) async fn process_deposit(mut deposit: Deposit) -> std::result::Result<Deposit, String> {
+    if deposit.is_complete() {
+        return Ok(deposit);
+    }
+    if deposit.status == DepositStatus::Refunded {
+        return Err("Deposit was refunded".to_string());
+    }
+    if deposit.refund_created_at.is_some() {
+        return Err("Deposit is being refunded".to_string());
+    }
+    let user_principal = deposit.user_principal;
 
-    let principal = Principal::from_text(CKBTC_LEDGER_CANISTER_ID).map_err(|e| {
-        BitcoinUSTBillsError::StorageError(format!("Invalid ckBTC principal: {:?}", e))
-    })?;
-    let service = OusgLedgerService(principal);
-    let result = service.get_transactions(request).await;
+    // A mint was already sent for this deposit: only resume it, never price it again
+    let mint_sent = deposit.mint_created_at.is_some()
+        && matches!(
+            deposit.status,
+            DepositStatus::Minting | DepositStatus::Failed | DepositStatus::ManualReview
+        );
 
-    let transactions_response = result
-        .map_err(|e| {
-            BitcoinUSTBillsError::StorageError(format!("Failed to get transactions: {:?}", e))
-        })?
-        .0;
+    if !mint_sent {
+        match price_and_validate_deposit(&mut deposit).await {
+            Ok(()) => {}
+            Err(DepositCheckError::Retryable(e)) => {
+                deposit.mark_failed_with(e.clone());
+                let _ = DepositStorage::update(deposit);
+                return Err(e);
+            }
+            // The transfer is valid but will never be accepted, send the ckBTC back
+            Err(DepositCheckError::Rejected(e)) => {
+                deposit.mark_failed_with(e.clone());
+                let _ = DepositStorage::update(deposit.clone());
+                return match refund_deposit(deposit).await {
+                    Ok(_) => Err(format!("{}, the ckBTC was refunded", e)),
+                    Err(refund_err) => Err(format!("{}, refund pending: {}", e, refund_err)),
+                };
+            }
+        }
 
-    let log_length = transactions_response.log_length.0.to_u64_digits();
-    if log_length.is_empty() || block_index >= log_length[0] {
-        return Err(BitcoinUSTBillsError::StorageError(format!(
-            "Invalid block index: {} (log_length: {:?})",
-            block_index, log_length
+        // Calculate OUSG tokens to mint
+        let Some(ousg_to_mint) = deposit.calculate_ousg_to_mint() else {
+            let error = format!("OUSG amount for {} is out of range", deposit.usd_value);
+            deposit.mark_failed_with(error.clone());
+            let _ = DepositStorage::update(deposit);
+            return Err(error);
+        };
+        deposit.mark_minting(ousg_to_mint, ic_cdk::api::time());
+    } else {
+        deposit.status = DepositStatus::Minting;
+    }
+    DepositStorage::update(deposit.clone())
+        .map_err(|e| format!("Failed to store deposit: {:?}", e))?;
+
+    let ousg_to_mint = deposit.ousg_minted;
+    let mint_created_at = deposit.mint_created_at.unwrap_or_else(ic_cdk::api::time);
+
+    // Mint OUSG tokens, the ledger deduplicates retries with the same created_at_time
+    match mint_ousg_tokens(user_principal, ousg_to_mint, mint_created_at).await {
+        Ok(mint_block_index) => {
+            // Everything below runs in one message, so it is applied entirely or not at all
+            deposit.mint_block_index = Some(mint_block_index);
+            deposit.mark_validated(ousg_to_mint);
+
+            // Re-read the user, it may have changed while the mint was in flight
+            if let Ok(mut user) = UserStorage::get(&user_principal) {
+                user.update_after_deposit(ousg_to_mint);
+                UserStorage::update(user).map_err(|e| format!("Failed to update user: {:?}", e))?;
+            }
+
+            // Mark block as processed
+            ProcessedDepositsStorage::insert(deposit.block_index, user_principal)
+                .map_err(|e| format!("Failed to mark block as processed: {:?}", e))?;
+
+            deposit.mark_processed();
+            DepositStorage::update(deposit.clone())
+                .map_err(|e| format!("Failed to update deposit: {:?}", e))?;
+            Ok(deposit)
+        }
+        Err(e) => {
+            // The ledger rejected the mint outright, so nothing was minted: the deposit may be
+            // refunded and a retry needs a fresh dedup key. Otherwise keep mint_created_at so a
+            // retry reuses the same dedup key.
+            if matches!(e, BitcoinUSTBillsError::LedgerError(_)) {
+                deposit.mint_created_at = None;
+            }
+            let error = format!("Failed to mint OUSG: {}", e);
+            deposit.mark_failed_with(error.clone());
+            DepositStorage::update(deposit)
+                .map_err(|update_err| format!("{}, update error: {:?}", error, update_err))?;
+            Err(error)
+        }
+    }
+}
+
+thread_local! {
+    // Principals with a notify_deposit call in flight
+    static DEPOSIT_LOCKS: std::cell::RefCell<std::collections::BTreeSet<Principal>> =
+        const { std::cell::RefCell::new(std::collections::BTreeSet::new()) };
+}
+
+// Held for the whole deposit flow, released on drop (also when a callback traps)
+pub(crate) struct DepositLock(Principal);
+
+impl DepositLock {
+    pub(crate) fn acquire(principal: Principal) -> Option<Self> {
+        DEPOSIT_LOCKS
+            .with(|locks| locks.borrow_mut().insert(principal))
+            .then_some(DepositLock(principal))
+    }
+}
+
+impl Drop for DepositLock {
+    fn drop(&mut self) {
+        DEPOSIT_LOCKS.with(|locks| locks.borrow_mut().remove(&self.0));
+    }
+}
+
+// Nothing is stored for a notified block until the ledger shows it pays the caller, so
+// nobody can claim another user's block or one that does not exist yet. Only the caller
+// passes that check and its DepositLock is held, so no other call can reserve in between.
+async fn reserve_notified_deposit(
+    caller: Principal,
+    block_index: u64,
+    quote_id: Option<&str>,
+) -> std::result::Result<Deposit, String> {
+    let already_known = DepositStorage::get_by_block_index(block_index).is_some()
+        || ProcessedDepositsStorage::contains(block_index);
+    if !already_known {
+        validate_ckbtc_deposit_transaction(block_index, caller)
+            .await
+            .map_err(|e| format!("Deposit validation failed: {:?}", e))?;
+    }
+
+    match quote_id {
+        Some(quote_id) => reserve_quoted_deposit(caller, block_index, quote_id),
+        None => reserve_deposit(caller, block_index),
+    }
+}
+
+// Returns the deposit already claiming this block index, or claims it for the caller.
+// The block must be known to pay the caller, see reserve_notified_deposit.
+pub(crate) fn reserve_deposit(
+    caller: Principal,
+    block_index: u64,
+) -> std::result::Result<Deposit, String> {
+    if let Some(existing) = DepositStorage::get_by_block_index(block_index) {
+        if existing.user_principal != caller {
+            return Err("Deposit already processed".to_string());
+        }
+        return Ok(existing);
+    }
+
+    // Blocks processed before deposits were indexed by block
+    if ProcessedDepositsStorage::contains(block_index) {
+        return Err("Deposit already processed".to_string());
+    }
+
+    let deposit = Deposit::new_reservation(generate_deposit_id(), caller, block_index);
+    DepositStorage::insert(deposit.clone())
+        .map_err(|e| format!("Failed to store deposit: {:?}", e))?;
+    Ok(deposit)
+}
+
+// A new reservation claims the quote, a block that is already reserved must carry the same one
+fn reserve_quoted_deposit(
+    caller: Principal,
+    block_index: u64,
+    quote_id: &str,
+) -> std::result::Result<Deposit, String> {
+    if let Some(existing) = DepositStorage::get_by_block_index(block_index) {
+        if existing.user_principal == caller && existing.quote_id.as_deref() != Some(quote_id) {
+            return Err("Deposit was already notified without this quote".to_string());
+        }
+        return reserve_deposit(caller, block_index);
+    }
+
+    quotes::claim(quote_id, caller, QuoteSide::Deposit).map_err(|e| e.to_string())?;
+    let mut deposit = reserve_deposit(caller, block_index)?;
+    deposit.quote_id = Some(quote_id.to_string());
+    DepositStorage::update(deposit.clone())
+        .map_err(|e| format!("Failed to store deposit: {:?}", e))?;
+    Ok(deposit)
+}
+
+// Why a deposit could not be priced: rejections are final and get refunded, the rest is retried
+enum DepositCheckError {
+    Rejected(String),
+    Retryable(String),
+}
+
+// Prices the deposit and checks the ledger transaction, filling in amount and USD value.
+// A quoted or pulled deposit keeps its locked price however long it takes to go through.
+async fn price_and_validate_deposit(
+    deposit: &mut Deposit,
+) -> std::result::Result<(), DepositCheckError> {
+    let (btc_price, price_timestamp, quoted_amount) =
+        match (&deposit.quote_id, &deposit.locked_price) {
+            (Some(quote_id), _) => {
+                let quote = quotes::get(quote_id).map_err(|e| {
+                    DepositCheckError::Rejected(format!("Quote {}: {}", quote_id, e))
+                })?;
+                (
+                    quote.btc_price_usd,
+                    quote.price_timestamp,
+                    Some(quote.ckbtc_amount),
+                )
+            }
+            (None, Some(observation)) => (observation.rate, observation.timestamp, None),
+            (None, None) => {
+                let observation = price_oracle::observe_pricing_rate()
+                    .await
+                    .map_err(|e| DepositCheckError::Retryable(e.to_string()))?;
+                (observation.rate, observation.timestamp, None)
+            }
+        };
+
+    // Validate the deposit transaction
+    let validated = validate_ckbtc_deposit_transaction(deposit.block_index, deposit.user_principal)
+        .await
+        .map_err(|e| DepositCheckError::Retryable(format!("Deposit validation failed: {:?}", e)))?;
+    let validated_amount = validated.amount;
+    // Known from here on, a refund sends back exactly this amount from this account
+    deposit.ckbtc_amount = validated_amount;
+    deposit.subaccount = validated.subaccount;
+    deposit.sender = Some(validated.from.owner);
+    deposit.sender_subaccount = validated
+        .from
+        .subaccount
+        .map(|subaccount| subaccount.into_vec());
+
+    if let Some(quoted_amount) = quoted_amount.filter(|amount| *amount != validated_amount) {
+        return Err(DepositCheckError::Rejected(format!(
+            "Deposited {} ckBTC e8s but the quote is for {}",
+            validated_amount, quoted_amount
         )));
     }
 
-    if transactions_response.transactions.is_empty() {
-        return Err(BitcoinUSTBillsError::StorageError(
-            "No transaction found at block index".to_string(),
+    let usd_value = check_deposit_amount(&deposit.user_principal, validated_amount, btc_price)?;
+
+    // The exact price the mint is priced with, for audits
+    deposit.usd_value = usd_value;
+    deposit.btc_price_usd = btc_price;
+    deposit.btc_price_timestamp = Some(price_timestamp);
+    deposit.status = DepositStatus::Pending;
+    Ok(())
+}
+
+// Eligibility and minimum checks shared by every deposit flow, returns the USD value
+fn check_deposit_amount(
+    user_principal: &Principal,
+    ckbtc_amount: u64,
+    btc_price: BtcUsdRate,
+) -> std::result::Result<UsdCents, DepositCheckError> {
+    // Also covers transfers to the deposit subaccount of a principal that is not registered
+    let user = UserStorage::get(user_principal)
+        .map_err(|_| DepositCheckError::Rejected("User not registered".to_string()))?;
+    if !user.can_make_deposit(ckbtc_amount) {
+        return Err(DepositCheckError::Rejected(
+            "User not eligible for deposit or amount exceeds limit".to_string(),
         ));
     }
 
-    let transaction = &transactions_response.transactions[0];
+    // Calculate USD value of ckBTC deposit, rounded down so the user is never over-credited
+    let usd_value = CkbtcE8s(ckbtc_amount)
+        .to_usd(btc_price, Rounding::Down)
+        .ok_or_else(|| DepositCheckError::Rejected("Deposit amount out of range".to_string()))?;
 
-    // Check if it's a transfer transaction
-    let transfer = match &transaction.transfer {
-        Some(transfer) => transfer,
-        None => {
-            return Err(BitcoinUSTBillsError::StorageError(
-                "Transaction is not a transfer".to_string(),
-            ))
-        }
-    };
+    // Check minimum deposit amount ($5000 USD)
+    if usd_value < MINIMUM_DEPOSIT_USD {
+        return Err(DepositCheckError::Rejected(format!(
+            "Deposit value {} below minimum {}",
+            usd_value, MINIMUM_DEPOSIT_USD
+        )));
+    }
+    Ok(usd_value)
+}
 
-    // Check if transfer is to this canister
-    let canister_account = Account {
-        owner: ic_cdk::api::id(),
-        subaccount: None,
-    };
+// A ckBTC transfer credited to a user, the account it came from and the canister subaccount
+// holding the funds
+struct ValidatedTransfer {
+    amount: u64,
+    from: Account,
+    subaccount: Option<Vec<u8>>,
+}
 
-    if transfer.to.owner != canister_account.owner
-        || transfer.to.subaccount != canister_account.subaccount
-    {
+// Memo the backend puts on its own ckBTC payouts, such blocks are never deposits
+const CKBTC_PAYOUT_MEMO: [u8; 4] = [2, 1, 0, 3];
+
+/// Validate ckBTC deposit transaction
+///
+/// Works for archived blocks too, see `ledger_blocks::get_transfer`
+async fn validate_ckbtc_deposit_transaction(
+    block_index: u64,
+    caller: Principal,
+) -> Result<ValidatedTransfer> {
+    let transfer = ledger_blocks::get_transfer(block_index)
+        .await
+        .map_err(BitcoinUSTBillsError::LedgerError)?
+        .ok_or_else(|| {
+            BitcoinUSTBillsError::StorageError("Transaction is not a transfer".to_string())
+        })?;
+
+    // Check if transfer is to this canister
+    let canister = ic_cdk::api::canister_self();
+    if transfer.to.owner != canister {
         return Err(BitcoinUSTBillsError::StorageError(
             "Transfer not sent to this canister".to_string(),
         ));
     }
 
-    // Check if transfer is from the caller
-    if transfer.from.owner != caller {
+    // Moves between the canister's own accounts (sweeps) and its payouts are not deposits
+    if transfer.from.owner == canister
+        || transfer.memo.as_deref() == Some(CKBTC_PAYOUT_MEMO.as_slice())
+    {
         return Err(BitcoinUSTBillsError::StorageError(
-            "Transfer not from caller".to_string(),
+            "Transfer was made by this canister".to_string(),
         ));
     }
 
+    // Transfers to the default account must come from the caller, transfers to the caller's
+    // deposit subaccount are credited to the caller whoever sent them
+    let subaccount = deposit_scanner::normalize_subaccount(
+        transfer
+            .to
+            .subaccount
+            .as_ref()
+            .map(|subaccount| subaccount.as_slice()),
+    );
+    match &subaccount {
+        None if transfer.from.owner != caller => {
+            return Err(BitcoinUSTBillsError::StorageError(
+                "Transfer not from caller".to_string(),
+            ));
+        }
+        Some(subaccount) if *subaccount != deposit_scanner::deposit_subaccount(&caller) => {
+            return Err(BitcoinUSTBillsError::StorageError(
+                "Transfer not sent to the caller's deposit subaccount".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
     // Check minimum amount (will be validated against USD value later)
-    let amount = transfer.amount.0.to_u64_digits();
-    if amount.is_empty() || amount[0] == 0 {
+    if transfer.amount == 0 {
         return Err(BitcoinUSTBillsError::StorageError(
             "Transfer amount cannot be zero".to_string(),
         ));
     }
 
-    Ok(amount[0])
+    Ok(ValidatedTransfer {
+        amount: transfer.amount,
+        from: transfer.from,
+        subaccount,
+    })
 }
 
 /// Mint OUSG tokens to user account
-async fn mint_ousg_tokens(user: Principal, amount: u64) -> Result<u64> {
+async fn mint_ousg_tokens(user: Principal, amount: u64, created_at_time: u64) -> Result<u64> {
     // Create transfer to user account using generated binding types
     let transfer_args = TransferArg {
         from_subaccount: None,
@@ -868,14 +1748,11 @@ async fn mint_ousg_tokens(user: Principal, amount: u64) -> Result<u64> {
         amount: candid::Nat::from(amount),
         fee: None,
         memo: None,
-        created_at_time: Some(ic_cdk::api::time()),
+        created_at_time: Some(created_at_time),
     };
 
     // Execute transfer (minting) via OUSG ledger using generated binding
-    let principal = Principal::from_text(OUSG_LEDGER_CANISTER_ID).map_err(|e| {
-        BitcoinUSTBillsError::StorageError(format!("Invalid OUSG principal: {:?}", e))
-    })?;
-    let service = OusgLedgerService(principal);
+    let service = ousg_ledger_service();
     let result = service.icrc_1_transfer(transfer_args).await;
 
     match result {
@@ -889,7 +1766,16 @@ async fn mint_ousg_tokens(user: Principal, amount: u64) -> Result<u64> {
                 Ok(digits[0])
             }
         }
-        Ok((TransferResult::Err(e),)) => Err(BitcoinUSTBillsError::StorageError(format!(
+        // Same mint was already executed, e.g. by an interrupted earlier attempt
+        Ok((TransferResult::Err(TransferError::Duplicate { duplicate_of }),)) => {
+            utils::nat_to_u64(&duplicate_of)
+        }
+        // Past the dedup window an earlier attempt may have gone through, so this is not a rejection
+        Ok((TransferResult::Err(TransferError::TooOld),)) => Err(
+            BitcoinUSTBillsError::StorageError("Transfer failed: TooOld".to_string()),
+        ),
+        // The ledger checked the dedup key and refused the mint, nothing was minted
+        Ok((TransferResult::Err(e),)) => Err(BitcoinUSTBillsError::LedgerError(format!(
             "Transfer failed: {:?}",
             e
         ))),
@@ -900,17 +1786,231 @@ async fn mint_ousg_tokens(user: Principal, amount: u64) -> Result<u64> {
     }
 }
 
-/// Get user's deposit history
-#[query]
-pub fn get_user_deposits() -> Result<Vec<Deposit>> {
-    let caller = ic_cdk::api::msg_caller();
-    Ok(DepositStorage::get_by_user(&caller))
-}
-
-/// Get deposit by ID
-#[query]
-pub fn get_deposit(deposit_id: u64) -> Result<Deposit> {
-    DepositStorage::get(deposit_id)
+/// Get user's deposit history
+#[query]
+pub fn get_user_deposits() -> Result<Vec<Deposit>> {
+    let caller = ic_cdk::api::msg_caller();
+    Ok(DepositStorage::get_by_user(&caller))
+}
+
+/// Get the caller's deposit account, ckBTC sent there is credited without calling notify_deposit
+#[query]
+pub fn get_my_deposit_account() -> Result<Account> {
+    let caller = ic_cdk::api::msg_caller();
+    UserStorage::get(&caller)?;
+
+    // Transfers before the block the scanner starts at would never be credited
+    if !deposit_scanner::is_started() {
+        return Err(BitcoinUSTBillsError::validation_error(
+            "Deposit accounts are not available yet, try again shortly",
+        ));
+    }
+
+    Ok(Account {
+        owner: ic_cdk::api::canister_self(),
+        subaccount: Some(deposit_scanner::deposit_subaccount(&caller).into()),
+    })
+}
+
+/// Get the price oracle's quality settings and last accepted rate
+#[query]
+pub fn admin_get_price_oracle_state() -> Result<PriceOracleState> {
+    guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    Ok(PriceOracleState {
+        config: PriceOracleStorage::get_config(),
+        last_good_rate: PriceOracleStorage::get_last_good_rate(),
+        xrc_error_counts: PriceOracleStorage::get_xrc_error_counts(),
+        latest_sample: PriceHistoryStorage::latest(),
+        sample_count: PriceHistoryStorage::count(),
+    })
+}
+
+// Runs from an approved SetPriceOracleConfig treasury proposal
+fn set_price_oracle_config(args: PriceOracleArgs) -> Result<PriceOracleConfig> {
+    let before = PriceOracleStorage::get_config();
+    let mut config = before.clone();
+    config
+        .apply_args(args)
+        .map_err(BitcoinUSTBillsError::ValidationError)?;
+    PriceOracleStorage::update_config(config.clone())?;
+    audit_log::record(
+        AuditAction::PriceOracleConfigUpdated,
+        "price_oracle",
+        audit_log::snapshot(&before),
+        audit_log::snapshot(&config),
+    );
+    Ok(config)
+}
+
+/// Get the progress of the deposit subaccount ledger scan
+#[query]
+pub fn admin_get_deposit_scan_state() -> Result<DepositScanState> {
+    guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    Ok(DepositScanStorage::get())
+}
+
+/// Get deposit by ID
+#[query]
+pub fn get_deposit(deposit_id: u64) -> Result<Deposit> {
+    DepositStorage::get(deposit_id)
+}
+
+/// Get deposits the reconciler gave up on
+#[query]
+pub fn admin_get_deposits_for_review() -> Result<Vec<Deposit>> {
+    guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    Ok(DepositStorage::get_by_status(&[
+        DepositStatus::ManualReview,
+    ]))
+}
+
+/// Get the reconciler's retry state of every unfinished deposit
+#[query]
+pub fn admin_get_deposit_retry_states() -> Result<Vec<DepositRetryState>> {
+    guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    Ok(DepositRetryStorage::get_all())
+}
+
+/// Process an unfinished deposit now, resetting its backoff
+#[update]
+pub async fn admin_force_retry_deposit(deposit_id: u64) -> Result<Deposit> {
+    guard::assert_role(Role::TreasuryOperator)?;
+
+    let deposit = DepositStorage::get(deposit_id)?;
+    if deposit.is_complete() || deposit.status == DepositStatus::Refunded {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Deposit {} is already settled",
+            deposit_id
+        )));
+    }
+    let _lock = DepositLock::acquire(deposit.user_principal).ok_or_else(|| {
+        BitcoinUSTBillsError::validation_error("Another deposit is being processed")
+    })?;
+
+    // Re-read under the lock, the reconciler may have moved the deposit meanwhile
+    let deposit = DepositStorage::get(deposit_id)?;
+    let before = audit_log::snapshot(&deposit.status);
+    DepositRetryStorage::remove(deposit_id);
+
+    let result = process_deposit(deposit)
+        .await
+        .map_err(BitcoinUSTBillsError::ValidationError);
+    audit_log::record(
+        AuditAction::DepositRetryForced,
+        deposit_id,
+        before,
+        deposit_audit_outcome(&result),
+    );
+    result
+}
+
+/// Return the ckBTC of a deposit that was never minted, minus the ledger fee
+#[update]
+pub async fn admin_refund_deposit(deposit_id: u64) -> Result<Deposit> {
+    guard::assert_role(Role::TreasuryOperator)?;
+
+    let deposit = DepositStorage::get(deposit_id)?;
+    let _lock = DepositLock::acquire(deposit.user_principal).ok_or_else(|| {
+        BitcoinUSTBillsError::validation_error("Another deposit is being processed")
+    })?;
+
+    let deposit = DepositStorage::get(deposit_id)?;
+    let before = audit_log::snapshot(&deposit.status);
+
+    let result = refund_deposit(deposit).await;
+    audit_log::record(
+        AuditAction::DepositRefunded,
+        deposit_id,
+        before,
+        deposit_audit_outcome(&result),
+    );
+    result
+}
+
+// Sends the deposited ckBTC back, the caller must hold the user's DepositLock
+pub(crate) async fn refund_deposit(mut deposit: Deposit) -> Result<Deposit> {
+    if !deposit.is_refundable() {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Deposit {} cannot be refunded in status {:?}",
+            deposit.id, deposit.status
+        )));
+    }
+
+    // Reserved deposits were never validated, and deposits validated before the sender was
+    // recorded lack it, so both still have to be read from the ledger
+    if deposit.ckbtc_amount == 0 || deposit.sender.is_none() {
+        let validated =
+            validate_ckbtc_deposit_transaction(deposit.block_index, deposit.user_principal).await?;
+        deposit.ckbtc_amount = validated.amount;
+        deposit.subaccount = validated.subaccount;
+        deposit.sender = Some(validated.from.owner);
+        deposit.sender_subaccount = validated
+            .from
+            .subaccount
+            .map(|subaccount| subaccount.into_vec());
+    }
+
+    let fee = get_ckbtc_fee().await?;
+    if deposit.ckbtc_amount <= fee {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Deposit of {} e8s does not cover the ledger fee of {} e8s",
+            deposit.ckbtc_amount, fee
+        )));
+    }
+
+    // Store the dedup key before the await, a retried refund reuses it and cannot pay twice
+    let refund_created_at = *deposit
+        .refund_created_at
+        .get_or_insert_with(ic_cdk::api::time);
+    DepositStorage::update(deposit.clone())?;
+
+    // Back to the account that sent it, which for subaccount deposits need not be the user's
+    let refund_amount = deposit.ckbtc_amount - fee;
+    let refund_to = Account {
+        owner: deposit.sender.unwrap_or(deposit.user_principal),
+        subaccount: deposit.sender_subaccount.clone().map(Into::into),
+    };
+    match transfer_ckbtc(
+        deposit.subaccount.clone(),
+        refund_to,
+        refund_amount,
+        refund_created_at,
+        None,
+    )
+    .await
+    {
+        Ok(refund_block_index) => {
+            deposit.mark_refunded(refund_block_index);
+            // The block is settled, it must never be credited afterwards
+            ProcessedDepositsStorage::insert(deposit.block_index, deposit.user_principal)?;
+            DepositRetryStorage::remove(deposit.id);
+            DepositStorage::update(deposit.clone())?;
+            Ok(deposit)
+        }
+        Err(e) => {
+            deposit.last_error = Some(format!("Failed to refund ckBTC: {}", e));
+            deposit.updated_at = get_current_timestamp();
+            DepositStorage::update(deposit)?;
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn get_ckbtc_fee() -> Result<u64> {
+    match ckbtc_ledger_service().icrc_1_fee().await {
+        Ok((fee,)) => utils::nat_to_u64(&fee),
+        Err(e) => Err(BitcoinUSTBillsError::LedgerError(format!(
+            "Failed to get ckBTC fee: {:?}",
+            e
+        ))),
+    }
+}
+
+fn deposit_audit_outcome(result: &Result<Deposit>) -> Option<String> {
+    match result {
+        Ok(deposit) => audit_log::snapshot(&deposit.status),
+        Err(e) => Some(format!("Error: {}", e)),
+    }
 }
 
 /// Get user's OUSG balance
@@ -923,10 +2023,7 @@ pub async fn get_ousg_balance() -> Result<u64> {
         subaccount: None,
     };
 
-    let principal = Principal::from_text(OUSG_LEDGER_CANISTER_ID).map_err(|e| {
-        BitcoinUSTBillsError::StorageError(format!("Invalid OUSG principal: {:?}", e))
-    })?;
-    let service = OusgLedgerService(principal);
+    let service = ousg_ledger_service();
     let result = service.icrc_1_balance_of(account).await;
 
     result
@@ -934,25 +2031,61 @@ pub async fn get_ousg_balance() -> Result<u64> {
         .map(|(balance,)| balance.0.to_u64_digits()[0])
 }
 
-/// Get current BTC price
-#[update]
-pub async fn get_current_btc_price() -> Result<f64> {
-    get_btc_price().await.map_err(|e| {
-        BitcoinUSTBillsError::StorageError(format!("Failed to get BTC price: {:?}", e))
-    })
+/// Get the latest known BTC price, for display
+///
+/// Served from the last good oracle rate or the price history, deposits and redemptions are
+/// priced separately when they go through.
+#[query]
+pub fn get_current_btc_price() -> Result<BtcUsdRate> {
+    price_oracle::latest_known_rate().map(|observation| observation.rate)
+}
+
+/// Get sampled BTC/USD rates between two timestamps (seconds), oldest first
+#[query]
+pub fn get_btc_price_history(
+    from_timestamp: u64,
+    to_timestamp: u64,
+    limit: u64,
+) -> Vec<PriceSample> {
+    let limit = limit.min(MAX_PRICE_HISTORY_PAGE_SIZE);
+    PriceHistoryStorage::get_range(from_timestamp, to_timestamp, limit)
+}
+
+/// Get the time-weighted average BTC/USD rate over the last `window_seconds`
+#[query]
+pub fn get_btc_twap(window_seconds: u64) -> Result<PriceObservation> {
+    if !(MIN_TWAP_WINDOW_SECONDS..=MAX_TWAP_WINDOW_SECONDS).contains(&window_seconds) {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "window_seconds must be between {} and {}",
+            MIN_TWAP_WINDOW_SECONDS, MAX_TWAP_WINDOW_SECONDS
+        )));
+    }
+    price_history::twap(
+        window_seconds,
+        get_current_timestamp(),
+        &PriceOracleStorage::get_config(),
+    )
+    .map_err(BitcoinUSTBillsError::PriceUnavailable)
 }
 
-/// Calculate USD value of ckBTC amount
+// A day of five-minute samples
+const MAX_PRICE_HISTORY_PAGE_SIZE: u64 = 288;
+
+/// Calculate USD value of ckBTC amount (rounded down)
 #[query]
-pub fn calculate_ckbtc_usd_value(ckbtc_amount: u64, btc_price_usd: f64) -> f64 {
-    convert_ckbtc_to_usd(ckbtc_amount, btc_price_usd)
+pub fn calculate_ckbtc_usd_value(ckbtc_amount: u64, btc_price_usd: BtcUsdRate) -> Result<UsdCents> {
+    CkbtcE8s(ckbtc_amount)
+        .to_usd(btc_price_usd, Rounding::Down)
+        .ok_or_else(|| BitcoinUSTBillsError::validation_error("Amount out of range"))
 }
 
-/// Calculate OUSG tokens for USD amount
+/// Calculate OUSG tokens for USD amount (rounded down)
 #[query]
-pub fn calculate_ousg_for_usd(usd_amount: f64) -> u64 {
-    let ousg_tokens = usd_amount / 5000.0; // Each OUSG = $5000
-    (ousg_tokens * 1_000_000.0) as u64 // Convert to OUSG units (6 decimals)
+pub fn calculate_ousg_for_usd(usd_amount: UsdCents) -> Result<u64> {
+    usd_amount
+        .to_ousg(OUSG_UNIT_PRICE, Rounding::Down)
+        .map(|ousg| ousg.0)
+        .ok_or_else(|| BitcoinUSTBillsError::validation_error("Amount out of range"))
 }
 
 /// Approve OUSG tokens for redemption (user must call this first)
@@ -960,10 +2093,7 @@ pub fn calculate_ousg_for_usd(usd_amount: f64) -> u64 {
 pub async fn approve_ousg_for_redemption(ousg_amount: u64) -> Result<u64> {
     let _caller = ic_cdk::api::msg_caller();
 
-    let principal = Principal::from_text(OUSG_LEDGER_CANISTER_ID).map_err(|e| {
-        BitcoinUSTBillsError::StorageError(format!("Invalid OUSG principal: {:?}", e))
-    })?;
-    let service = OusgLedgerService(principal);
+    let service = ousg_ledger_service();
 
     // Approve the canister to spend user's OUSG tokens
     let approve_args = ApproveArgs {
@@ -1004,9 +2134,9 @@ pub async fn approve_ousg_for_redemption(ousg_amount: u64) -> Result<u64> {
     }
 }
 
-/// Redeem OUSG tokens for ckBTC
+/// Redeem OUSG tokens for ckBTC, at the price of `quote_id` if given
 #[update]
-pub async fn redeem_ousg_tokens(ousg_amount: u64) -> Result<u64> {
+pub async fn redeem_ousg_tokens(ousg_amount: u64, quote_id: Option<String>) -> Result<u64> {
     let caller = ic_cdk::api::msg_caller();
 
     // Check if user is registered and eligible
@@ -1023,6 +2153,94 @@ pub async fn redeem_ousg_tokens(ousg_amount: u64) -> Result<u64> {
     //     return Err(BitcoinUSTBillsError::KYCNotVerified);
     // }
 
+    // A request the quote does not cover is rejected before the quote is used up
+    let btc_price = match &quote_id {
+        Some(quote_id) => {
+            let quote = quotes::get(quote_id)?;
+            if quote.ousg_amount != ousg_amount {
+                return Err(BitcoinUSTBillsError::ValidationError(format!(
+                    "Quote {} is for {} OUSG e6s",
+                    quote_id, quote.ousg_amount
+                )));
+            }
+            quote.btc_price_usd
+        }
+        // Fails closed: no redemption is priced without an acceptable rate
+        None => price_oracle::observe_pricing_rate().await?.rate,
+    };
+
+    let (usd_value, ckbtc_amount) = price_redemption(ousg_amount, btc_price)?;
+
+    // A quote is claimed before the first await, so it cannot back two redemptions
+    if let Some(quote_id) = &quote_id {
+        quotes::claim(quote_id, caller, QuoteSide::Redemption)?;
+    }
+
+    // Check if we have enough ckBTC in reserve
+    // TODO: Implement ckBTC reserve balance check
+    // For now, we'll assume we have enough
+
+    // Record the redemption before touching any ledger
+    let mut redemption = Redemption::new(
+        generate_redemption_id(),
+        caller,
+        ousg_amount,
+        ckbtc_amount,
+        usd_value,
+        btc_price,
+    );
+    redemption.quote_id = quote_id;
+    RedemptionStorage::insert(redemption.clone())?;
+
+    // Burn OUSG tokens from user
+    let burn_block_index = match burn_ousg_tokens(caller, ousg_amount).await {
+        Ok(block_index) => block_index,
+        Err(e) => {
+            redemption.mark_failed(format!("Burn failed: {}", e));
+            RedemptionStorage::update(redemption)?;
+            return Err(e);
+        }
+    };
+    redemption.mark_burned(burn_block_index);
+    RedemptionStorage::update(redemption.clone())?;
+
+    // Update user balance
+    let mut updated_user = user;
+    updated_user.total_invested = updated_user.total_invested.saturating_sub(ousg_amount);
+    if let Err(e) = UserStorage::update(updated_user) {
+        ic_cdk::println!(
+            "Failed to update user after burn for redemption {}: {:?}",
+            redemption.id,
+            e
+        );
+    }
+
+    // Transfer ckBTC to user
+    let redemption = pay_out_redemption(redemption).await?;
+    match redemption.status {
+        RedemptionStatus::PaidOut => Ok(ckbtc_amount),
+        _ => {
+            // ckBTC leg failed after the burn - give the user their OUSG back, unless the
+            // ledger shows the payout went through despite the error
+            let payout_error = redemption.last_error.clone().unwrap_or_default();
+            match refund_redemption(redemption).await {
+                Ok(refunded) if refunded.status == RedemptionStatus::PaidOut => Ok(ckbtc_amount),
+                Ok(refunded) => Err(BitcoinUSTBillsError::LedgerError(format!(
+                    "ckBTC payout failed ({}), OUSG re-minted for redemption {}",
+                    payout_error, refunded.id
+                ))),
+                Err(e) => Err(BitcoinUSTBillsError::LedgerError(format!(
+                    "ckBTC payout failed ({}), refund pending manual review: {}",
+                    payout_error, e
+                ))),
+            }
+        }
+    }
+}
+
+// USD value and ckBTC payout of redeeming `ousg_amount`, both steps round down so the payout
+// never exceeds the burned OUSG's value
+fn price_redemption(ousg_amount: u64, btc_price: BtcUsdRate) -> Result<(UsdCents, u64)> {
     // Check minimum redeem amount (1 OUSG token = $5000)
     if ousg_amount < 1_000_000 {
         return Err(BitcoinUSTBillsError::ValidationError(
@@ -1030,79 +2248,399 @@ pub async fn redeem_ousg_tokens(ousg_amount: u64) -> Result<u64> {
         ));
     }
 
-    // Get current BTC price
-    let btc_price = match get_btc_price().await {
-        Ok(price) => price,
+    let out_of_range = || BitcoinUSTBillsError::validation_error("Redeem amount out of range");
+    let usd_value = convert_ousg_to_usd(ousg_amount).ok_or_else(out_of_range)?;
+    let ckbtc_amount = usd_value
+        .to_ckbtc(btc_price, Rounding::Down)
+        .ok_or_else(out_of_range)?
+        .0;
+    Ok((usd_value, ckbtc_amount))
+}
+
+/// Lock in a price for a deposit of `amount` ckBTC e8s or a redemption of `amount` OUSG e6s
+///
+/// Pass the quote's ID to notify_deposit or redeem_ousg_tokens before it expires to trade at
+/// its price. The quote carries a spread over the oracle rate and can be used once.
+#[update]
+pub async fn request_quote(side: QuoteSide, amount: u64) -> Result<Quote> {
+    let caller = ic_cdk::api::msg_caller();
+    if UserStorage::get(&caller).is_err() {
+        return Err(BitcoinUSTBillsError::UserNotFound);
+    }
+
+    // Fails closed like the trades themselves
+    let observation = price_oracle::observe_pricing_rate().await?;
+    let btc_price = quotes::quoted_price(observation.rate, side)
+        .ok_or_else(|| BitcoinUSTBillsError::validation_error("BTC price out of range"))?;
+
+    let (ckbtc_amount, ousg_amount, usd_value) = match side {
+        QuoteSide::Deposit => {
+            let usd_value = check_deposit_amount(&caller, amount, btc_price).map_err(
+                |(DepositCheckError::Rejected(e) | DepositCheckError::Retryable(e))| {
+                    BitcoinUSTBillsError::ValidationError(e)
+                },
+            )?;
+            let ousg_amount = usd_value
+                .to_ousg(OUSG_UNIT_PRICE, Rounding::Down)
+                .ok_or_else(|| {
+                    BitcoinUSTBillsError::validation_error("Deposit amount out of range")
+                })?
+                .0;
+            (amount, ousg_amount, usd_value)
+        }
+        QuoteSide::Redemption => {
+            let (usd_value, ckbtc_amount) = price_redemption(amount, btc_price)?;
+            (ckbtc_amount, amount, usd_value)
+        }
+    };
+
+    quotes::issue(Quote {
+        id: String::new(),
+        user_principal: caller,
+        side,
+        btc_price_usd: btc_price,
+        mid_price_usd: observation.rate,
+        price_timestamp: observation.timestamp,
+        spread_bps: quotes::QUOTE_SPREAD_BPS,
+        ckbtc_amount,
+        ousg_amount,
+        usd_value,
+        created_at: 0,
+        expires_at: 0,
+        used_at: None,
+    })
+    .await
+}
+
+/// Get one of the caller's quotes
+#[query]
+pub fn get_quote(quote_id: String) -> Result<Quote> {
+    let quote = quotes::get(&quote_id)?;
+    if quote.user_principal != ic_cdk::api::msg_caller() {
+        return Err(BitcoinUSTBillsError::Unauthorized);
+    }
+    Ok(quote)
+}
+
+// The ckBTC ledger deduplicates transfers for 24 hours and rejects older keys as TooOld
+pub(crate) const CKBTC_DEDUP_WINDOW_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Attempts the ckBTC payout of a burned redemption and persists the outcome
+///
+/// Every attempt reuses `payout_created_at`, so retrying a payout that actually went through
+/// resolves to the original ledger block instead of paying twice. Once the key is too old for
+/// the ledger to deduplicate, the ledger is searched for an earlier payout under it and only
+/// if there is none the payout is retried under a fresh key.
+async fn pay_out_redemption(mut redemption: Redemption) -> Result<Redemption> {
+    if ic_cdk::api::time().saturating_sub(redemption.payout_created_at) >= CKBTC_DEDUP_WINDOW_NANOS
+    {
+        match find_redemption_payout(&redemption).await {
+            Ok(Some(block_index)) => redemption.mark_paid_out(block_index),
+            Ok(None) => redemption.payout_created_at = ic_cdk::api::time(),
+            Err(e) => {
+                redemption.mark_failed(format!("Could not rule out an earlier payout: {}", e))
+            }
+        }
+        if redemption.status != RedemptionStatus::Burned {
+            RedemptionStorage::update(redemption.clone())?;
+            return Ok(redemption);
+        }
+    }
+
+    redemption.payout_attempts += 1;
+    RedemptionStorage::update(redemption.clone())?;
+
+    match transfer_ckbtc_to_user(
+        redemption.user_principal,
+        redemption.ckbtc_amount,
+        redemption.payout_created_at,
+    )
+    .await
+    {
+        Ok(block_index) => redemption.mark_paid_out(block_index),
         Err(e) => {
             ic_cdk::println!(
-                "Failed to get BTC price from XRC: {:?}, using hardcoded price for testing",
+                "ckBTC payout for redemption {} failed: {}",
+                redemption.id,
                 e
             );
-            100000.0
+            redemption.mark_failed(e.to_string());
         }
+    }
+
+    RedemptionStorage::update(redemption.clone())?;
+    Ok(redemption)
+}
+
+// Ledger block of a payout sent under the redemption's current payout key, if any
+async fn find_redemption_payout(
+    redemption: &Redemption,
+) -> std::result::Result<Option<u64>, String> {
+    let recipient = Account {
+        owner: redemption.user_principal,
+        subaccount: None,
     };
+    ledger_blocks::find_sent_transfer(
+        None,
+        &recipient,
+        Some(CKBTC_PAYOUT_MEMO.as_slice()),
+        redemption.payout_created_at,
+    )
+    .await
+}
 
-    // Calculate USD value of OUSG tokens to redeem
-    let usd_value = (ousg_amount as f64) / 1_000_000.0 * 5000.0; // Convert OUSG units to USD
-    let ckbtc_amount = convert_usd_to_ckbtc(usd_value, btc_price);
+/// Compensates a failed redemption by re-minting the burned OUSG to the user
+///
+/// A payout call that failed may still have been executed by the ledger, and `transfer_ckbtc`
+/// reports such a call error like a rejection. So before the first re-mint the ledger is
+/// searched for the payout, a redemption that was paid after all is marked as such instead.
+async fn refund_redemption(mut redemption: Redemption) -> Result<Redemption> {
+    if !redemption.needs_compensation() {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Redemption {} is not awaiting compensation",
+            redemption.id
+        )));
+    }
 
-    // Check if we have enough ckBTC in reserve
-    // TODO: Implement ckBTC reserve balance check
-    // For now, we'll assume we have enough
+    // Leave the Failed state before the await so a concurrent retry or refund is rejected
+    redemption.status = RedemptionStatus::Refunded;
+    RedemptionStorage::update(redemption.clone())?;
 
-    // Burn OUSG tokens from user
-    match burn_ousg_tokens(caller, ousg_amount).await {
-        Ok(_) => {
-            // Update user balance
-            let mut updated_user = user;
-            updated_user.total_invested = updated_user.total_invested.saturating_sub(ousg_amount);
-            if let Err(e) = UserStorage::update(updated_user) {
-                return Err(BitcoinUSTBillsError::StorageError(format!(
-                    "Failed to update user: {:?}",
-                    e
-                )));
+    if redemption.refund_created_at.is_none() {
+        match find_redemption_payout(&redemption).await {
+            Ok(Some(block_index)) => {
+                redemption.mark_paid_out(block_index);
+                RedemptionStorage::update(redemption.clone())?;
+                return Ok(redemption);
             }
-
-            // Transfer ckBTC to user
-            match transfer_ckbtc_to_user(caller, ckbtc_amount).await {
-                Ok(_) => Ok(ckbtc_amount),
-                Err(e) => Err(BitcoinUSTBillsError::StorageError(format!(
-                    "Failed to transfer ckBTC to user: {:?}",
-                    e
-                ))),
+            Ok(None) => {}
+            Err(e) => {
+                let error = format!("Could not rule out an earlier payout: {}", e);
+                redemption.mark_failed(error.clone());
+                RedemptionStorage::update(redemption)?;
+                return Err(BitcoinUSTBillsError::LedgerError(error));
             }
         }
-        Err(e) => Err(e),
+    }
+
+    // The re-mint key is stored before the await, a retried refund that already went through
+    // is a Duplicate. Once it is set the payout can no longer be retried.
+    let refund_created_at = *redemption
+        .refund_created_at
+        .get_or_insert_with(ic_cdk::api::time);
+    RedemptionStorage::update(redemption.clone())?;
+
+    let refund_block_index = match mint_ousg_tokens(
+        redemption.user_principal,
+        redemption.ousg_amount,
+        refund_created_at,
+    )
+    .await
+    {
+        Ok(block_index) => block_index,
+        Err(e) => {
+            ic_cdk::println!("Re-mint for redemption {} failed: {:?}", redemption.id, e);
+            redemption.mark_failed(format!("Refund failed: {}", e));
+            RedemptionStorage::update(redemption)?;
+            return Err(e);
+        }
+    };
+
+    redemption.mark_refunded(refund_block_index);
+    RedemptionStorage::update(redemption.clone())?;
+
+    if let Ok(mut user) = UserStorage::get(&redemption.user_principal) {
+        user.total_invested += redemption.ousg_amount;
+        UserStorage::update(user)?;
+    }
+
+    Ok(redemption)
+}
+
+/// Get user's redemption history
+#[query]
+pub fn get_user_redemptions() -> Result<Vec<Redemption>> {
+    let caller = ic_cdk::api::msg_caller();
+    Ok(RedemptionStorage::get_by_user(&caller))
+}
+
+/// Get redemption by ID
+#[query]
+pub fn get_redemption(redemption_id: u64) -> Result<Redemption> {
+    let redemption = RedemptionStorage::get(redemption_id)?;
+
+    // Only allow user or admin to see the redemption
+    if ic_cdk::api::msg_caller() != redemption.user_principal {
+        guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+    }
+
+    Ok(redemption)
+}
+
+/// Get redemptions whose ckBTC payout failed after the burn
+#[query]
+pub fn admin_get_failed_redemptions() -> Result<Vec<Redemption>> {
+    guard::assert_any_role(&[Role::TreasuryOperator, Role::Auditor])?;
+
+    Ok(RedemptionStorage::get_by_status(RedemptionStatus::Failed)
+        .into_iter()
+        .filter(|redemption| redemption.needs_compensation())
+        .collect())
+}
+
+/// Retry the ckBTC payout of a failed redemption
+#[update]
+pub async fn admin_retry_redemption_payout(redemption_id: u64) -> Result<Redemption> {
+    guard::assert_role(Role::TreasuryOperator)?;
+
+    let mut redemption = RedemptionStorage::get(redemption_id)?;
+    if !redemption.needs_compensation() {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Redemption {} is not awaiting compensation",
+            redemption_id
+        )));
+    }
+    // The re-mint may have gone through even if it reported an error, paying out as well
+    // could compensate the user twice. Such a redemption can only retry its refund.
+    if redemption.refund_created_at.is_some() {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "A refund of redemption {} was already attempted, retry the refund instead",
+            redemption_id
+        )));
+    }
+
+    // Move back to Burned before the await so concurrent retries or refunds are rejected
+    let before = audit_log::snapshot(&redemption.status);
+    redemption.status = RedemptionStatus::Burned;
+    RedemptionStorage::update(redemption.clone())?;
+
+    let result = pay_out_redemption(redemption).await;
+    audit_log::record(
+        AuditAction::RedemptionPayoutRetried,
+        redemption_id,
+        before,
+        audit_outcome(&result),
+    );
+    result
+}
+
+/// Re-mint the burned OUSG of a failed redemption back to the user
+#[update]
+pub async fn admin_refund_redemption(redemption_id: u64) -> Result<Redemption> {
+    guard::assert_role(Role::TreasuryOperator)?;
+
+    let redemption = RedemptionStorage::get(redemption_id)?;
+    let before = audit_log::snapshot(&redemption.status);
+
+    let result = refund_redemption(redemption).await;
+    audit_log::record(
+        AuditAction::RedemptionRefunded,
+        redemption_id,
+        before,
+        audit_outcome(&result),
+    );
+    result
+}
+
+// Both outcomes of a manual compensation are worth keeping in the audit log
+fn audit_outcome(result: &Result<Redemption>) -> Option<String> {
+    match result {
+        Ok(redemption) => audit_log::snapshot(&redemption.status),
+        Err(e) => Some(format!("Error: {}", e)),
     }
 }
 
 /// Transfer ckBTC to user account
-async fn transfer_ckbtc_to_user(user: Principal, amount: u64) -> Result<u64> {
-    // Create transfer to user account
-    let _transfer_args = TransferArg {
-        from_subaccount: None,
-        to: Account {
-            owner: user,
-            subaccount: None,
-        },
+async fn transfer_ckbtc_to_user(user: Principal, amount: u64, created_at_time: u64) -> Result<u64> {
+    let to = Account {
+        owner: user,
+        subaccount: None,
+    };
+    // Memo marks it as a redemption payout
+    transfer_ckbtc(
+        None,
+        to,
+        amount,
+        created_at_time,
+        Some(CKBTC_PAYOUT_MEMO.to_vec()),
+    )
+    .await
+}
+
+/// Transfer ckBTC out of one of the canister's accounts
+///
+/// `created_at_time` is part of the ledger's deduplication key: retrying with the same value
+/// can never pay out twice, the ledger answers with `Duplicate` and the original block index.
+pub(crate) async fn transfer_ckbtc(
+    from_subaccount: Option<Vec<u8>>,
+    to: Account,
+    amount: u64,
+    created_at_time: u64,
+    memo: Option<Vec<u8>>,
+) -> Result<u64> {
+    let recipient = to.owner;
+    let transfer_args = TransferArg {
+        from_subaccount: from_subaccount.map(Into::into),
+        to,
         amount: candid::Nat::from(amount),
         fee: None,
-        memo: Some(vec![2, 1, 0, 3].into()), // Mark as redemption transfer
-        created_at_time: Some(ic_cdk::api::time()),
+        memo: memo.map(Into::into),
+        created_at_time: Some(created_at_time),
     };
 
-    // Execute transfer via ckBTC ledger
-    let _principal = Principal::from_text(CKBTC_LEDGER_CANISTER_ID).map_err(|e| {
-        BitcoinUSTBillsError::StorageError(format!("Invalid ckBTC principal: {:?}", e))
-    })?;
+    // The ckBTC ledger speaks the same ICRC-1/2 interface as the OUSG ledger
+    let service = ckbtc_ledger_service();
+    let result = service.icrc_1_transfer(transfer_args).await;
 
-    // Use the ckBTC ledger service (we need to implement this similar to OUSG)
-    // For now, let's simulate the transfer and assume it works
-    // In production, this would call the ckBTC ledger canister
+    match result {
+        Ok((TransferResult::Ok(block_index),)) => utils::nat_to_u64(&block_index),
+        // Same transfer was already executed, e.g. by an earlier attempt of this payout
+        Ok((TransferResult::Err(TransferError::Duplicate { duplicate_of }),)) => {
+            ic_cdk::println!(
+                "ckBTC payout to {} already executed in block {}",
+                recipient.to_text(),
+                duplicate_of
+            );
+            utils::nat_to_u64(&duplicate_of)
+        }
+        Ok((TransferResult::Err(e),)) => Err(map_ckbtc_transfer_error(e)),
+        Err(e) => Err(BitcoinUSTBillsError::LedgerError(format!(
+            "ckBTC transfer call failed: {:?}",
+            e
+        ))),
+    }
+}
 
-    // TODO: Implement actual ckBTC transfer using ckBTC ledger canister
-    // This is a placeholder that returns success
-    Ok(amount)
+/// Maps a ckBTC ledger transfer error into a platform error with an actionable message
+fn map_ckbtc_transfer_error(error: TransferError) -> BitcoinUSTBillsError {
+    let message = match error {
+        TransferError::InsufficientFunds { balance } => format!(
+            "Insufficient ckBTC reserve in canister (balance: {})",
+            balance
+        ),
+        TransferError::BadFee { expected_fee } => {
+            format!("Bad ckBTC fee, ledger expects {}", expected_fee)
+        }
+        TransferError::BadBurn { min_burn_amount } => {
+            format!("Amount below ckBTC minimum burn amount {}", min_burn_amount)
+        }
+        TransferError::TooOld => "ckBTC transfer expired (created_at_time too old)".to_string(),
+        TransferError::CreatedInFuture { ledger_time } => format!(
+            "ckBTC transfer created in the future (ledger time: {})",
+            ledger_time
+        ),
+        TransferError::TemporarilyUnavailable => {
+            "ckBTC ledger temporarily unavailable, retry later".to_string()
+        }
+        TransferError::Duplicate { duplicate_of } => {
+            format!("Duplicate ckBTC transfer of block {}", duplicate_of)
+        }
+        TransferError::GenericError {
+            message,
+            error_code,
+        } => format!("ckBTC ledger error {}: {}", error_code, message),
+    };
+    BitcoinUSTBillsError::LedgerError(message)
 }
 
 /// Burn OUSG tokens from user account
@@ -1113,10 +2651,7 @@ async fn burn_ousg_tokens(user: Principal, amount: u64) -> Result<u64> {
         subaccount: None,
     };
 
-    let principal = Principal::from_text(OUSG_LEDGER_CANISTER_ID).map_err(|e| {
-        BitcoinUSTBillsError::StorageError(format!("Invalid OUSG principal: {:?}", e))
-    })?;
-    let service = OusgLedgerService(principal);
+    let service = ousg_ledger_service();
 
     // Check user's current balance
     let balance_result = service.icrc_1_balance_of(user_account.clone()).await;
@@ -1216,9 +2751,254 @@ fn generate_deposit_id() -> u64 {
     id_string.parse().unwrap_or(0)
 }
 
+/// Generate unique EVM transaction ID
+fn generate_evm_transaction_id() -> u64 {
+    let id_string = generate_id();
+    id_string.parse().unwrap_or(0)
+}
+
+/// Generate unique redemption ID
+fn generate_redemption_id() -> u64 {
+    let id_string = generate_id();
+    id_string.parse().unwrap_or(0)
+}
+
+fn generate_proposal_id() -> u64 {
+    let id_string = generate_id();
+    id_string.parse().unwrap_or(0)
+}
+
 #[test]
 pub fn generate_candid() {
     candid::export_service!();
     std::fs::write("../distributed/backend/backend.did", __export_service())
         .expect("Failed to write backend.did");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::k256::ecdsa::{Signature as EcdsaSignature, SigningKey};
+    use ethers_core::types::H256;
+    use ethers_core::utils::{keccak256, secret_key_to_address};
+    use evm_rpc::{EthMainnetService, ProviderError, RpcError, RpcService};
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&[7u8; 32]).unwrap()
+    }
+
+    fn public_key(key: &SigningKey) -> Vec<u8> {
+        key.verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec()
+    }
+
+    #[test]
+    fn recovers_the_signer_from_an_eth_signature() {
+        let key = signing_key();
+        for message in [&b"first"[..], b"second", b"third", b"fourth"] {
+            let hash = keccak256(message);
+            let (signature, recovery_id) = key.sign_prehash_recoverable(&hash).unwrap();
+
+            let eth_signature =
+                to_eth_signature(&signature.to_bytes(), &hash, &public_key(&key)).unwrap();
+            assert_eq!(eth_signature.v, recovery_id.to_byte() as u64);
+            assert_eq!(
+                eth_signature.recover(H256::from(hash)).unwrap(),
+                secret_key_to_address(&key)
+            );
+        }
+    }
+
+    #[test]
+    fn normalizes_high_s_signatures() {
+        let key = signing_key();
+        let hash = keccak256(b"high s");
+        let (signature, _) = key.sign_prehash_recoverable(&hash).unwrap();
+        let high_s = EcdsaSignature::from_scalars(*signature.r(), -*signature.s()).unwrap();
+
+        let eth_signature = to_eth_signature(&high_s.to_bytes(), &hash, &public_key(&key)).unwrap();
+        let (_, low_s) = signature.split_bytes();
+        assert_eq!(eth_signature.s, U256::from_big_endian(&low_s));
+        assert_eq!(
+            eth_signature.recover(H256::from(hash)).unwrap(),
+            secret_key_to_address(&key)
+        );
+    }
+
+    #[test]
+    fn rejects_signatures_from_another_key() {
+        let hash = keccak256(b"message");
+        let (signature, _) = signing_key().sign_prehash_recoverable(&hash).unwrap();
+        let other_key = SigningKey::from_slice(&[9u8; 32]).unwrap();
+
+        assert!(to_eth_signature(&signature.to_bytes(), &hash, &public_key(&other_key)).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_inputs() {
+        let key = signing_key();
+        let hash = keccak256(b"message");
+        let (signature, _) = key.sign_prehash_recoverable(&hash).unwrap();
+
+        assert!(to_eth_signature(&signature.to_bytes()[..63], &hash, &public_key(&key)).is_err());
+        assert!(to_eth_signature(&[0u8; 64], &hash, &public_key(&key)).is_err());
+        assert!(to_eth_signature(&signature.to_bytes(), &hash, &[2u8; 33]).is_err());
+    }
+
+    const LOCAL_HASH: &str = "0xlocal";
+
+    fn interpret(
+        result: MultiSendRawTransactionResult,
+    ) -> std::result::Result<RawTransactionSubmission, String> {
+        interpret_send_raw_transaction_result(result, LOCAL_HASH.to_string())
+    }
+
+    fn provider_response(
+        provider: EthMainnetService,
+        status: SendRawTransactionStatus,
+    ) -> (RpcService, SendRawTransactionResult) {
+        (
+            RpcService::EthMainnet(provider),
+            SendRawTransactionResult::Ok(status),
+        )
+    }
+
+    #[test]
+    fn maps_consistent_send_statuses() {
+        assert_eq!(
+            interpret(MultiSendRawTransactionResult::Consistent(
+                SendRawTransactionResult::Ok(SendRawTransactionStatus::Ok(Some(
+                    "0xabc".to_string()
+                )))
+            )),
+            Ok(RawTransactionSubmission::Submitted {
+                transaction_hash: "0xabc".to_string()
+            })
+        );
+        // Providers that do not echo the hash fall back to the locally computed one
+        assert_eq!(
+            interpret(MultiSendRawTransactionResult::Consistent(
+                SendRawTransactionResult::Ok(SendRawTransactionStatus::Ok(None))
+            )),
+            Ok(RawTransactionSubmission::Submitted {
+                transaction_hash: LOCAL_HASH.to_string()
+            })
+        );
+        assert_eq!(
+            interpret(MultiSendRawTransactionResult::Consistent(
+                SendRawTransactionResult::Ok(SendRawTransactionStatus::NonceTooLow)
+            )),
+            Ok(RawTransactionSubmission::NonceTooLow)
+        );
+        assert_eq!(
+            interpret(MultiSendRawTransactionResult::Consistent(
+                SendRawTransactionResult::Ok(SendRawTransactionStatus::NonceTooHigh)
+            )),
+            Ok(RawTransactionSubmission::NonceTooHigh)
+        );
+        assert_eq!(
+            interpret(MultiSendRawTransactionResult::Consistent(
+                SendRawTransactionResult::Ok(SendRawTransactionStatus::InsufficientFunds)
+            )),
+            Ok(RawTransactionSubmission::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn consistent_rpc_errors_are_errors() {
+        assert!(interpret(MultiSendRawTransactionResult::Consistent(
+            SendRawTransactionResult::Err(RpcError::ProviderError(ProviderError::NoPermission))
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn any_accepting_provider_means_submitted() {
+        let result = MultiSendRawTransactionResult::Inconsistent(vec![
+            provider_response(
+                EthMainnetService::Alchemy,
+                SendRawTransactionStatus::Ok(Some("0xabc".to_string())),
+            ),
+            provider_response(
+                EthMainnetService::Ankr,
+                SendRawTransactionStatus::NonceTooLow,
+            ),
+        ]);
+
+        assert_eq!(
+            interpret(result),
+            Ok(RawTransactionSubmission::Submitted {
+                transaction_hash: LOCAL_HASH.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn inconsistent_rejections_are_errors() {
+        let result = MultiSendRawTransactionResult::Inconsistent(vec![
+            provider_response(
+                EthMainnetService::Alchemy,
+                SendRawTransactionStatus::NonceTooLow,
+            ),
+            provider_response(
+                EthMainnetService::Ankr,
+                SendRawTransactionStatus::InsufficientFunds,
+            ),
+            (
+                RpcService::EthMainnet(EthMainnetService::PublicNode),
+                SendRawTransactionResult::Err(RpcError::ProviderError(
+                    ProviderError::ProviderNotFound,
+                )),
+            ),
+        ]);
+
+        assert!(interpret(result).is_err());
+    }
+
+    fn transfer_request(contract_address: &str, recipient: &str) -> TransferRequest {
+        TransferRequest {
+            contract_address: contract_address.to_string(),
+            recipient: recipient.to_string(),
+            amount: "1000".to_string(),
+            from_caller_address: None,
+        }
+    }
+
+    #[test]
+    fn parses_transfer_addresses() {
+        let request = transfer_request(
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            "0x00000000000000000000000000000000000000ff",
+        );
+        let transfer = validate_transfer_request(&request).unwrap();
+
+        assert_eq!(transfer.amount, U256::from(1000));
+        let data = encode_transfer(transfer.recipient, transfer.amount);
+        assert_eq!(data.len(), 68);
+        assert_eq!(data[..4], [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(data[35], 0xff);
+        assert_eq!(data[66..], [0x03, 0xe8]);
+    }
+
+    #[test]
+    fn rejects_addresses_that_are_not_hex() {
+        let valid = "0x00000000000000000000000000000000000000ff";
+        let not_hex = "0xzz000000000000000000000000000000000000ff";
+
+        assert!(validate_transfer_request(&transfer_request(not_hex, valid)).is_err());
+        assert!(validate_transfer_request(&transfer_request(valid, not_hex)).is_err());
+        assert!(validate_transfer_request(&transfer_request(valid, "0xff")).is_err());
+    }
+
+    #[test]
+    fn one_nonce_lock_per_address() {
+        let lock = NonceLock::acquire("0xAbC0000000000000000000000000000000000001").unwrap();
+        assert!(NonceLock::acquire("0xabc0000000000000000000000000000000000001").is_none());
+        assert!(NonceLock::acquire("0xabc0000000000000000000000000000000000002").is_some());
+
+        drop(lock);
+        assert!(NonceLock::acquire("0xabc0000000000000000000000000000000000001").is_some());
+    }
+}
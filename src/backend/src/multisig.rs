@@ -0,0 +1,409 @@
+use candid::Principal;
+
+use crate::errors::{BitcoinUSTBillsError, Result};
+use crate::storage::{get_current_timestamp, MultisigPolicyStorage, TreasuryProposalStorage};
+use crate::types::{
+    AuditAction, MultisigPolicy, ProposalStatus, Role, TreasuryOperation, TreasuryProposal,
+};
+use crate::{audit_log, guard};
+
+/// Opens a proposal, the proposer's own approval is counted
+pub fn propose(operation: TreasuryOperation) -> Result<TreasuryProposal> {
+    guard::assert_role(operation.proposer_role())?;
+    validate_operation(&operation)?;
+
+    let caller = ic_cdk::api::msg_caller();
+    let policy = MultisigPolicyStorage::get();
+    let now = get_current_timestamp();
+
+    let proposal = TreasuryProposal {
+        id: crate::generate_proposal_id(),
+        operation,
+        proposer: caller,
+        approvals: vec![caller],
+        required_approvals: policy.required_approvals,
+        status: ProposalStatus::Open,
+        created_at: now,
+        expires_at: now + policy.proposal_ttl_seconds,
+        executed_by: None,
+        executed_at: None,
+        result: None,
+        updated_at: now,
+        execution_created_at: None,
+    };
+    TreasuryProposalStorage::insert(proposal.clone())?;
+
+    audit_log::record(
+        AuditAction::ProposalCreated,
+        proposal.id,
+        None,
+        audit_log::snapshot(&proposal.operation),
+    );
+    Ok(proposal)
+}
+
+/// Adds the caller's approval to an open proposal
+pub fn approve(proposal_id: u64) -> Result<TreasuryProposal> {
+    let caller = assert_approver()?;
+    let mut proposal = get_open_proposal(proposal_id)?;
+
+    if proposal.approvals.contains(&caller) {
+        return Err(BitcoinUSTBillsError::validation_error(
+            "Proposal already approved by caller",
+        ));
+    }
+
+    proposal.approvals.push(caller);
+    proposal.updated_at = get_current_timestamp();
+    TreasuryProposalStorage::update(proposal.clone())?;
+
+    audit_log::record(
+        AuditAction::ProposalApproved,
+        proposal.id,
+        None,
+        Some(format!(
+            "{}/{} approvals",
+            proposal.approvals.len(),
+            proposal.required_approvals
+        )),
+    );
+    Ok(proposal)
+}
+
+/// Runs an open proposal that reached its approval threshold
+///
+/// A failed mint or ckBTC withdrawal can be executed again, see `is_retryable`.
+pub async fn execute(proposal_id: u64) -> Result<TreasuryProposal> {
+    let caller = assert_approver()?;
+    let mut proposal = match TreasuryProposalStorage::get(proposal_id)? {
+        failed if failed.status == ProposalStatus::Failed && failed.operation.is_retryable() => {
+            failed
+        }
+        _ => get_open_proposal(proposal_id)?,
+    };
+
+    // Approvers who lost their role since approving no longer count
+    let valid_approvals = proposal
+        .approvals
+        .iter()
+        .filter(|approver| guard::is_treasury_approver(approver))
+        .count() as u64;
+    if valid_approvals < proposal.required_approvals as u64 {
+        return Err(BitcoinUSTBillsError::validation_error(format!(
+            "Proposal has {} of {} required approvals",
+            valid_approvals, proposal.required_approvals
+        )));
+    }
+
+    // Claim the proposal before the first await so it cannot run twice. The ledger key is
+    // stored with it, so a retry cannot mint or withdraw a second time.
+    proposal.status = ProposalStatus::Executing;
+    proposal.executed_by = Some(caller);
+    proposal.updated_at = get_current_timestamp();
+    let created_at = *proposal
+        .execution_created_at
+        .get_or_insert_with(ic_cdk::api::time);
+    TreasuryProposalStorage::update(proposal.clone())?;
+
+    let outcome = execute_operation(proposal.operation.clone(), created_at).await;
+
+    let now = get_current_timestamp();
+    match &outcome {
+        Ok(result) => {
+            proposal.status = ProposalStatus::Executed;
+            proposal.result = Some(result.clone());
+        }
+        Err(e) => {
+            proposal.status = ProposalStatus::Failed;
+            proposal.result = Some(e.to_string());
+        }
+    }
+    proposal.executed_at = Some(now);
+    proposal.updated_at = now;
+    TreasuryProposalStorage::update(proposal.clone())?;
+
+    audit_log::record(
+        AuditAction::ProposalExecuted,
+        proposal.id,
+        None,
+        audit_log::snapshot(&proposal.status),
+    );
+    Ok(proposal)
+}
+
+/// Withdraws an open proposal, allowed for its proposer and SuperAdmins
+pub fn cancel(proposal_id: u64) -> Result<TreasuryProposal> {
+    let mut proposal = get_open_proposal(proposal_id)?;
+    if ic_cdk::api::msg_caller() != proposal.proposer {
+        guard::assert_admin()?;
+    }
+
+    proposal.status = ProposalStatus::Cancelled;
+    proposal.updated_at = get_current_timestamp();
+    TreasuryProposalStorage::update(proposal.clone())?;
+
+    audit_log::record(
+        AuditAction::ProposalCancelled,
+        proposal.id,
+        audit_log::snapshot(&ProposalStatus::Open),
+        audit_log::snapshot(&proposal.status),
+    );
+    Ok(proposal)
+}
+
+/// Replaces the multi-signature policy, runs from an approved proposal
+pub fn set_policy(policy: MultisigPolicy) -> Result<()> {
+    validate_policy(&policy)?;
+    let before = MultisigPolicyStorage::get();
+    MultisigPolicyStorage::update(policy.clone())?;
+    audit_log::record(
+        AuditAction::MultisigPolicyUpdated,
+        "multisig_policy",
+        audit_log::snapshot(&before),
+        audit_log::snapshot(&policy),
+    );
+    Ok(())
+}
+
+fn assert_approver() -> Result<Principal> {
+    guard::assert_role(Role::TreasuryOperator)?;
+    Ok(ic_cdk::api::msg_caller())
+}
+
+// Loads a proposal that can still be approved or executed, expiring it if its time ran out
+fn get_open_proposal(proposal_id: u64) -> Result<TreasuryProposal> {
+    let mut proposal = TreasuryProposalStorage::get(proposal_id)?;
+
+    if proposal.is_expired(get_current_timestamp()) {
+        proposal.status = ProposalStatus::Expired;
+        proposal.updated_at = get_current_timestamp();
+        TreasuryProposalStorage::update(proposal.clone())?;
+    }
+
+    if proposal.status != ProposalStatus::Open {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Proposal {} is {:?}",
+            proposal_id, proposal.status
+        )));
+    }
+    Ok(proposal)
+}
+
+fn validate_policy(policy: &MultisigPolicy) -> Result<()> {
+    if policy.required_approvals == 0 {
+        return Err(BitcoinUSTBillsError::validation_error(
+            "At least one approval is required",
+        ));
+    }
+    if policy.required_approvals as u64 > guard::treasury_approver_count() {
+        return Err(BitcoinUSTBillsError::validation_error(
+            "Required approvals exceed the number of treasury approvers",
+        ));
+    }
+    if policy.proposal_ttl_seconds == 0 {
+        return Err(BitcoinUSTBillsError::validation_error(
+            "Proposal lifetime cannot be zero",
+        ));
+    }
+    Ok(())
+}
+
+// Rejects malformed operations up front instead of after collecting approvals
+fn validate_operation(operation: &TreasuryOperation) -> Result<()> {
+    match operation {
+        TreasuryOperation::MintOusg { ousg_amount, .. } if *ousg_amount == 0 => Err(
+            BitcoinUSTBillsError::validation_error("Mint amount must be positive"),
+        ),
+        TreasuryOperation::WithdrawCkbtc { ckbtc_amount, .. } if *ckbtc_amount == 0 => Err(
+            BitcoinUSTBillsError::validation_error("Withdrawal amount must be positive"),
+        ),
+        TreasuryOperation::MintOusg { recipient, .. }
+        | TreasuryOperation::WithdrawCkbtc { recipient, .. }
+            if *recipient == Principal::anonymous() =>
+        {
+            Err(BitcoinUSTBillsError::InvalidPrincipal)
+        }
+        TreasuryOperation::Erc20Transfer {
+            contract_address,
+            recipient,
+            amount,
+        } => crate::validate_transfer_request(&crate::TransferRequest {
+            contract_address: contract_address.clone(),
+            recipient: recipient.clone(),
+            amount: amount.clone(),
+            from_caller_address: None,
+        })
+        .map(|_| ())
+        .map_err(BitcoinUSTBillsError::ValidationError),
+        TreasuryOperation::SetEvmConfig(config) => config
+            .validate()
+            .map_err(BitcoinUSTBillsError::ValidationError),
+        TreasuryOperation::SetEvmNetwork(args) => crate::EvmConfigStorage::get()
+            .apply_network_args(args.clone())
+            .map_err(BitcoinUSTBillsError::ValidationError),
+        TreasuryOperation::SetGasPolicy(gas_policy) => crate::validate_gas_policy(gas_policy),
+        TreasuryOperation::SetMultisigPolicy(policy) => validate_policy(policy),
+        TreasuryOperation::SetPriceOracleConfig(args) => crate::PriceOracleStorage::get_config()
+            .apply_args(args.clone())
+            .map_err(BitcoinUSTBillsError::ValidationError),
+        TreasuryOperation::GrantRole { principal, role } => {
+            validate_role_change(principal, *role, true)
+        }
+        TreasuryOperation::RevokeRole { principal, role } => {
+            validate_role_change(principal, *role, false)
+        }
+        _ => Ok(()),
+    }
+}
+
+// Approver roles decide who signs off on proposals, so only they need a proposal. Revoking
+// one must leave enough approvers to reach the policy threshold.
+fn validate_role_change(principal: &Principal, role: Role, grant: bool) -> Result<()> {
+    if !role.approves_treasury() {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "The {:?} role is managed directly by SuperAdmins",
+            role
+        )));
+    }
+    if *principal == Principal::anonymous() {
+        return Err(BitcoinUSTBillsError::AnonymousCaller);
+    }
+
+    let holds_role = guard::get_roles(principal).contains(&role);
+    if grant {
+        if holds_role {
+            return Err(BitcoinUSTBillsError::ValidationError(format!(
+                "{} already holds the {:?} role",
+                principal, role
+            )));
+        }
+        return Ok(());
+    }
+    if !holds_role {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "{} does not hold the {:?} role",
+            principal, role
+        )));
+    }
+
+    let remains_approver = guard::get_roles(principal)
+        .iter()
+        .any(|held| *held != role && held.approves_treasury());
+    let approvers_after = if remains_approver {
+        guard::treasury_approver_count()
+    } else {
+        guard::treasury_approver_count().saturating_sub(1)
+    };
+    if approvers_after < MultisigPolicyStorage::get().required_approvals as u64 {
+        return Err(BitcoinUSTBillsError::validation_error(
+            "Revoking the role would leave fewer approvers than the policy requires",
+        ));
+    }
+    Ok(())
+}
+
+// Returns a short description of what happened, e.g. the ledger block or transaction hash.
+// Ledger transfers are sent with `created_at` (ns) as their created_at_time.
+async fn execute_operation(operation: TreasuryOperation, created_at: u64) -> Result<String> {
+    match operation {
+        TreasuryOperation::MintOusg {
+            recipient,
+            ousg_amount,
+        } => {
+            let block_index = crate::mint_ousg_tokens(recipient, ousg_amount, created_at).await?;
+            audit_log::record(
+                AuditAction::OusgMinted,
+                recipient,
+                None,
+                Some(format!("{} OUSG at block {}", ousg_amount, block_index)),
+            );
+            Ok(format!("Minted at block {}", block_index))
+        }
+        TreasuryOperation::WithdrawCkbtc {
+            recipient,
+            ckbtc_amount,
+        } => {
+            let block_index =
+                crate::transfer_ckbtc_to_user(recipient, ckbtc_amount, created_at).await?;
+            audit_log::record(
+                AuditAction::CkbtcWithdrawn,
+                recipient,
+                None,
+                Some(format!("{} e8s at block {}", ckbtc_amount, block_index)),
+            );
+            Ok(format!("Transferred at block {}", block_index))
+        }
+        TreasuryOperation::Erc20Transfer {
+            contract_address,
+            recipient,
+            amount,
+        } => {
+            let request = crate::TransferRequest {
+                contract_address,
+                recipient,
+                amount,
+                from_caller_address: None,
+            };
+            let transfer = crate::validate_transfer_request(&request)
+                .map_err(BitcoinUSTBillsError::ValidationError)?;
+            let caller = ic_cdk::api::msg_caller();
+
+            // Empty derivation path: sign from the canister (treasury) address
+            let response = crate::submit_erc20_transfer(caller, request, transfer, vec![]).await;
+            match (response.success, response.transaction_hash) {
+                (true, Some(transaction_hash)) => Ok(format!(
+                    "Submitted {} (EVM transaction {:?})",
+                    transaction_hash, response.evm_transaction_id
+                )),
+                _ => Err(BitcoinUSTBillsError::ValidationError(
+                    response
+                        .error_message
+                        .unwrap_or_else(|| "ERC-20 transfer failed".to_string()),
+                )),
+            }
+        }
+        TreasuryOperation::SetEvmConfig(config) => {
+            crate::set_evm_config(config).map(|_| "EVM config updated".to_string())
+        }
+        TreasuryOperation::SetEvmNetwork(args) => {
+            crate::set_evm_network(args).map(|_| "EVM network updated".to_string())
+        }
+        TreasuryOperation::SetGasPolicy(gas_policy) => {
+            crate::set_gas_policy(gas_policy).map(|_| "Gas policy updated".to_string())
+        }
+        TreasuryOperation::SetCanisterIds(args) => {
+            crate::set_canister_ids(args).map(|_| "Canister ids updated".to_string())
+        }
+        TreasuryOperation::SetMultisigPolicy(policy) => {
+            set_policy(policy).map(|_| "Multi-signature policy updated".to_string())
+        }
+        TreasuryOperation::SetPriceOracleConfig(args) => {
+            crate::set_price_oracle_config(args).map(|_| "Price oracle config updated".to_string())
+        }
+        // Re-checked because other proposals may have changed the roles since this one opened
+        TreasuryOperation::GrantRole { principal, role } => {
+            validate_role_change(&principal, role, true)?;
+            let before = guard::get_roles(&principal);
+            guard::grant_role(principal, role)?;
+            audit_log::record(
+                AuditAction::RoleGranted,
+                principal,
+                audit_log::snapshot(&before),
+                audit_log::snapshot(&guard::get_roles(&principal)),
+            );
+            Ok(format!("Granted {:?} to {}", role, principal))
+        }
+        TreasuryOperation::RevokeRole { principal, role } => {
+            validate_role_change(&principal, role, false)?;
+            let before = guard::get_roles(&principal);
+            guard::revoke_role(principal, role)?;
+            audit_log::record(
+                AuditAction::RoleRevoked,
+                principal,
+                audit_log::snapshot(&before),
+                audit_log::snapshot(&guard::get_roles(&principal)),
+            );
+            Ok(format!("Revoked {:?} from {}", role, principal))
+        }
+    }
+}
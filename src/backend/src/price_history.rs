@@ -0,0 +1,213 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use crate::price_oracle;
+use crate::storage::{get_current_timestamp, PriceHistoryStorage, PriceOracleStorage};
+use crate::types::{BtcUsdRate, PriceObservation, PriceOracleConfig, PriceSample, RateSource};
+
+// Samples are taken for timestamps on this grid, one per tick
+const SAMPLE_INTERVAL_SECONDS: u64 = 5 * 60;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(SAMPLE_INTERVAL_SECONDS);
+
+// Missed grid points (e.g. during an upgrade) are backfilled, at most this many per tick
+const MAX_SAMPLES_PER_TICK: u64 = 6;
+
+// Well beyond the longest TWAP window, older samples are pruned
+const HISTORY_RETENTION_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+thread_local! {
+    static SAMPLE_IN_PROGRESS: Cell<bool> = const { Cell::new(false) };
+}
+
+// Clears the in-progress flag even if a run is aborted by a trap
+struct SampleGuard;
+
+impl Drop for SampleGuard {
+    fn drop(&mut self) {
+        SAMPLE_IN_PROGRESS.with(|in_progress| in_progress.set(false));
+    }
+}
+
+/// Starts the price sampling timer, must be called from init and post_upgrade
+pub fn start_sampler() {
+    ic_cdk_timers::set_timer_interval(SAMPLE_INTERVAL, || ic_cdk::futures::spawn(sample_prices()));
+}
+
+/// Records the BTC/USD rate for every grid point since the last sample, then prunes
+pub async fn sample_prices() {
+    if SAMPLE_IN_PROGRESS.with(|in_progress| in_progress.replace(true)) {
+        return;
+    }
+    let _guard = SampleGuard;
+
+    let config = PriceOracleStorage::get_config();
+    let now = get_current_timestamp();
+    let current_slot = now - now % SAMPLE_INTERVAL_SECONDS;
+    let earliest_slot =
+        current_slot.saturating_sub((MAX_SAMPLES_PER_TICK - 1) * SAMPLE_INTERVAL_SECONDS);
+
+    // A fresh history starts at the current grid point, there is nothing to catch up on
+    let first_slot = match PriceHistoryStorage::latest() {
+        Some(latest) => {
+            let next_slot = latest.timestamp - latest.timestamp % SAMPLE_INTERVAL_SECONDS
+                + SAMPLE_INTERVAL_SECONDS;
+            next_slot.max(earliest_slot)
+        }
+        None => current_slot,
+    };
+
+    for slot in (first_slot..=current_slot).step_by(SAMPLE_INTERVAL_SECONDS as usize) {
+        // Rates are requested by timestamp so every sample sits exactly on the grid
+        match price_oracle::fetch_historical_rate(slot, &config).await {
+            Ok(rate) => PriceHistoryStorage::insert(PriceSample {
+                timestamp: slot,
+                rate,
+                fetched_at: get_current_timestamp(),
+            }),
+            Err(e) => ic_cdk::println!("Price history: no sample for {}: {}", slot, e),
+        }
+    }
+
+    PriceHistoryStorage::prune_before(now.saturating_sub(HISTORY_RETENTION_SECONDS));
+}
+
+/// Time-weighted average rate over the `window_seconds` before `now`
+///
+/// Every sample counts for as long as it was the newest one. Fails if the history does not
+/// cover the window, if its newest sample is stale, or if it has a gap longer than a rate
+/// may be old, so the average is never dominated by a price nobody observed recently.
+pub fn twap(
+    window_seconds: u64,
+    now: u64,
+    config: &PriceOracleConfig,
+) -> Result<PriceObservation, String> {
+    let window_start = now.saturating_sub(window_seconds);
+
+    let newest = PriceHistoryStorage::get_at_or_before(now).ok_or("the price history is empty")?;
+    let newest_age = now.saturating_sub(newest.timestamp);
+    if newest_age > config.max_rate_age_seconds {
+        return Err(format!(
+            "the newest price sample is {} seconds old",
+            newest_age
+        ));
+    }
+
+    // The sample in force when the window opens, followed by those inside it
+    let mut samples: Vec<PriceSample> = PriceHistoryStorage::get_at_or_before(window_start)
+        .into_iter()
+        .collect();
+    samples.extend(PriceHistoryStorage::get_range(
+        window_start + 1,
+        now,
+        u64::MAX,
+    ));
+
+    let first_timestamp = samples[0].timestamp;
+    if first_timestamp > window_start + SAMPLE_INTERVAL_SECONDS {
+        return Err(format!(
+            "the price history covers only {} of {} seconds",
+            now - first_timestamp,
+            window_seconds
+        ));
+    }
+
+    let mut weighted_sum: u128 = 0;
+    let mut covered: u128 = 0;
+    for (index, sample) in samples.iter().enumerate() {
+        let until = samples.get(index + 1).map_or(now, |next| next.timestamp);
+        if until - sample.timestamp > config.max_rate_age_seconds {
+            return Err(format!(
+                "the price history has a {} second gap after {}",
+                until - sample.timestamp,
+                sample.timestamp
+            ));
+        }
+        let weight = (until - sample.timestamp.max(window_start)) as u128;
+        weighted_sum += sample.rate.0 as u128 * weight;
+        covered += weight;
+    }
+
+    // Nothing is covered only for a sample taken exactly now with nothing before it
+    let rate = weighted_sum
+        .checked_div(covered)
+        .map_or(newest.rate, |average| BtcUsdRate(average as u64));
+
+    Ok(PriceObservation {
+        rate,
+        timestamp: newest.timestamp,
+        source: RateSource::Twap,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_000_450;
+
+    fn insert_samples(samples: &[(u64, u64)]) {
+        for &(timestamp, rate) in samples {
+            PriceHistoryStorage::insert(PriceSample {
+                timestamp,
+                rate: BtcUsdRate(rate),
+                fetched_at: timestamp,
+            });
+        }
+    }
+
+    #[test]
+    fn weights_each_sample_by_how_long_it_was_the_newest() {
+        insert_samples(&[
+            (999_400, 5_000),
+            (999_700, 1_000),
+            (1_000_000, 100),
+            (1_000_300, 200),
+        ]);
+        let config = PriceOracleConfig::default();
+
+        // The window opens at 999_850, the 999_700 sample only counts from there
+        let observation = twap(600, NOW, &config).unwrap();
+        assert_eq!(observation.rate, BtcUsdRate(350));
+        assert_eq!(observation.timestamp, 1_000_300);
+        assert_eq!(observation.source, RateSource::Twap);
+    }
+
+    #[test]
+    fn a_sample_taken_now_is_its_own_average() {
+        insert_samples(&[(NOW, 42)]);
+
+        let observation = twap(0, NOW, &PriceOracleConfig::default()).unwrap();
+        assert_eq!(observation.rate, BtcUsdRate(42));
+    }
+
+    #[test]
+    fn rejects_an_empty_history() {
+        assert!(twap(600, NOW, &PriceOracleConfig::default()).is_err());
+    }
+
+    #[test]
+    fn rejects_a_stale_newest_sample() {
+        let config = PriceOracleConfig::default();
+        insert_samples(&[(NOW - config.max_rate_age_seconds - 1, 100)]);
+
+        assert!(twap(600, NOW, &config).is_err());
+    }
+
+    #[test]
+    fn rejects_a_history_shorter_than_the_window() {
+        insert_samples(&[(999_900, 100), (1_000_200, 100)]);
+
+        assert!(twap(3_600, NOW, &PriceOracleConfig::default()).is_err());
+    }
+
+    #[test]
+    fn rejects_gaps_longer_than_the_max_rate_age() {
+        let config = PriceOracleConfig::default();
+        insert_samples(&[
+            (999_400, 100),
+            (999_400 + config.max_rate_age_seconds + 1, 100),
+        ]);
+
+        assert!(twap(900, NOW, &config).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+use std::cell::Cell;
+
+use ic_cdk::call::Call;
+
+use crate::errors::{BitcoinUSTBillsError, Result};
+use crate::exchange_rate_canister::{
+    Asset, AssetClass, ExchangeRate, ExchangeRateError, GetExchangeRateRequest,
+    GetExchangeRateResult,
+};
+use crate::price_history;
+use crate::storage::{get_current_timestamp, PriceHistoryStorage, PriceOracleStorage};
+use crate::types::{
+    BtcUsdRate, CachedRate, PriceObservation, PriceOracleConfig, RateSource, Rounding,
+};
+
+// Used in test mode when no test_rate is configured
+const DEFAULT_TEST_RATE: BtcUsdRate = BtcUsdRate::from_dollars(100_000);
+
+// The XRC charges up to 1B cycles per call and refunds what it does not use
+const XRC_CYCLES: u128 = 1_000_000_000;
+
+// Pending and RateLimited are retried, waiting 1, 2, then 4 consensus rounds
+const XRC_MAX_ATTEMPTS: u32 = 4;
+
+// Live XRC fetches are spaced at least this far apart while the last good rate is fresh,
+// calls in between use it. The XRC rate only changes once a minute anyway.
+const MIN_XRC_FETCH_INTERVAL_SECONDS: u64 = 60;
+
+thread_local! {
+    // When the last live fetch started (seconds), whether or not it succeeded
+    static LAST_XRC_FETCH: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// BTC/USD rate for minting and payouts, together with the time it applies to and its source
+///
+/// Fetches a fresh rate from the XRC and accepts it only if it passes the configured quality
+/// checks. Otherwise the last accepted rate is used while it is still fresh enough. With
+/// neither available the call fails closed, unless the oracle runs in explicit test mode.
+pub async fn observe_btc_usd_rate() -> Result<PriceObservation> {
+    let config = PriceOracleStorage::get_config();
+    let now = get_current_timestamp();
+
+    // Within the interval the last good rate is served while it is fresh. Once it is stale a
+    // recent fetch, failed or still running, does not keep this call from trying again.
+    let last_fetch = LAST_XRC_FETCH.with(|last_fetch| last_fetch.get());
+    if last_fetch.is_some_and(|at| now < at + MIN_XRC_FETCH_INTERVAL_SECONDS) {
+        if let Some(observation) = fresh_cached_rate(&config, now) {
+            return Ok(observation);
+        }
+    }
+    // Set before the await, so concurrent callers use the cache instead while it is fresh
+    LAST_XRC_FETCH.with(|last_fetch| last_fetch.set(Some(now)));
+
+    let rejection = match fetch_xrc_rate(None).await {
+        Ok(rate) => match accept_rate(&rate, &config, now) {
+            Ok(accepted) => {
+                PriceOracleStorage::set_last_good_rate(CachedRate {
+                    rate: accepted,
+                    rate_timestamp: rate.timestamp,
+                    fetched_at: now,
+                });
+                return Ok(PriceObservation {
+                    rate: accepted,
+                    timestamp: rate.timestamp,
+                    source: RateSource::Xrc,
+                });
+            }
+            Err(e) => e,
+        },
+        Err(e) => e,
+    };
+
+    cached_rate(&config, now, rejection)
+}
+
+/// Newest rate the oracle already knows, from the last good XRC rate or the price history
+///
+/// Never calls the XRC, so it is cheap enough for display purposes. Fails like
+/// `observe_btc_usd_rate` if neither is fresh enough.
+pub fn latest_known_rate() -> Result<PriceObservation> {
+    let config = PriceOracleStorage::get_config();
+    let now = get_current_timestamp();
+
+    if let Some(sample) = PriceHistoryStorage::latest() {
+        let cached_is_newer = PriceOracleStorage::get_last_good_rate()
+            .is_some_and(|cached| cached.rate_timestamp > sample.timestamp);
+        if !cached_is_newer && now.saturating_sub(sample.timestamp) <= config.max_rate_age_seconds {
+            return Ok(PriceObservation {
+                rate: sample.rate,
+                timestamp: sample.timestamp,
+                source: RateSource::Cache,
+            });
+        }
+    }
+    cached_rate(&config, now, "no recent rate is known".to_string())
+}
+
+// Fallback once no live rate can be used: the last good rate while it is fresh enough, then
+// the test rate in test mode
+fn cached_rate(
+    config: &PriceOracleConfig,
+    now: u64,
+    rejection: String,
+) -> Result<PriceObservation> {
+    if let Some(observation) = fresh_cached_rate(config, now) {
+        ic_cdk::println!("Price oracle: using cached rate, {}", rejection);
+        return Ok(observation);
+    }
+
+    if config.test_mode {
+        ic_cdk::println!(
+            "Price oracle: TEST MODE, using the fixed test rate because {}",
+            rejection
+        );
+        return Ok(PriceObservation {
+            rate: config.test_rate.unwrap_or(DEFAULT_TEST_RATE),
+            timestamp: now,
+            source: RateSource::TestFallback,
+        });
+    }
+
+    Err(BitcoinUSTBillsError::PriceUnavailable(rejection))
+}
+
+// The last good rate, unless it is older than the configured maximum age
+fn fresh_cached_rate(config: &PriceOracleConfig, now: u64) -> Option<PriceObservation> {
+    PriceOracleStorage::get_last_good_rate()
+        .filter(|cached| now.saturating_sub(cached.rate_timestamp) <= config.max_rate_age_seconds)
+        .map(|cached| PriceObservation {
+            rate: cached.rate,
+            timestamp: cached.rate_timestamp,
+            source: RateSource::Cache,
+        })
+}
+
+/// BTC/USD rate deposits and redemptions are priced at
+///
+/// With a TWAP window configured this is the time-weighted average over the price history,
+/// which a short-lived spike cannot move much. Without one it is the spot rate.
+pub async fn observe_pricing_rate() -> Result<PriceObservation> {
+    let config = PriceOracleStorage::get_config();
+    let Some(window_seconds) = config.twap_window_seconds else {
+        return observe_btc_usd_rate().await;
+    };
+
+    match price_history::twap(window_seconds, get_current_timestamp(), &config) {
+        Ok(observation) => Ok(observation),
+        // Test deployments rarely have enough history, the spot path ends in the test rate
+        Err(e) if config.test_mode => {
+            ic_cdk::println!("Price oracle: TEST MODE, no TWAP ({}), using spot", e);
+            observe_btc_usd_rate().await
+        }
+        Err(e) => Err(BitcoinUSTBillsError::PriceUnavailable(e)),
+    }
+}
+
+/// Rate for the minute starting at `timestamp`, held to the same quality checks as spot rates
+pub(crate) async fn fetch_historical_rate(
+    timestamp: u64,
+    config: &PriceOracleConfig,
+) -> std::result::Result<BtcUsdRate, String> {
+    let rate = fetch_xrc_rate(Some(timestamp)).await?;
+    accept_rate(&rate, config, timestamp)
+}
+
+async fn fetch_xrc_rate(timestamp: Option<u64>) -> std::result::Result<ExchangeRate, String> {
+    let mut attempt = 1;
+    loop {
+        let (code, error) = match call_xrc(timestamp).await {
+            Ok(rate) => return Ok(rate),
+            Err(failure) => failure,
+        };
+        PriceOracleStorage::record_xrc_error(&code);
+
+        let retryable = code == "Pending" || code == "RateLimited";
+        if !retryable || attempt >= XRC_MAX_ATTEMPTS {
+            return Err(error);
+        }
+        wait_rounds(1 << (attempt - 1)).await;
+        attempt += 1;
+    }
+}
+
+// Returns the error code (for the counters) and a message on failure. Without a timestamp
+// the XRC returns the rate for the start of the current minute.
+async fn call_xrc(timestamp: Option<u64>) -> std::result::Result<ExchangeRate, (String, String)> {
+    let request = GetExchangeRateRequest {
+        base_asset: Asset {
+            symbol: "BTC".to_string(),
+            class: AssetClass::Cryptocurrency,
+        },
+        quote_asset: Asset {
+            symbol: "USD".to_string(),
+            class: AssetClass::FiatCurrency,
+        },
+        timestamp,
+    };
+
+    let response = Call::unbounded_wait(crate::xrc_service().0, "get_exchange_rate")
+        .with_arg(request)
+        .with_cycles(XRC_CYCLES)
+        .await
+        .map_err(|e| {
+            (
+                "CallFailed".to_string(),
+                format!("Failed to call XRC: {}", e),
+            )
+        })?;
+
+    match response.candid::<GetExchangeRateResult>() {
+        Ok(GetExchangeRateResult::Ok(rate)) => Ok(rate),
+        Ok(GetExchangeRateResult::Err(e)) => Err((error_code(&e), format!("XRC error: {:?}", e))),
+        Err(e) => Err((
+            "DecodeFailed".to_string(),
+            format!("Failed to decode the XRC response: {}", e),
+        )),
+    }
+}
+
+fn error_code(error: &ExchangeRateError) -> String {
+    match error {
+        ExchangeRateError::Other { code, .. } => format!("Other({})", code),
+        other => format!("{:?}", other),
+    }
+}
+
+// A call cannot sleep, but every management canister round trip takes a consensus round
+async fn wait_rounds(rounds: u32) {
+    for _ in 0..rounds {
+        let _ = ic_cdk::management_canister::raw_rand().await;
+    }
+}
+
+// Checks the rate's quality and converts it from the reported decimals to BtcUsdRate's.
+// The age is measured from `requested_at`, which is now for spot rates.
+fn accept_rate(
+    rate: &ExchangeRate,
+    config: &PriceOracleConfig,
+    requested_at: u64,
+) -> std::result::Result<BtcUsdRate, String> {
+    let metadata = &rate.metadata;
+
+    if rate.rate == 0 {
+        return Err("XRC returned a zero rate".to_string());
+    }
+
+    let age = requested_at.saturating_sub(rate.timestamp);
+    if age > config.max_rate_age_seconds {
+        return Err(format!("XRC rate is {} seconds old", age));
+    }
+
+    if metadata.base_asset_num_received_rates < config.min_base_sources {
+        return Err(format!(
+            "only {} BTC sources answered, {} required",
+            metadata.base_asset_num_received_rates, config.min_base_sources
+        ));
+    }
+    for (asset, received, queried) in [
+        (
+            "BTC",
+            metadata.base_asset_num_received_rates,
+            metadata.base_asset_num_queried_sources,
+        ),
+        (
+            "USD",
+            metadata.quote_asset_num_received_rates,
+            metadata.quote_asset_num_queried_sources,
+        ),
+    ] {
+        if received.saturating_mul(100) < queried.saturating_mul(config.min_received_percent) {
+            return Err(format!(
+                "only {} of {} {} sources answered",
+                received, queried, asset
+            ));
+        }
+    }
+
+    // Standard deviation shares the rate's scale, compare in basis points of the rate
+    let std_dev_bps = metadata.standard_deviation as u128 * 10_000 / rate.rate as u128;
+    if std_dev_bps > config.max_std_dev_bps as u128 {
+        return Err(format!(
+            "sources disagree, standard deviation is {} bps of the rate",
+            std_dev_bps
+        ));
+    }
+
+    // Digits beyond BtcUsdRate's 8 decimals are below a millionth of a cent
+    BtcUsdRate::from_scaled(rate.rate, metadata.decimals, Rounding::Down).ok_or_else(|| {
+        format!(
+            "XRC rate {} with {} decimals is out of range",
+            rate.rate, metadata.decimals
+        )
+    })
+}
@@ -0,0 +1,277 @@
+use candid::Principal;
+use ethers_core::utils::keccak256;
+
+use crate::errors::{BitcoinUSTBillsError, Result};
+use crate::storage::{get_current_timestamp, QuoteStorage};
+use crate::types::{BtcUsdRate, Quote, QuoteSide, Rounding};
+
+// Taken off the oracle rate for deposits and added for redemptions, in basis points
+pub const QUOTE_SPREAD_BPS: u64 = 50;
+
+// How long a quote can be claimed by notify_deposit or redeem_ousg_tokens
+const QUOTE_TTL_SECONDS: u64 = 2 * 60;
+
+// Expired quotes are kept this long for support requests, then pruned
+const QUOTE_RETENTION_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+// Keeps the pruning done by each new quote small
+const MAX_PRUNED_PER_QUOTE: usize = 20;
+
+// Bytes of the keccak256 signature kept in the quote ID
+const SIGNATURE_BYTES: usize = 16;
+
+/// Rate a quote on `side` settles at, the spread always goes against the user
+pub fn quoted_price(mid_price: BtcUsdRate, side: QuoteSide) -> Option<BtcUsdRate> {
+    match side {
+        QuoteSide::Deposit => mid_price.scale_bps(10_000 - QUOTE_SPREAD_BPS, Rounding::Down),
+        QuoteSide::Redemption => mid_price.scale_bps(10_000 + QUOTE_SPREAD_BPS, Rounding::Up),
+    }
+}
+
+/// Signs and stores a priced quote, filling in its ID, creation time and expiry
+pub async fn issue(quote: Quote) -> Result<Quote> {
+    let signing_key = signing_key().await?;
+
+    // Nothing below awaits, so the sequence number cannot be taken twice
+    Ok(store(quote, &signing_key, get_current_timestamp()))
+}
+
+fn store(mut quote: Quote, signing_key: &[u8], now: u64) -> Quote {
+    QuoteStorage::prune_expired_before(
+        now.saturating_sub(QUOTE_RETENTION_SECONDS),
+        MAX_PRUNED_PER_QUOTE,
+    );
+
+    let sequence = QuoteStorage::next_sequence();
+    quote.created_at = now;
+    quote.expires_at = now + QUOTE_TTL_SECONDS;
+    quote.used_at = None;
+    quote.id = format!("{}-{}", sequence, sign(signing_key, sequence, &quote));
+    QuoteStorage::insert(sequence, quote.clone());
+    quote
+}
+
+/// Looks up a quote by ID, rejecting IDs whose signature does not match the stored quote
+pub fn get(quote_id: &str) -> Result<Quote> {
+    load(quote_id).map(|(_, quote)| quote)
+}
+
+fn load(quote_id: &str) -> Result<(u64, Quote)> {
+    let unknown = || BitcoinUSTBillsError::ValidationError("Unknown quote".to_string());
+
+    let (sequence, _) = quote_id.split_once('-').ok_or_else(unknown)?;
+    let sequence: u64 = sequence.parse().map_err(|_| unknown())?;
+    let quote = QuoteStorage::get(sequence).ok_or_else(unknown)?;
+    let signing_key = QuoteStorage::get_signing_key().ok_or_else(unknown)?;
+
+    let expected_id = format!("{}-{}", sequence, sign(&signing_key, sequence, &quote));
+    if quote.id != quote_id || expected_id != quote_id {
+        return Err(unknown());
+    }
+    Ok((sequence, quote))
+}
+
+/// Marks the caller's unexpired, unused quote as used and returns it
+///
+/// A quote is good for exactly one deposit or redemption, even if that later fails.
+pub fn claim(quote_id: &str, user: Principal, side: QuoteSide) -> Result<Quote> {
+    claim_at(quote_id, user, side, get_current_timestamp())
+}
+
+fn claim_at(quote_id: &str, user: Principal, side: QuoteSide, now: u64) -> Result<Quote> {
+    let (sequence, mut quote) = load(quote_id)?;
+
+    if quote.user_principal != user {
+        return Err(BitcoinUSTBillsError::Unauthorized);
+    }
+    if quote.side != side {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Quote {} is for a {:?}",
+            quote_id, quote.side
+        )));
+    }
+    if quote.used_at.is_some() {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Quote {} was already used",
+            quote_id
+        )));
+    }
+    if now > quote.expires_at {
+        return Err(BitcoinUSTBillsError::ValidationError(format!(
+            "Quote {} expired",
+            quote_id
+        )));
+    }
+
+    quote.used_at = Some(now);
+    QuoteStorage::insert(sequence, quote.clone());
+    Ok(quote)
+}
+
+// Generated once from raw_rand, it only has to stay unknown outside the canister
+async fn signing_key() -> Result<Vec<u8>> {
+    if let Some(signing_key) = QuoteStorage::get_signing_key() {
+        return Ok(signing_key);
+    }
+    let randomness = ic_cdk::management_canister::raw_rand().await.map_err(|e| {
+        BitcoinUSTBillsError::StorageError(format!(
+            "Failed to generate the quote signing key: {}",
+            e
+        ))
+    })?;
+
+    // Another quote may have set the key while this one waited
+    if let Some(signing_key) = QuoteStorage::get_signing_key() {
+        return Ok(signing_key);
+    }
+    QuoteStorage::set_signing_key(randomness.clone());
+    Ok(randomness)
+}
+
+// Covers every field a user relies on, the ID carries the sequence instead of itself
+fn sign(signing_key: &[u8], sequence: u64, quote: &Quote) -> String {
+    let mut unsigned = quote.clone();
+    unsigned.id = sequence.to_string();
+    unsigned.used_at = None;
+
+    let mut message = signing_key.to_vec();
+    message.extend(candid::encode_one(&unsigned).expect("quotes always encode"));
+    hex::encode(&keccak256(message)[..SIGNATURE_BYTES])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::UsdCents;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn alice() -> Principal {
+        Principal::from_slice(&[1; 29])
+    }
+
+    fn deposit_quote() -> Quote {
+        let signing_key = vec![3; 32];
+        QuoteStorage::set_signing_key(signing_key.clone());
+        store(
+            Quote {
+                id: String::new(),
+                user_principal: alice(),
+                side: QuoteSide::Deposit,
+                btc_price_usd: BtcUsdRate(99_500),
+                mid_price_usd: BtcUsdRate(100_000),
+                price_timestamp: NOW,
+                spread_bps: QUOTE_SPREAD_BPS,
+                ckbtc_amount: 100_000,
+                ousg_amount: 1_000_000,
+                usd_value: UsdCents(9_950),
+                created_at: 0,
+                expires_at: 0,
+                used_at: None,
+            },
+            &signing_key,
+            NOW,
+        )
+    }
+
+    fn is_unknown(result: Result<Quote>) -> bool {
+        matches!(result, Err(BitcoinUSTBillsError::ValidationError(message)) if message == "Unknown quote")
+    }
+
+    #[test]
+    fn spreads_go_against_the_user() {
+        let mid_price = BtcUsdRate(100_000);
+
+        assert_eq!(
+            quoted_price(mid_price, QuoteSide::Deposit),
+            Some(BtcUsdRate(99_500))
+        );
+        assert_eq!(
+            quoted_price(mid_price, QuoteSide::Redemption),
+            Some(BtcUsdRate(100_500))
+        );
+    }
+
+    #[test]
+    fn issued_quotes_can_be_looked_up() {
+        let quote = deposit_quote();
+
+        assert_eq!(quote.created_at, NOW);
+        assert_eq!(quote.expires_at, NOW + QUOTE_TTL_SECONDS);
+        assert_eq!(get(&quote.id).unwrap().ousg_amount, quote.ousg_amount);
+    }
+
+    #[test]
+    fn rejects_tampered_ids() {
+        let quote = deposit_quote();
+        let (sequence, signature) = quote.id.split_once('-').unwrap();
+
+        let mut forged_signature = signature.to_string();
+        forged_signature.replace_range(..1, if signature.starts_with('0') { "1" } else { "0" });
+        assert!(is_unknown(get(&format!(
+            "{}-{}",
+            sequence, forged_signature
+        ))));
+        assert!(is_unknown(get(&format!("{}0-{}", sequence, signature))));
+        assert!(is_unknown(get(sequence)));
+        assert!(is_unknown(get("not-a-quote")));
+    }
+
+    #[test]
+    fn rejects_quotes_altered_in_storage() {
+        let quote = deposit_quote();
+        let sequence: u64 = quote.id.split_once('-').unwrap().0.parse().unwrap();
+
+        let mut altered = quote.clone();
+        altered.btc_price_usd = BtcUsdRate(1);
+        QuoteStorage::insert(sequence, altered);
+        assert!(is_unknown(get(&quote.id)));
+    }
+
+    #[test]
+    fn rejects_ids_signed_with_another_key() {
+        let quote = deposit_quote();
+
+        QuoteStorage::set_signing_key(vec![4; 32]);
+        assert!(is_unknown(get(&quote.id)));
+    }
+
+    #[test]
+    fn claims_a_quote_once() {
+        let quote = deposit_quote();
+
+        let claimed = claim_at(&quote.id, alice(), QuoteSide::Deposit, NOW + 1).unwrap();
+        assert_eq!(claimed.used_at, Some(NOW + 1));
+        // Marking the quote as used keeps its signature valid
+        assert_eq!(get(&quote.id).unwrap().used_at, Some(NOW + 1));
+        assert!(claim_at(&quote.id, alice(), QuoteSide::Deposit, NOW + 2).is_err());
+    }
+
+    #[test]
+    fn rejects_claims_by_other_users() {
+        let quote = deposit_quote();
+        let bob = Principal::from_slice(&[2; 29]);
+
+        assert!(matches!(
+            claim_at(&quote.id, bob, QuoteSide::Deposit, NOW),
+            Err(BitcoinUSTBillsError::Unauthorized)
+        ));
+        assert!(claim_at(&quote.id, alice(), QuoteSide::Deposit, NOW).is_ok());
+    }
+
+    #[test]
+    fn rejects_claims_for_the_other_side() {
+        let quote = deposit_quote();
+
+        assert!(claim_at(&quote.id, alice(), QuoteSide::Redemption, NOW).is_err());
+        assert!(get(&quote.id).unwrap().used_at.is_none());
+    }
+
+    #[test]
+    fn rejects_expired_quotes() {
+        let quote = deposit_quote();
+
+        assert!(claim_at(&quote.id, alice(), QuoteSide::Deposit, quote.expires_at + 1).is_err());
+        assert!(claim_at(&quote.id, alice(), QuoteSide::Deposit, quote.expires_at).is_ok());
+    }
+}
@@ -3,6 +3,7 @@ use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemor
 use ic_stable_structures::{Cell, DefaultMemoryImpl, StableBTreeMap, Storable};
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::ops::Bound;
 
 use crate::errors::{BitcoinUSTBillsError, Result};
 use crate::types::*;
@@ -18,6 +19,27 @@ const VERIFIED_PURCHASES_LEDGER_MEMORY_ID: MemoryId = MemoryId::new(8);
 const FREE_KYC_SESSIONS_MEMORY_ID: MemoryId = MemoryId::new(11);
 const DEPOSITS_MEMORY_ID: MemoryId = MemoryId::new(12);
 const PROCESSED_DEPOSITS_MEMORY_ID: MemoryId = MemoryId::new(13);
+const REDEMPTIONS_MEMORY_ID: MemoryId = MemoryId::new(14);
+const EVM_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(15);
+const EVM_TRANSACTIONS_MEMORY_ID: MemoryId = MemoryId::new(16);
+const ETH_ADDRESSES_MEMORY_ID: MemoryId = MemoryId::new(17);
+const CANISTER_IDS_MEMORY_ID: MemoryId = MemoryId::new(18);
+const ROLE_ASSIGNMENTS_MEMORY_ID: MemoryId = MemoryId::new(19);
+const AUDIT_LOG_MEMORY_ID: MemoryId = MemoryId::new(20);
+const TREASURY_PROPOSALS_MEMORY_ID: MemoryId = MemoryId::new(21);
+const MULTISIG_POLICY_MEMORY_ID: MemoryId = MemoryId::new(22);
+const DEPOSIT_BLOCK_INDEX_MEMORY_ID: MemoryId = MemoryId::new(23);
+const DEPOSIT_RETRIES_MEMORY_ID: MemoryId = MemoryId::new(24);
+const DEPOSIT_SCAN_STATE_MEMORY_ID: MemoryId = MemoryId::new(25);
+const PRICE_ORACLE_CONFIG_MEMORY_ID: MemoryId = MemoryId::new(26);
+const LAST_GOOD_RATE_MEMORY_ID: MemoryId = MemoryId::new(27);
+const XRC_ERROR_COUNTS_MEMORY_ID: MemoryId = MemoryId::new(28);
+const PRICE_HISTORY_MEMORY_ID: MemoryId = MemoryId::new(29);
+const QUOTES_MEMORY_ID: MemoryId = MemoryId::new(30);
+const QUOTE_SIGNING_KEY_MEMORY_ID: MemoryId = MemoryId::new(31);
+const AUDIT_LOG_TIMESTAMPS_MEMORY_ID: MemoryId = MemoryId::new(32);
+const SCHEMA_VERSION_MEMORY_ID: MemoryId = MemoryId::new(33);
+const DEPOSIT_SWEEP_QUEUE_MEMORY_ID: MemoryId = MemoryId::new(34);
 
 // Thread-local storage for memory manager and stable data structures
 thread_local! {
@@ -72,19 +94,175 @@ thread_local! {
         )
     );
 
+    // ckBTC block index -> deposit id, lets notify_deposit find the deposit a block was claimed by
+    static DEPOSIT_BLOCK_INDEX: RefCell<StableBTreeMap<u64, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(DEPOSIT_BLOCK_INDEX_MEMORY_ID))
+        )
+    );
+
+    // Reconciler state of deposits that did not complete
+    static DEPOSIT_RETRIES: RefCell<StableBTreeMap<u64, DepositRetryState, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(DEPOSIT_RETRIES_MEMORY_ID))
+        )
+    );
+
+    // Ids of minted deposits whose ckBTC still sits in their subaccount, see Deposit::needs_sweep
+    static DEPOSIT_SWEEP_QUEUE: RefCell<StableBTreeMap<u64, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(DEPOSIT_SWEEP_QUEUE_MEMORY_ID))
+        )
+    );
+
+    // Ledger cursor of the deposit subaccount scan
+    static DEPOSIT_SCAN_STATE: RefCell<Cell<DepositScanState, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(DEPOSIT_SCAN_STATE_MEMORY_ID)),
+            DepositScanState::default()
+        )
+    );
+
+    // ============= PRICE ORACLE STORAGE STRUCTURES =============
+
+    static PRICE_ORACLE_CONFIG: RefCell<Cell<PriceOracleConfig, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(PRICE_ORACLE_CONFIG_MEMORY_ID)),
+            PriceOracleConfig::default()
+        )
+    );
+
+    // Survives upgrades, staleness is checked on every use
+    static LAST_GOOD_RATE: RefCell<Cell<Option<CachedRate>, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(LAST_GOOD_RATE_MEMORY_ID)),
+            None
+        )
+    );
+
+    // XRC failures by error code, for monitoring
+    static XRC_ERROR_COUNTS: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(XRC_ERROR_COUNTS_MEMORY_ID))
+        )
+    );
+
+    // Sampled BTC/USD rates by XRC timestamp, the basis for TWAP pricing
+    static PRICE_HISTORY: RefCell<StableBTreeMap<u64, PriceSample, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(PRICE_HISTORY_MEMORY_ID))
+        )
+    );
+
+    // ============= PRICE QUOTE STORAGE STRUCTURES =============
+
+    // Quotes by sequence number, which orders them by creation and expiry
+    static QUOTES: RefCell<StableBTreeMap<u64, Quote, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(QUOTES_MEMORY_ID))
+        )
+    );
+
+    // Secret the quote IDs are signed with, empty until the first quote
+    static QUOTE_SIGNING_KEY: RefCell<Cell<Vec<u8>, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(QUOTE_SIGNING_KEY_MEMORY_ID)),
+            Vec::new()
+        )
+    );
+
     static PROCESSED_DEPOSITS: RefCell<StableBTreeMap<u64, Principal, Memory>> = RefCell::new(
         StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(PROCESSED_DEPOSITS_MEMORY_ID))
         )
     );
 
+    // ============= REDEMPTION STORAGE STRUCTURES =============
+
+    static REDEMPTIONS: RefCell<StableBTreeMap<u64, Redemption, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(REDEMPTIONS_MEMORY_ID))
+        )
+    );
+
+    // ============= EVM CONFIG STORAGE STRUCTURES =============
+
+    static EVM_CONFIG: RefCell<Cell<EvmConfig, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(EVM_CONFIG_MEMORY_ID)),
+            EvmConfig::default()
+        )
+    );
+
+    static EVM_TRANSACTIONS: RefCell<StableBTreeMap<u64, EvmTransaction, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(EVM_TRANSACTIONS_MEMORY_ID))
+        )
+    );
+
+    // ============= PLATFORM CONFIG STORAGE STRUCTURES =============
+
+    // Roles held by each admin principal, checked by the guard
+    static ROLE_ASSIGNMENTS: RefCell<StableBTreeMap<Principal, RoleAssignment, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(ROLE_ASSIGNMENTS_MEMORY_ID))
+        )
+    );
+
+    // Append-only log of privileged actions, keyed by sequence number
+    static AUDIT_LOG: RefCell<StableBTreeMap<u64, AuditEntry, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_LOG_MEMORY_ID))
+        )
+    );
+
+    // (timestamp, id) of every audit entry, maps time filters to a range of ids
+    static AUDIT_LOG_TIMESTAMPS: RefCell<StableBTreeMap<(u64, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_LOG_TIMESTAMPS_MEMORY_ID))
+        )
+    );
+
+    // ============= MULTI-SIGNATURE STORAGE STRUCTURES =============
+
+    static TREASURY_PROPOSALS: RefCell<StableBTreeMap<u64, TreasuryProposal, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(TREASURY_PROPOSALS_MEMORY_ID))
+        )
+    );
+
+    static MULTISIG_POLICY: RefCell<Cell<MultisigPolicy, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MULTISIG_POLICY_MEMORY_ID)),
+            MultisigPolicy::default()
+        )
+    );
+
+    static CANISTER_IDS: RefCell<Cell<CanisterIds, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CANISTER_IDS_MEMORY_ID)),
+            CanisterIds::default()
+        )
+    );
+
+    // Per-principal derived Ethereum addresses (cached, deriving needs an update call)
+    static ETH_ADDRESSES: RefCell<StableBTreeMap<Principal, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(ETH_ADDRESSES_MEMORY_ID))
+        )
+    );
+
+    // Version of the stored data layout, 0 for canisters that predate it
+    static SCHEMA_VERSION: RefCell<Cell<u32, Memory>> = RefCell::new(
+        Cell::init(MEMORY_MANAGER.with(|m| m.borrow().get(SCHEMA_VERSION_MEMORY_ID)), 0)
+    );
 
 }
 
 // USTBill Storable implementation removed - not used in current implementation
 
 impl Storable for User {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(candid::encode_one(self).unwrap())
     }
 
@@ -103,7 +281,7 @@ impl Storable for User {
 // TokenHolding, Transaction, PlatformConfig, TreasuryRate Storable implementations removed - not used
 
 impl Storable for TradingMetrics {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(candid::encode_one(self).unwrap())
     }
 
@@ -120,7 +298,7 @@ impl Storable for TradingMetrics {
 }
 
 impl Storable for VerifiedBrokerPurchase {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(candid::encode_one(self).unwrap())
     }
 
@@ -139,7 +317,7 @@ impl Storable for VerifiedBrokerPurchase {
 // ============= FREE KYC STORABLE IMPLEMENTATIONS =============
 
 impl Storable for FreeKYCSession {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(candid::encode_one(self).unwrap())
     }
 
@@ -158,7 +336,28 @@ impl Storable for FreeKYCSession {
 // ============= DEPOSIT STORABLE IMPLEMENTATIONS =============
 
 impl Storable for Deposit {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    // Records written before the fixed-point money types are converted on read
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes)
+            .unwrap_or_else(|_| candid::decode_one::<LegacyDeposit>(&bytes).unwrap().into())
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+// ============= REDEMPTION STORABLE IMPLEMENTATIONS =============
+
+impl Storable for Redemption {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::Owned(candid::encode_one(self).unwrap())
     }
 
@@ -174,292 +373,1228 @@ impl Storable for Deposit {
         ic_stable_structures::storable::Bound::Unbounded;
 }
 
-// USTBillStorage removed - not used in current implementation
+// ============= EVM CONFIG STORABLE IMPLEMENTATIONS =============
 
-// Storage interface for Users
-pub struct UserStorage;
+impl Storable for RoleAssignment {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
 
-impl UserStorage {
-    pub fn insert(user: User) -> Result<()> {
-        USERS.with(|users| {
-            if users.borrow().contains_key(&user.principal) {
-                Err(BitcoinUSTBillsError::UserAlreadyExists)
-            } else {
-                users.borrow_mut().insert(user.principal, user);
-                Ok(())
-            }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for AuditEntry {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for TreasuryProposal {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for MultisigPolicy {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for DepositRetryState {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for DepositScanState {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for PriceOracleConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for CachedRate {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for PriceSample {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for Quote {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for CanisterIds {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for EvmConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+impl Storable for EvmTransaction {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(candid::encode_one(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap()
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        candid::encode_one(self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound =
+        ic_stable_structures::storable::Bound::Unbounded;
+}
+
+// USTBillStorage removed - not used in current implementation
+
+// Storage interface for Users
+pub struct UserStorage;
+
+impl UserStorage {
+    pub fn insert(user: User) -> Result<()> {
+        USERS.with(|users| {
+            if users.borrow().contains_key(&user.principal) {
+                Err(BitcoinUSTBillsError::UserAlreadyExists)
+            } else {
+                users.borrow_mut().insert(user.principal, user);
+                Ok(())
+            }
+        })
+    }
+
+    pub fn get(principal: &Principal) -> Result<User> {
+        USERS.with(|users| {
+            users
+                .borrow()
+                .get(principal)
+                .ok_or(BitcoinUSTBillsError::UserNotFound)
+        })
+    }
+
+    pub fn update(user: User) -> Result<()> {
+        USERS.with(|users| {
+            let mut users = users.borrow_mut();
+            if users.contains_key(&user.principal) {
+                users.insert(user.principal, user);
+                Ok(())
+            } else {
+                Err(BitcoinUSTBillsError::UserNotFound)
+            }
+        })
+    }
+
+    pub fn remove(principal: &Principal) -> Result<User> {
+        USERS.with(|users| {
+            users
+                .borrow_mut()
+                .remove(principal)
+                .ok_or(BitcoinUSTBillsError::UserNotFound)
+        })
+    }
+
+    pub fn get_all() -> Vec<User> {
+        USERS.with(|users| {
+            users
+                .borrow()
+                .iter()
+                .map(|entry| entry.value().clone())
+                .collect()
+        })
+    }
+
+    pub fn count() -> u64 {
+        USERS.with(|users| users.borrow().len())
+    }
+}
+
+// HoldingStorage, TransactionStorage, PlatformConfigStorage, TreasuryRateStorage removed - not used
+
+// Storage interface for Trading Metrics
+pub struct TradingMetricsStorage;
+
+impl TradingMetricsStorage {
+    pub fn get() -> TradingMetrics {
+        TRADING_METRICS.with(|metrics| metrics.borrow().get().clone())
+    }
+
+    pub fn update(metrics: TradingMetrics) -> Result<()> {
+        TRADING_METRICS.with(|trading_metrics| {
+            trading_metrics.borrow_mut().set(metrics);
+            Ok(())
+        })
+    }
+
+    pub fn update_volume(volume: u64) -> Result<()> {
+        let mut metrics = Self::get();
+        metrics.total_volume += volume;
+        metrics.total_transactions += 1;
+        metrics.last_updated = ic_cdk::api::time() / 1_000_000_000;
+        Self::update(metrics)
+    }
+
+    pub fn update_price(price: u64) -> Result<()> {
+        let mut metrics = Self::get();
+        if metrics.highest_price == 0 || price > metrics.highest_price {
+            metrics.highest_price = price;
+        }
+        if metrics.lowest_price == 0 || price < metrics.lowest_price {
+            metrics.lowest_price = price;
+        }
+        // Calculate new average price
+        let total_volume = metrics.total_volume;
+        if total_volume > 0 {
+            metrics.average_price =
+                (metrics.average_price * total_volume + price) / (total_volume + 1);
+        } else {
+            metrics.average_price = price;
+        }
+        metrics.last_updated = ic_cdk::api::time() / 1_000_000_000;
+        Self::update(metrics)
+    }
+}
+
+// Storage interface for Verified Purchases Ledger
+pub struct VerifiedPurchasesLedgerStorage;
+
+impl VerifiedPurchasesLedgerStorage {
+    pub fn insert(purchase: VerifiedBrokerPurchase) -> Result<()> {
+        VERIFIED_PURCHASES_LEDGER.with(|ledger| {
+            let id = ledger.borrow().len();
+            ledger.borrow_mut().insert(id, purchase);
+            Ok(())
+        })
+    }
+
+    pub fn get_all() -> Vec<VerifiedBrokerPurchase> {
+        VERIFIED_PURCHASES_LEDGER.with(|ledger| {
+            ledger
+                .borrow()
+                .iter()
+                .map(|entry| entry.value().clone())
+                .collect()
+        })
+    }
+
+    pub fn count() -> u64 {
+        VERIFIED_PURCHASES_LEDGER.with(|ledger| ledger.borrow().len())
+    }
+}
+
+// ============= FREE KYC STORAGE INTERFACES =============
+
+// Storage interface for Free KYC Sessions
+pub struct FreeKYCStorage;
+
+impl FreeKYCStorage {
+    pub fn insert(upload_id: String, session: FreeKYCSession) -> Result<()> {
+        FREE_KYC_SESSIONS.with(|sessions| {
+            sessions.borrow_mut().insert(upload_id, session);
+            Ok(())
+        })
+    }
+
+    pub fn get(upload_id: &String) -> Result<FreeKYCSession> {
+        FREE_KYC_SESSIONS.with(|sessions| {
+            sessions
+                .borrow()
+                .get(upload_id)
+                .ok_or(BitcoinUSTBillsError::StorageError(
+                    "KYC session not found".to_string(),
+                ))
+        })
+    }
+
+    pub fn update(upload_id: String, session: FreeKYCSession) -> Result<()> {
+        FREE_KYC_SESSIONS.with(|sessions| {
+            sessions.borrow_mut().insert(upload_id, session);
+            Ok(())
+        })
+    }
+
+    pub fn remove(upload_id: &String) -> Option<FreeKYCSession> {
+        FREE_KYC_SESSIONS.with(|sessions| sessions.borrow_mut().remove(upload_id))
+    }
+
+    pub fn get_all() -> Vec<(String, FreeKYCSession)> {
+        FREE_KYC_SESSIONS.with(|sessions| {
+            sessions
+                .borrow()
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect()
+        })
+    }
+
+    pub fn get_by_user(user_principal: &Principal) -> Vec<(String, FreeKYCSession)> {
+        FREE_KYC_SESSIONS.with(|sessions| {
+            sessions
+                .borrow()
+                .iter()
+                .filter(|entry| entry.value().user_principal == *user_principal)
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect()
+        })
+    }
+
+    pub fn get_pending_reviews() -> Vec<(String, FreeKYCSession)> {
+        FREE_KYC_SESSIONS.with(|sessions| {
+            sessions
+                .borrow()
+                .iter()
+                .filter(|entry| entry.value().status == FreeKYCStatus::PendingReview)
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect()
+        })
+    }
+
+    pub fn count() -> u64 {
+        FREE_KYC_SESSIONS.with(|sessions| sessions.borrow().len())
+    }
+}
+
+// ============= DEPOSIT STORAGE INTERFACES =============
+
+// Storage interface for Deposits
+pub struct DepositStorage;
+
+impl DepositStorage {
+    pub fn insert(deposit: Deposit) -> Result<()> {
+        DEPOSIT_BLOCK_INDEX.with(|index| {
+            index.borrow_mut().insert(deposit.block_index, deposit.id);
+        });
+        Self::queue_sweep(&deposit);
+        DEPOSITS.with(|deposits| {
+            deposits.borrow_mut().insert(deposit.id, deposit);
+            Ok(())
+        })
+    }
+
+    pub fn get_by_block_index(block_index: u64) -> Option<Deposit> {
+        let deposit_id = DEPOSIT_BLOCK_INDEX.with(|index| index.borrow().get(&block_index))?;
+        DEPOSITS.with(|deposits| deposits.borrow().get(&deposit_id))
+    }
+
+    // Indexes deposits created before the block index map existed, keeps the newest deposit per block
+    pub fn backfill_block_index() {
+        DEPOSITS.with(|deposits| {
+            DEPOSIT_BLOCK_INDEX.with(|index| {
+                let mut index = index.borrow_mut();
+                for entry in deposits.borrow().iter() {
+                    let deposit = entry.value();
+                    let newer_indexed = index
+                        .get(&deposit.block_index)
+                        .is_some_and(|indexed_id| indexed_id >= deposit.id);
+                    if !newer_indexed {
+                        index.insert(deposit.block_index, deposit.id);
+                    }
+                }
+            })
+        })
+    }
+
+    // Queues deposits minted before the sweep queue existed
+    pub fn backfill_sweep_queue() {
+        DEPOSITS.with(|deposits| {
+            for entry in deposits.borrow().iter() {
+                Self::queue_sweep(&entry.value());
+            }
+        })
+    }
+
+    // Keeps the deposit's sweep queue entry in line with its state
+    fn queue_sweep(deposit: &Deposit) {
+        DEPOSIT_SWEEP_QUEUE.with(|queue| {
+            let mut queue = queue.borrow_mut();
+            if deposit.needs_sweep() {
+                queue.insert(deposit.id, ());
+            } else {
+                queue.remove(&deposit.id);
+            }
+        })
+    }
+
+    /// Up to `limit` deposits waiting to be swept with an id above `after`, lowest id first
+    pub fn get_awaiting_sweep(after: Option<u64>, limit: usize) -> Vec<Deposit> {
+        let start = after.map_or(Bound::Unbounded, Bound::Excluded);
+        let ids: Vec<u64> = DEPOSIT_SWEEP_QUEUE.with(|queue| {
+            queue
+                .borrow()
+                .range((start, Bound::Unbounded))
+                .take(limit)
+                .map(|entry| *entry.key())
+                .collect()
+        });
+        DEPOSITS.with(|deposits| {
+            let deposits = deposits.borrow();
+            ids.iter().filter_map(|id| deposits.get(id)).collect()
+        })
+    }
+
+    /// Re-encodes every deposit in the current format, legacy f64 records are converted on read
+    pub fn migrate_legacy_records() {
+        DEPOSITS.with(|deposits| {
+            let mut deposits = deposits.borrow_mut();
+            let all: Vec<Deposit> = deposits.iter().map(|entry| entry.value()).collect();
+            for deposit in all {
+                deposits.insert(deposit.id, deposit);
+            }
+        })
+    }
+
+    pub fn get(deposit_id: u64) -> Result<Deposit> {
+        DEPOSITS.with(|deposits| {
+            deposits
+                .borrow()
+                .get(&deposit_id)
+                .ok_or(BitcoinUSTBillsError::StorageError(
+                    "Deposit not found".to_string(),
+                ))
+        })
+    }
+
+    pub fn update(deposit: Deposit) -> Result<()> {
+        Self::queue_sweep(&deposit);
+        DEPOSITS.with(|deposits| {
+            deposits.borrow_mut().insert(deposit.id, deposit);
+            Ok(())
+        })
+    }
+
+    pub fn get_by_user(user_principal: &Principal) -> Vec<Deposit> {
+        DEPOSITS.with(|deposits| {
+            deposits
+                .borrow()
+                .iter()
+                .filter(|entry| entry.value().user_principal == *user_principal)
+                .map(|entry| entry.value().clone())
+                .collect()
+        })
+    }
+
+    pub fn get_by_status(statuses: &[DepositStatus]) -> Vec<Deposit> {
+        DEPOSITS.with(|deposits| {
+            deposits
+                .borrow()
+                .iter()
+                .filter(|entry| statuses.contains(&entry.value().status))
+                .map(|entry| entry.value().clone())
+                .collect()
+        })
+    }
+
+    pub fn get_pending_deposits() -> Vec<Deposit> {
+        DEPOSITS.with(|deposits| {
+            deposits
+                .borrow()
+                .iter()
+                .filter(|entry| entry.value().status == DepositStatus::Pending)
+                .map(|entry| entry.value().clone())
+                .collect()
+        })
+    }
+
+    pub fn count() -> u64 {
+        DEPOSITS.with(|deposits| deposits.borrow().len())
+    }
+}
+
+// Storage interface for the deposit reconciler's retry bookkeeping
+pub struct DepositRetryStorage;
+
+impl DepositRetryStorage {
+    pub fn get(deposit_id: u64) -> Option<DepositRetryState> {
+        DEPOSIT_RETRIES.with(|retries| retries.borrow().get(&deposit_id))
+    }
+
+    pub fn upsert(state: DepositRetryState) -> Result<()> {
+        DEPOSIT_RETRIES.with(|retries| {
+            retries.borrow_mut().insert(state.deposit_id, state);
+            Ok(())
+        })
+    }
+
+    pub fn remove(deposit_id: u64) {
+        DEPOSIT_RETRIES.with(|retries| {
+            retries.borrow_mut().remove(&deposit_id);
+        })
+    }
+
+    pub fn get_all() -> Vec<DepositRetryState> {
+        DEPOSIT_RETRIES.with(|retries| {
+            retries
+                .borrow()
+                .iter()
+                .map(|entry| entry.value().clone())
+                .collect()
+        })
+    }
+}
+
+// Storage interface for the deposit subaccount scan cursor
+pub struct DepositScanStorage;
+
+impl DepositScanStorage {
+    pub fn get() -> DepositScanState {
+        DEPOSIT_SCAN_STATE.with(|state| state.borrow().get().clone())
+    }
+
+    pub fn update(scan_state: DepositScanState) -> Result<()> {
+        DEPOSIT_SCAN_STATE.with(|state| {
+            state.borrow_mut().set(scan_state);
+            Ok(())
+        })
+    }
+}
+
+// Storage interface for the data layout version, see handlers::run_migrations
+pub struct SchemaVersionStorage;
+
+impl SchemaVersionStorage {
+    pub fn get() -> u32 {
+        SCHEMA_VERSION.with(|version| *version.borrow().get())
+    }
+
+    pub fn set(version: u32) {
+        SCHEMA_VERSION.with(|stored| {
+            stored.borrow_mut().set(version);
+        })
+    }
+}
+
+// Storage interface for the price oracle's config and last accepted rate
+pub struct PriceOracleStorage;
+
+impl PriceOracleStorage {
+    pub fn get_config() -> PriceOracleConfig {
+        PRICE_ORACLE_CONFIG.with(|config| config.borrow().get().clone())
+    }
+
+    pub fn update_config(oracle_config: PriceOracleConfig) -> Result<()> {
+        PRICE_ORACLE_CONFIG.with(|config| {
+            config.borrow_mut().set(oracle_config);
+            Ok(())
+        })
+    }
+
+    pub fn get_last_good_rate() -> Option<CachedRate> {
+        LAST_GOOD_RATE.with(|rate| rate.borrow().get().clone())
+    }
+
+    pub fn set_last_good_rate(cached_rate: CachedRate) {
+        LAST_GOOD_RATE.with(|rate| {
+            rate.borrow_mut().set(Some(cached_rate));
+        })
+    }
+
+    pub fn record_xrc_error(code: &str) {
+        XRC_ERROR_COUNTS.with(|counts| {
+            let mut counts = counts.borrow_mut();
+            let count = counts.get(&code.to_string()).unwrap_or(0);
+            counts.insert(code.to_string(), count.saturating_add(1));
+        })
+    }
+
+    pub fn get_xrc_error_counts() -> Vec<(String, u64)> {
+        XRC_ERROR_COUNTS.with(|counts| {
+            counts
+                .borrow()
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value()))
+                .collect()
+        })
+    }
+}
+
+// Storage interface for the sampled BTC/USD price history
+pub struct PriceHistoryStorage;
+
+impl PriceHistoryStorage {
+    pub fn insert(sample: PriceSample) {
+        PRICE_HISTORY.with(|history| {
+            history.borrow_mut().insert(sample.timestamp, sample);
+        })
+    }
+
+    // Oldest first, both bounds inclusive
+    pub fn get_range(from_timestamp: u64, to_timestamp: u64, limit: u64) -> Vec<PriceSample> {
+        if from_timestamp > to_timestamp {
+            return Vec::new();
+        }
+        PRICE_HISTORY.with(|history| {
+            history
+                .borrow()
+                .range(from_timestamp..=to_timestamp)
+                .take(limit as usize)
+                .map(|entry| entry.value())
+                .collect()
+        })
+    }
+
+    /// Newest sample at or before the timestamp
+    pub fn get_at_or_before(timestamp: u64) -> Option<PriceSample> {
+        PRICE_HISTORY.with(|history| {
+            history
+                .borrow()
+                .range(..=timestamp)
+                .next_back()
+                .map(|entry| entry.value())
+        })
+    }
+
+    pub fn latest() -> Option<PriceSample> {
+        PRICE_HISTORY.with(|history| history.borrow().last_key_value().map(|(_, sample)| sample))
+    }
+
+    /// Drops every sample older than the cutoff, returns how many were removed
+    pub fn prune_before(cutoff: u64) -> u64 {
+        PRICE_HISTORY.with(|history| {
+            let mut history = history.borrow_mut();
+            let expired: Vec<u64> = history.range(..cutoff).map(|entry| *entry.key()).collect();
+            for timestamp in &expired {
+                history.remove(timestamp);
+            }
+            expired.len() as u64
+        })
+    }
+
+    pub fn count() -> u64 {
+        PRICE_HISTORY.with(|history| history.borrow().len())
+    }
+}
+
+// Storage interface for price quotes and the key their IDs are signed with
+pub struct QuoteStorage;
+
+impl QuoteStorage {
+    pub fn next_sequence() -> u64 {
+        QUOTES.with(|quotes| {
+            quotes
+                .borrow()
+                .last_key_value()
+                .map_or(0, |(sequence, _)| sequence + 1)
+        })
+    }
+
+    pub fn insert(sequence: u64, quote: Quote) {
+        QUOTES.with(|quotes| {
+            quotes.borrow_mut().insert(sequence, quote);
+        })
+    }
+
+    pub fn get(sequence: u64) -> Option<Quote> {
+        QUOTES.with(|quotes| quotes.borrow().get(&sequence))
+    }
+
+    /// Drops quotes that expired before the cutoff, oldest first and at most `limit` per call
+    pub fn prune_expired_before(cutoff: u64, limit: usize) -> u64 {
+        QUOTES.with(|quotes| {
+            let mut quotes = quotes.borrow_mut();
+            let expired: Vec<u64> = quotes
+                .iter()
+                .take(limit)
+                .take_while(|entry| entry.value().expires_at < cutoff)
+                .map(|entry| *entry.key())
+                .collect();
+            for sequence in &expired {
+                quotes.remove(sequence);
+            }
+            expired.len() as u64
+        })
+    }
+
+    pub fn get_signing_key() -> Option<Vec<u8>> {
+        QUOTE_SIGNING_KEY.with(|key| Some(key.borrow().get().clone()).filter(|key| !key.is_empty()))
+    }
+
+    pub fn set_signing_key(signing_key: Vec<u8>) {
+        QUOTE_SIGNING_KEY.with(|key| {
+            key.borrow_mut().set(signing_key);
+        })
+    }
+}
+
+// Storage interface for Processed Deposits (to prevent double processing)
+pub struct ProcessedDepositsStorage;
+
+impl ProcessedDepositsStorage {
+    pub fn insert(block_index: u64, principal: Principal) -> Result<()> {
+        PROCESSED_DEPOSITS.with(|processed| {
+            processed.borrow_mut().insert(block_index, principal);
+            Ok(())
+        })
+    }
+
+    pub fn get(block_index: u64) -> Option<Principal> {
+        PROCESSED_DEPOSITS.with(|processed| processed.borrow().get(&block_index))
+    }
+
+    pub fn contains(block_index: u64) -> bool {
+        PROCESSED_DEPOSITS.with(|processed| processed.borrow().contains_key(&block_index))
+    }
+
+    pub fn count() -> u64 {
+        PROCESSED_DEPOSITS.with(|processed| processed.borrow().len())
+    }
+}
+
+// ============= REDEMPTION STORAGE INTERFACES =============
+
+// Storage interface for Redemptions
+pub struct RedemptionStorage;
+
+impl RedemptionStorage {
+    pub fn insert(redemption: Redemption) -> Result<()> {
+        REDEMPTIONS.with(|redemptions| {
+            redemptions.borrow_mut().insert(redemption.id, redemption);
+            Ok(())
         })
     }
 
-    pub fn get(principal: &Principal) -> Result<User> {
-        USERS.with(|users| {
-            users
+    pub fn get(redemption_id: u64) -> Result<Redemption> {
+        REDEMPTIONS.with(|redemptions| {
+            redemptions
                 .borrow()
-                .get(principal)
-                .ok_or(BitcoinUSTBillsError::UserNotFound)
+                .get(&redemption_id)
+                .ok_or(BitcoinUSTBillsError::StorageError(
+                    "Redemption not found".to_string(),
+                ))
         })
     }
 
-    pub fn update(user: User) -> Result<()> {
-        USERS.with(|users| {
-            let mut users = users.borrow_mut();
-            if users.contains_key(&user.principal) {
-                users.insert(user.principal, user);
-                Ok(())
-            } else {
-                Err(BitcoinUSTBillsError::UserNotFound)
-            }
+    pub fn update(redemption: Redemption) -> Result<()> {
+        REDEMPTIONS.with(|redemptions| {
+            redemptions.borrow_mut().insert(redemption.id, redemption);
+            Ok(())
         })
     }
 
-    pub fn remove(principal: &Principal) -> Result<User> {
-        USERS.with(|users| {
-            users
-                .borrow_mut()
-                .remove(principal)
-                .ok_or(BitcoinUSTBillsError::UserNotFound)
+    pub fn get_by_user(user_principal: &Principal) -> Vec<Redemption> {
+        REDEMPTIONS.with(|redemptions| {
+            redemptions
+                .borrow()
+                .iter()
+                .filter(|entry| entry.value().user_principal == *user_principal)
+                .map(|entry| entry.value().clone())
+                .collect()
         })
     }
 
-    pub fn get_all() -> Vec<User> {
-        USERS.with(|users| {
-            users
+    pub fn get_by_status(status: RedemptionStatus) -> Vec<Redemption> {
+        REDEMPTIONS.with(|redemptions| {
+            redemptions
                 .borrow()
                 .iter()
+                .filter(|entry| entry.value().status == status)
                 .map(|entry| entry.value().clone())
                 .collect()
         })
     }
 
     pub fn count() -> u64 {
-        USERS.with(|users| users.borrow().len())
+        REDEMPTIONS.with(|redemptions| redemptions.borrow().len())
     }
 }
 
-// HoldingStorage, TransactionStorage, PlatformConfigStorage, TreasuryRateStorage removed - not used
+// ============= EVM CONFIG STORAGE INTERFACES =============
 
-// Storage interface for Trading Metrics
-pub struct TradingMetricsStorage;
+// Storage interface for the EVM RPC configuration
+pub struct EvmConfigStorage;
 
-impl TradingMetricsStorage {
-    pub fn get() -> TradingMetrics {
-        TRADING_METRICS.with(|metrics| metrics.borrow().get().clone())
+impl EvmConfigStorage {
+    pub fn get() -> EvmConfig {
+        EVM_CONFIG.with(|config| config.borrow().get().clone())
     }
 
-    pub fn update(metrics: TradingMetrics) -> Result<()> {
-        TRADING_METRICS.with(|trading_metrics| {
-            trading_metrics.borrow_mut().set(metrics);
+    /// Cached user addresses belong to the old key, so a new key name forgets them
+    pub fn update(config: EvmConfig) -> Result<()> {
+        let key_changed = Self::get().ecdsa_key_name != config.ecdsa_key_name;
+        EVM_CONFIG.with(|evm_config| {
+            evm_config.borrow_mut().set(config);
+        });
+        if key_changed {
+            EthAddressStorage::clear();
+        }
+        Ok(())
+    }
+}
+
+// Storage interface for outbound EVM transactions
+pub struct EvmTransactionStorage;
+
+impl EvmTransactionStorage {
+    pub fn insert(transaction: EvmTransaction) -> Result<()> {
+        EVM_TRANSACTIONS.with(|transactions| {
+            transactions
+                .borrow_mut()
+                .insert(transaction.id, transaction);
             Ok(())
         })
     }
 
-    pub fn update_volume(volume: u64) -> Result<()> {
-        let mut metrics = Self::get();
-        metrics.total_volume += volume;
-        metrics.total_transactions += 1;
-        metrics.last_updated = ic_cdk::api::time() / 1_000_000_000;
-        Self::update(metrics)
+    pub fn get(transaction_id: u64) -> Result<EvmTransaction> {
+        EVM_TRANSACTIONS.with(|transactions| {
+            transactions
+                .borrow()
+                .get(&transaction_id)
+                .ok_or(BitcoinUSTBillsError::StorageError(
+                    "EVM transaction not found".to_string(),
+                ))
+        })
     }
 
-    pub fn update_price(price: u64) -> Result<()> {
-        let mut metrics = Self::get();
-        if metrics.highest_price == 0 || price > metrics.highest_price {
-            metrics.highest_price = price;
-        }
-        if metrics.lowest_price == 0 || price < metrics.lowest_price {
-            metrics.lowest_price = price;
-        }
-        // Calculate new average price
-        let total_volume = metrics.total_volume;
-        if total_volume > 0 {
-            metrics.average_price =
-                (metrics.average_price * total_volume + price) / (total_volume + 1);
-        } else {
-            metrics.average_price = price;
-        }
-        metrics.last_updated = ic_cdk::api::time() / 1_000_000_000;
-        Self::update(metrics)
+    pub fn update(transaction: EvmTransaction) -> Result<()> {
+        EVM_TRANSACTIONS.with(|transactions| {
+            transactions
+                .borrow_mut()
+                .insert(transaction.id, transaction);
+            Ok(())
+        })
     }
-}
-
-// Storage interface for Verified Purchases Ledger
-pub struct VerifiedPurchasesLedgerStorage;
 
-impl VerifiedPurchasesLedgerStorage {
-    pub fn insert(purchase: VerifiedBrokerPurchase) -> Result<()> {
-        VERIFIED_PURCHASES_LEDGER.with(|ledger| {
-            let id = ledger.borrow().len();
-            ledger.borrow_mut().insert(id, purchase);
-            Ok(())
+    pub fn get_by_user(user_principal: &Principal) -> Vec<EvmTransaction> {
+        EVM_TRANSACTIONS.with(|transactions| {
+            transactions
+                .borrow()
+                .iter()
+                .filter(|entry| entry.value().requested_by == *user_principal)
+                .map(|entry| entry.value().clone())
+                .collect()
         })
     }
 
-    pub fn get_all() -> Vec<VerifiedBrokerPurchase> {
-        VERIFIED_PURCHASES_LEDGER.with(|ledger| {
-            ledger
+    pub fn get_by_status(status: Option<EvmTransactionStatus>) -> Vec<EvmTransaction> {
+        EVM_TRANSACTIONS.with(|transactions| {
+            transactions
                 .borrow()
                 .iter()
+                .filter(|entry| status.as_ref().is_none_or(|s| entry.value().status == *s))
                 .map(|entry| entry.value().clone())
                 .collect()
         })
     }
 
     pub fn count() -> u64 {
-        VERIFIED_PURCHASES_LEDGER.with(|ledger| ledger.borrow().len())
+        EVM_TRANSACTIONS.with(|transactions| transactions.borrow().len())
     }
 }
 
-// ============= FREE KYC STORAGE INTERFACES =============
+// Storage interface for the role assignments checked by the guard
+pub struct RoleStorage;
 
-// Storage interface for Free KYC Sessions
-pub struct FreeKYCStorage;
+impl RoleStorage {
+    pub fn get(principal: &Principal) -> Option<RoleAssignment> {
+        ROLE_ASSIGNMENTS.with(|assignments| assignments.borrow().get(principal))
+    }
 
-impl FreeKYCStorage {
-    pub fn insert(upload_id: String, session: FreeKYCSession) -> Result<()> {
-        FREE_KYC_SESSIONS.with(|sessions| {
-            sessions.borrow_mut().insert(upload_id, session);
+    pub fn upsert(assignment: RoleAssignment) -> Result<()> {
+        ROLE_ASSIGNMENTS.with(|assignments| {
+            assignments
+                .borrow_mut()
+                .insert(assignment.principal, assignment);
             Ok(())
         })
     }
 
-    pub fn get(upload_id: &String) -> Result<FreeKYCSession> {
-        FREE_KYC_SESSIONS.with(|sessions| {
-            sessions
-                .borrow()
-                .get(upload_id)
+    pub fn remove(principal: &Principal) -> Result<()> {
+        ROLE_ASSIGNMENTS.with(|assignments| {
+            assignments
+                .borrow_mut()
+                .remove(principal)
+                .map(|_| ())
                 .ok_or(BitcoinUSTBillsError::StorageError(
-                    "KYC session not found".to_string(),
+                    "Role assignment not found".to_string(),
                 ))
         })
     }
 
-    pub fn update(upload_id: String, session: FreeKYCSession) -> Result<()> {
-        FREE_KYC_SESSIONS.with(|sessions| {
-            sessions.borrow_mut().insert(upload_id, session);
-            Ok(())
-        })
-    }
-
-    pub fn remove(upload_id: &String) -> Option<FreeKYCSession> {
-        FREE_KYC_SESSIONS.with(|sessions| sessions.borrow_mut().remove(upload_id))
-    }
-
-    pub fn get_all() -> Vec<(String, FreeKYCSession)> {
-        FREE_KYC_SESSIONS.with(|sessions| {
-            sessions
+    pub fn get_all() -> Vec<RoleAssignment> {
+        ROLE_ASSIGNMENTS.with(|assignments| {
+            assignments
                 .borrow()
                 .iter()
-                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .map(|entry| entry.value().clone())
                 .collect()
         })
     }
 
-    pub fn get_by_user(user_principal: &Principal) -> Vec<(String, FreeKYCSession)> {
-        FREE_KYC_SESSIONS.with(|sessions| {
-            sessions
+    pub fn count_with_role(role: Role) -> u64 {
+        ROLE_ASSIGNMENTS.with(|assignments| {
+            assignments
                 .borrow()
                 .iter()
-                .filter(|entry| entry.value().user_principal == *user_principal)
-                .map(|entry| (entry.key().clone(), entry.value().clone()))
-                .collect()
+                .filter(|entry| entry.value().roles.contains(&role))
+                .count() as u64
         })
     }
 
-    pub fn get_pending_reviews() -> Vec<(String, FreeKYCSession)> {
-        FREE_KYC_SESSIONS.with(|sessions| {
-            sessions
-                .borrow()
-                .iter()
-                .filter(|entry| entry.value().status == FreeKYCStatus::PendingReview)
-                .map(|entry| (entry.key().clone(), entry.value().clone()))
-                .collect()
+    pub fn count() -> u64 {
+        ROLE_ASSIGNMENTS.with(|assignments| assignments.borrow().len())
+    }
+}
+
+// Storage interface for the admin audit log, there is deliberately no update or remove
+pub struct AuditLogStorage;
+
+impl AuditLogStorage {
+    pub fn append(mut entry: AuditEntry) -> u64 {
+        AUDIT_LOG.with(|log| {
+            let mut log = log.borrow_mut();
+            let id = log.len(); // Nothing is ever removed, so the length is the next id
+            entry.id = id;
+            AUDIT_LOG_TIMESTAMPS.with(|index| index.borrow_mut().insert((entry.timestamp, id), ()));
+            log.insert(id, entry);
+            id
+        })
+    }
+
+    pub fn get(id: u64) -> Result<AuditEntry> {
+        AUDIT_LOG.with(|log| {
+            log.borrow()
+                .get(&id)
+                .ok_or(BitcoinUSTBillsError::StorageError(
+                    "Audit entry not found".to_string(),
+                ))
+        })
+    }
+
+    // Walks newest to oldest from below `before_id`. Time filters narrow the ids walked
+    // through the timestamp index, the other filters are checked per entry. A page stops
+    // after `limit` matches or MAX_AUDIT_ENTRIES_SCANNED entries, whichever comes first.
+    pub fn query(filter: &AuditLogFilter, before_id: Option<u64>, limit: u64) -> AuditLogPage {
+        let empty_page = AuditLogPage {
+            entries: Vec::new(),
+            next_before_id: None,
+        };
+        let Some((first_id, end_id)) = Self::id_range(filter) else {
+            return empty_page;
+        };
+        let end_id = before_id.map_or(end_id, |before_id| before_id.min(end_id));
+        if first_id >= end_id {
+            return empty_page;
+        }
+
+        AUDIT_LOG.with(|log| {
+            let log = log.borrow();
+            let mut entries = Vec::new();
+            let mut cursor = end_id;
+
+            let scanned = log
+                .range(first_id..end_id)
+                .rev()
+                .take(MAX_AUDIT_ENTRIES_SCANNED as usize);
+            for entry in scanned {
+                if entries.len() as u64 >= limit {
+                    break;
+                }
+                let entry = entry.value();
+                cursor = entry.id;
+                if filter.matches(&entry) {
+                    entries.push(entry);
+                }
+            }
+
+            AuditLogPage {
+                entries,
+                next_before_id: (cursor > first_id).then_some(cursor),
+            }
+        })
+    }
+
+    // Ids [first, end) the time filters allow. Entries are appended with the current time,
+    // so ids and timestamps grow together.
+    fn id_range(filter: &AuditLogFilter) -> Option<(u64, u64)> {
+        AUDIT_LOG_TIMESTAMPS.with(|index| {
+            let index = index.borrow();
+            let first_id = match filter.from_timestamp {
+                Some(from) => index.range((from, 0)..).next()?.key().1,
+                None => 0,
+            };
+            let end_id = match filter.to_timestamp {
+                Some(to) => index.range(..=(to, u64::MAX)).next_back()?.key().1 + 1,
+                None => Self::count(),
+            };
+            Some((first_id, end_id))
         })
     }
 
+    // Indexes entries written before the timestamp index existed, a no-op once it is complete
+    pub fn backfill_timestamp_index() {
+        let indexed = AUDIT_LOG_TIMESTAMPS.with(|index| index.borrow().len());
+        if indexed >= Self::count() {
+            return;
+        }
+        AUDIT_LOG.with(|log| {
+            AUDIT_LOG_TIMESTAMPS.with(|index| {
+                let mut index = index.borrow_mut();
+                for entry in log.borrow().iter() {
+                    let entry = entry.value();
+                    index.insert((entry.timestamp, entry.id), ());
+                }
+            })
+        });
+    }
+
     pub fn count() -> u64 {
-        FREE_KYC_SESSIONS.with(|sessions| sessions.borrow().len())
+        AUDIT_LOG.with(|log| log.borrow().len())
     }
 }
 
-// ============= DEPOSIT STORAGE INTERFACES =============
+// Bounds the work of one audit log page when the filters match few entries
+const MAX_AUDIT_ENTRIES_SCANNED: u64 = 5_000;
 
-// Storage interface for Deposits
-pub struct DepositStorage;
+// Storage interface for multi-signature treasury proposals
+pub struct TreasuryProposalStorage;
 
-impl DepositStorage {
-    pub fn insert(deposit: Deposit) -> Result<()> {
-        DEPOSITS.with(|deposits| {
-            deposits.borrow_mut().insert(deposit.id, deposit);
+impl TreasuryProposalStorage {
+    pub fn insert(proposal: TreasuryProposal) -> Result<()> {
+        TREASURY_PROPOSALS.with(|proposals| {
+            proposals.borrow_mut().insert(proposal.id, proposal);
             Ok(())
         })
     }
 
-    pub fn get(deposit_id: u64) -> Result<Deposit> {
-        DEPOSITS.with(|deposits| {
-            deposits
+    pub fn get(proposal_id: u64) -> Result<TreasuryProposal> {
+        TREASURY_PROPOSALS.with(|proposals| {
+            proposals
                 .borrow()
-                .get(&deposit_id)
+                .get(&proposal_id)
                 .ok_or(BitcoinUSTBillsError::StorageError(
-                    "Deposit not found".to_string(),
+                    "Proposal not found".to_string(),
                 ))
         })
     }
 
-    pub fn update(deposit: Deposit) -> Result<()> {
-        DEPOSITS.with(|deposits| {
-            deposits.borrow_mut().insert(deposit.id, deposit);
+    pub fn update(proposal: TreasuryProposal) -> Result<()> {
+        TREASURY_PROPOSALS.with(|proposals| {
+            proposals.borrow_mut().insert(proposal.id, proposal);
             Ok(())
         })
     }
 
-    pub fn get_by_user(user_principal: &Principal) -> Vec<Deposit> {
-        DEPOSITS.with(|deposits| {
-            deposits
+    pub fn get_by_status(status: Option<ProposalStatus>) -> Vec<TreasuryProposal> {
+        TREASURY_PROPOSALS.with(|proposals| {
+            proposals
                 .borrow()
                 .iter()
-                .filter(|entry| entry.value().user_principal == *user_principal)
+                .filter(|entry| {
+                    status
+                        .as_ref()
+                        .is_none_or(|status| entry.value().status == *status)
+                })
                 .map(|entry| entry.value().clone())
                 .collect()
         })
     }
 
-    pub fn get_pending_deposits() -> Vec<Deposit> {
-        DEPOSITS.with(|deposits| {
-            deposits
-                .borrow()
-                .iter()
-                .filter(|entry| entry.value().status == DepositStatus::Pending)
-                .map(|entry| entry.value().clone())
-                .collect()
-        })
+    pub fn count() -> u64 {
+        TREASURY_PROPOSALS.with(|proposals| proposals.borrow().len())
     }
+}
 
-    pub fn count() -> u64 {
-        DEPOSITS.with(|deposits| deposits.borrow().len())
+// Storage interface for the multi-signature policy
+pub struct MultisigPolicyStorage;
+
+impl MultisigPolicyStorage {
+    pub fn get() -> MultisigPolicy {
+        MULTISIG_POLICY.with(|policy| policy.borrow().get().clone())
+    }
+
+    pub fn update(policy: MultisigPolicy) -> Result<()> {
+        MULTISIG_POLICY.with(|multisig_policy| {
+            multisig_policy.borrow_mut().set(policy);
+            Ok(())
+        })
     }
 }
 
-// Storage interface for Processed Deposits (to prevent double processing)
-pub struct ProcessedDepositsStorage;
+// Storage interface for the external canister ids
+pub struct CanisterIdsStorage;
 
-impl ProcessedDepositsStorage {
-    pub fn insert(block_index: u64, principal: Principal) -> Result<()> {
-        PROCESSED_DEPOSITS.with(|processed| {
-            processed.borrow_mut().insert(block_index, principal);
+impl CanisterIdsStorage {
+    pub fn get() -> CanisterIds {
+        CANISTER_IDS.with(|ids| ids.borrow().get().clone())
+    }
+
+    pub fn update(canister_ids: CanisterIds) -> Result<()> {
+        CANISTER_IDS.with(|ids| {
+            ids.borrow_mut().set(canister_ids);
             Ok(())
         })
     }
+}
 
-    pub fn get(block_index: u64) -> Option<Principal> {
-        PROCESSED_DEPOSITS.with(|processed| processed.borrow().get(&block_index))
+// Storage interface for per-user derived Ethereum addresses
+pub struct EthAddressStorage;
+
+impl EthAddressStorage {
+    pub fn insert(principal: Principal, address: String) -> Result<()> {
+        ETH_ADDRESSES.with(|addresses| {
+            addresses.borrow_mut().insert(principal, address);
+            Ok(())
+        })
     }
 
-    pub fn contains(block_index: u64) -> bool {
-        PROCESSED_DEPOSITS.with(|processed| processed.borrow().contains_key(&block_index))
+    pub fn get(principal: &Principal) -> Option<String> {
+        ETH_ADDRESSES.with(|addresses| addresses.borrow().get(principal))
     }
 
     pub fn count() -> u64 {
-        PROCESSED_DEPOSITS.with(|processed| processed.borrow().len())
+        ETH_ADDRESSES.with(|addresses| addresses.borrow().len())
+    }
+
+    pub fn clear() {
+        ETH_ADDRESSES.with(|addresses| addresses.borrow_mut().clear_new())
     }
 }
 
@@ -490,6 +1625,137 @@ pub fn get_storage_stats() -> std::collections::HashMap<String, u64> {
         "processed_deposits".to_string(),
         ProcessedDepositsStorage::count(),
     );
+    stats.insert("redemptions".to_string(), RedemptionStorage::count());
+    stats.insert(
+        "evm_transactions".to_string(),
+        EvmTransactionStorage::count(),
+    );
 
     stats
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append_audit_entries(timestamps: &[u64]) {
+        for (i, timestamp) in timestamps.iter().enumerate() {
+            AuditLogStorage::append(AuditEntry {
+                id: 0,
+                actor: Principal::anonymous(),
+                action: if i % 2 == 0 {
+                    AuditAction::RoleGranted
+                } else {
+                    AuditAction::RoleRevoked
+                },
+                target: format!("target {}", i),
+                before: None,
+                after: None,
+                timestamp: *timestamp,
+            });
+        }
+    }
+
+    fn ids(page: &AuditLogPage) -> Vec<u64> {
+        page.entries.iter().map(|entry| entry.id).collect()
+    }
+
+    // Deposit::new reads the canister clock, which tests do not have
+    fn minted_deposit(id: u64, subaccount: Option<Vec<u8>>) -> Deposit {
+        let mut deposit = Deposit::from(LegacyDeposit {
+            id,
+            user_principal: Principal::anonymous(),
+            ckbtc_amount: 100_000,
+            usd_value: 5_000.0,
+            btc_price_usd: 100_000.0,
+            deposit_time: 0,
+            block_index: id,
+            status: DepositStatus::Validated,
+            ousg_minted: 1_000_000,
+            created_at: 0,
+            updated_at: 0,
+        });
+        deposit.subaccount = subaccount;
+        deposit
+    }
+
+    fn awaiting_sweep(after: Option<u64>, limit: usize) -> Vec<u64> {
+        DepositStorage::get_awaiting_sweep(after, limit)
+            .iter()
+            .map(|deposit| deposit.id)
+            .collect()
+    }
+
+    #[test]
+    fn queues_minted_subaccount_deposits_until_swept() {
+        for id in 1..=4 {
+            DepositStorage::insert(minted_deposit(id, Some(vec![1; 32]))).unwrap();
+        }
+        // Deposits to the default account never need a sweep
+        DepositStorage::insert(minted_deposit(5, None)).unwrap();
+
+        assert_eq!(awaiting_sweep(None, 10), vec![1, 2, 3, 4]);
+        assert_eq!(awaiting_sweep(None, 2), vec![1, 2]);
+        assert_eq!(awaiting_sweep(Some(2), 2), vec![3, 4]);
+
+        let mut swept = DepositStorage::get(2).unwrap();
+        swept.sweep_block_index = Some(77);
+        DepositStorage::update(swept).unwrap();
+        assert_eq!(awaiting_sweep(None, 10), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn audit_log_pages_newest_first() {
+        append_audit_entries(&[100, 101, 102, 103, 104]);
+        let filter = AuditLogFilter::default();
+
+        let page = AuditLogStorage::query(&filter, None, 2);
+        assert_eq!(ids(&page), vec![4, 3]);
+        assert_eq!(page.next_before_id, Some(3));
+
+        let page = AuditLogStorage::query(&filter, page.next_before_id, 2);
+        assert_eq!(ids(&page), vec![2, 1]);
+
+        let page = AuditLogStorage::query(&filter, page.next_before_id, 2);
+        assert_eq!(ids(&page), vec![0]);
+        assert_eq!(page.next_before_id, None);
+    }
+
+    #[test]
+    fn audit_log_time_filters_use_the_timestamp_index() {
+        append_audit_entries(&[100, 100, 200, 300, 300, 400]);
+        let filter = AuditLogFilter {
+            from_timestamp: Some(150),
+            to_timestamp: Some(300),
+            ..Default::default()
+        };
+
+        let page = AuditLogStorage::query(&filter, None, 10);
+        assert_eq!(ids(&page), vec![4, 3, 2]);
+        assert_eq!(page.next_before_id, None);
+
+        let outside = AuditLogFilter {
+            from_timestamp: Some(500),
+            ..Default::default()
+        };
+        assert!(AuditLogStorage::query(&outside, None, 10)
+            .entries
+            .is_empty());
+    }
+
+    #[test]
+    fn audit_log_filters_combine_with_paging() {
+        append_audit_entries(&[100, 101, 102, 103, 104, 105]);
+        let filter = AuditLogFilter {
+            action: Some(AuditAction::RoleGranted),
+            to_timestamp: Some(104),
+            ..Default::default()
+        };
+
+        let page = AuditLogStorage::query(&filter, None, 1);
+        assert_eq!(ids(&page), vec![4]);
+        let page = AuditLogStorage::query(&filter, page.next_before_id, 10);
+        assert_eq!(ids(&page), vec![2, 0]);
+        assert_eq!(page.next_before_id, None);
+    }
+}
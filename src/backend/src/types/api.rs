@@ -1,6 +1,8 @@
-use candid::CandidType;
+use candid::{CandidType, Principal};
 use serde::Deserialize;
 
+use super::{CanisterIdsArgs, EvmNetworkArgs, PriceOracleConfig};
+
 // ============= REQUEST STRUCTURES (KEEP - Used in lib.rs) =============
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
@@ -8,4 +10,14 @@ pub struct UserRegistrationRequest {
     pub email: String,
     pub phone_number: Option<String>,
     pub country: String,
-}
\ No newline at end of file
+}
+
+// ============= INIT / UPGRADE ARGUMENTS =============
+
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct BackendInitArgs {
+    pub canister_ids: Option<CanisterIdsArgs>,
+    pub evm: Option<EvmNetworkArgs>,
+    pub price_oracle: Option<PriceOracleConfig>,
+    pub initial_admins: Option<Vec<Principal>>, // SuperAdmins next to the built-in one, fresh installs only
+}
@@ -0,0 +1,76 @@
+use candid::{CandidType, Principal};
+use serde::Deserialize;
+
+// ============= ADMIN AUDIT LOG =============
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub enum AuditAction {
+    KycApproved,
+    KycRejected,
+    RoleGranted,
+    RoleRevoked,
+    EvmConfigUpdated,
+    EvmNetworkUpdated,
+    GasPolicyUpdated,
+    CanisterIdsUpdated,
+    RedemptionPayoutRetried,
+    RedemptionRefunded,
+    TreasuryErc20Transfer,
+    OusgMinted,
+    CkbtcWithdrawn,
+    MultisigPolicyUpdated,
+    ProposalCreated,
+    ProposalApproved,
+    ProposalExecuted,
+    ProposalCancelled,
+    DepositRetryForced,
+    DepositRefunded,
+    PriceOracleConfigUpdated,
+}
+
+// One privileged action, entries are never modified once written
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub actor: Principal,
+    pub action: AuditAction,
+    pub target: String, // What was acted on, e.g. a principal, upload id or config name
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub timestamp: u64,
+}
+
+// Every set field must match
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct AuditLogFilter {
+    pub actor: Option<Principal>,
+    pub action: Option<AuditAction>,
+    pub target: Option<String>,
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+}
+
+impl AuditLogFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actor.is_none_or(|actor| actor == entry.actor)
+            && self
+                .action
+                .as_ref()
+                .is_none_or(|action| *action == entry.action)
+            && self
+                .target
+                .as_ref()
+                .is_none_or(|target| *target == entry.target)
+            && self
+                .from_timestamp
+                .is_none_or(|from| entry.timestamp >= from)
+            && self.to_timestamp.is_none_or(|to| entry.timestamp <= to)
+    }
+}
+
+// Newest entries first
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct AuditLogPage {
+    pub entries: Vec<AuditEntry>,
+    pub next_before_id: Option<u64>, // Pass as before_id for older entries, None at the end
+}
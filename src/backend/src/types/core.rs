@@ -1,6 +1,9 @@
 use candid::{CandidType, Principal};
 use serde::{Deserialize, Serialize};
 
+use super::money::{BtcUsdRate, OusgE6s, Rounding, UsdCents};
+use super::oracle::PriceObservation;
+
 // ============= CORE DATA STRUCTURES =============
 
 // USTBill types removed - not used in current implementation
@@ -35,29 +38,64 @@ pub struct User {
 pub struct Deposit {
     pub id: u64,
     pub user_principal: Principal,
-    pub ckbtc_amount: u64,  // Deposit amount in ckBTC (8 decimals)
-    pub usd_value: f64,     // USD value of the deposit
-    pub btc_price_usd: f64, // BTC price at time of deposit
-    pub deposit_time: u64,  // Timestamp when deposit was made
-    pub block_index: u64,   // Block index of the deposit transaction
+    pub ckbtc_amount: u64,         // Deposit amount in ckBTC (8 decimals)
+    pub usd_value: UsdCents,       // USD value of the deposit
+    pub btc_price_usd: BtcUsdRate, // BTC price at time of deposit
+    pub deposit_time: u64,         // Timestamp when deposit was made
+    pub block_index: u64,          // Block index of the deposit transaction
     pub status: DepositStatus,
     pub ousg_minted: u64, // Amount of OUSG tokens minted (6 decimals)
     pub created_at: u64,
     pub updated_at: u64,
+    pub mint_created_at: Option<u64>, // Ledger created_at_time (ns) of the mint, reused on retries for dedup
+    pub mint_block_index: Option<u64>,
+    pub last_error: Option<String>,
+    pub refund_created_at: Option<u64>, // Ledger created_at_time (ns) of the refund, reused on retries
+    pub refund_block_index: Option<u64>,
+    pub subaccount: Option<Vec<u8>>, // Canister subaccount that received the ckBTC, None for the default account
+    pub sweep_created_at: Option<u64>, // Ledger created_at_time (ns) of the sweep into the default account
+    pub sweep_block_index: Option<u64>,
+    pub btc_price_timestamp: Option<u64>, // Oracle timestamp (seconds) btc_price_usd applies to
+    pub quote_id: Option<String>,         // Quote the deposit is priced at instead of the oracle
+    pub locked_price: Option<PriceObservation>, // Rate deposit_with_allowance checked before the pull
+    pub sender: Option<Principal>, // Account the ckBTC came from, refunds go back to it
+    pub sender_subaccount: Option<Vec<u8>>,
+}
+
+// Progress of the ckBTC ledger scan that credits deposit subaccounts
+#[derive(Clone, Debug, Default, CandidType, Deserialize, Serialize)]
+pub struct DepositScanState {
+    pub next_block: Option<u64>, // None until the first scan, which starts at the ledger tip
+    pub last_scan_at: Option<u64>,
+    pub deposits_found: u64,
+}
+
+// Reconciler bookkeeping for a deposit that did not complete, removed once it does
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct DepositRetryState {
+    pub deposit_id: u64,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_attempt_at: Option<u64>,
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
 pub enum DepositStatus {
-    Pending,   // Deposit received, waiting for validation
-    Validated, // Deposit validated, OUSG minted
-    Failed,    // Deposit validation failed
-    Processed, // Fully processed
+    Pending,      // Deposit received, waiting for validation
+    Validated,    // Deposit validated, OUSG minted
+    Failed,       // Deposit validation failed
+    Processed,    // Fully processed
+    Reserved,     // Block index claimed by a notify_deposit call that is still running
+    Minting,      // Mint sent to the OUSG ledger, outcome not yet recorded
+    ManualReview, // Reconciler gave up, an admin has to retry or refund
+    Refunded,     // ckBTC sent back to the depositor, nothing was minted
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
 pub struct DepositRequest {
-    pub ckbtc_amount: u64, // Deposit amount in ckBTC (8 decimals)
-    pub block_index: u64,  // Block index of the deposit transaction
+    pub ckbtc_amount: u64,        // Deposit amount in ckBTC (8 decimals)
+    pub block_index: u64,         // Block index of the deposit transaction
+    pub quote_id: Option<String>, // Quote from request_quote to lock in its price
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
@@ -68,6 +106,38 @@ pub struct DepositResponse {
     pub error_message: Option<String>,
 }
 
+// ============= OUSG REDEMPTION TYPES =============
+
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct Redemption {
+    pub id: u64,
+    pub user_principal: Principal,
+    pub ousg_amount: u64,          // OUSG tokens redeemed (6 decimals)
+    pub ckbtc_amount: u64,         // ckBTC owed to the user (8 decimals)
+    pub usd_value: UsdCents,       // USD value of the redeemed OUSG
+    pub btc_price_usd: BtcUsdRate, // BTC price at time of redemption
+    pub status: RedemptionStatus,
+    pub burn_block_index: Option<u64>, // OUSG ledger block of the burn
+    pub payout_block_index: Option<u64>, // ckBTC ledger block of the payout
+    pub refund_block_index: Option<u64>, // OUSG ledger block of the compensating re-mint
+    pub payout_created_at: u64,        // created_at_time (ns) reused by every payout attempt
+    pub payout_attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub quote_id: Option<String>, // Quote the redemption was priced at, None for the oracle rate
+    pub refund_created_at: Option<u64>, // created_at_time (ns) of the compensating re-mint, reused on retries
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub enum RedemptionStatus {
+    Requested, // Redemption recorded, OUSG not burned yet
+    Burned,    // OUSG burned, ckBTC payout in progress
+    PaidOut,   // ckBTC paid out to the user
+    Failed,    // Burn or payout failed, see last_error
+    Refunded,  // Payout abandoned, burned OUSG re-minted to the user
+}
+
 // ============= XRC (EXCHANGE RATE CANISTER) TYPES =============
 // These types are now imported from the generated exchange_rate_canister binding
 
@@ -176,8 +246,8 @@ impl Deposit {
         id: u64,
         user_principal: Principal,
         ckbtc_amount: u64,
-        usd_value: f64,
-        btc_price_usd: f64,
+        usd_value: UsdCents,
+        btc_price_usd: BtcUsdRate,
         block_index: u64,
     ) -> Self {
         let current_time = ic_cdk::api::time() / 1_000_000_000;
@@ -193,12 +263,47 @@ impl Deposit {
             ousg_minted: 0,
             created_at: current_time,
             updated_at: current_time,
+            mint_created_at: None,
+            mint_block_index: None,
+            last_error: None,
+            refund_created_at: None,
+            refund_block_index: None,
+            subaccount: None,
+            sweep_created_at: None,
+            sweep_block_index: None,
+            btc_price_timestamp: None,
+            quote_id: None,
+            locked_price: None,
+            sender: None,
+            sender_subaccount: None,
         }
     }
 
+    // Claims the block index before any await, price and amount are filled in once validated
+    pub fn new_reservation(id: u64, user_principal: Principal, block_index: u64) -> Self {
+        let mut deposit = Self::new(
+            id,
+            user_principal,
+            0,
+            UsdCents::default(),
+            BtcUsdRate::default(),
+            block_index,
+        );
+        deposit.status = DepositStatus::Reserved;
+        deposit
+    }
+
+    pub fn mark_minting(&mut self, ousg_to_mint: u64, mint_created_at: u64) {
+        self.status = DepositStatus::Minting;
+        self.ousg_minted = ousg_to_mint;
+        self.mint_created_at = Some(mint_created_at);
+        self.updated_at = ic_cdk::api::time() / 1_000_000_000;
+    }
+
     pub fn mark_validated(&mut self, ousg_minted: u64) {
         self.status = DepositStatus::Validated;
         self.ousg_minted = ousg_minted;
+        self.last_error = None;
         self.updated_at = ic_cdk::api::time() / 1_000_000_000;
     }
 
@@ -207,31 +312,179 @@ impl Deposit {
         self.updated_at = ic_cdk::api::time() / 1_000_000_000;
     }
 
+    pub fn mark_failed_with(&mut self, error: String) {
+        self.last_error = Some(error);
+        self.mark_failed();
+    }
+
+    pub fn mark_manual_review(&mut self) {
+        self.status = DepositStatus::ManualReview;
+        self.updated_at = ic_cdk::api::time() / 1_000_000_000;
+    }
+
+    pub fn mark_refunded(&mut self, refund_block_index: u64) {
+        self.status = DepositStatus::Refunded;
+        self.refund_block_index = Some(refund_block_index);
+        self.updated_at = ic_cdk::api::time() / 1_000_000_000;
+    }
+
+    // Nothing can have been minted yet, so returning the ckBTC is safe
+    pub fn is_refundable(&self) -> bool {
+        self.mint_created_at.is_none()
+            && matches!(
+                self.status,
+                DepositStatus::Pending
+                    | DepositStatus::Reserved
+                    | DepositStatus::Failed
+                    | DepositStatus::ManualReview
+            )
+    }
+
+    // Minted from a deposit subaccount whose ckBTC has not been moved to the default account yet
+    pub fn needs_sweep(&self) -> bool {
+        self.subaccount.is_some() && self.sweep_block_index.is_none() && self.is_complete()
+    }
+
+    // OUSG has been minted for this deposit, nothing left to do
+    pub fn is_complete(&self) -> bool {
+        matches!(
+            self.status,
+            DepositStatus::Validated | DepositStatus::Processed
+        )
+    }
+
     pub fn mark_processed(&mut self) {
         self.status = DepositStatus::Processed;
         self.updated_at = ic_cdk::api::time() / 1_000_000_000;
     }
 
-    // Calculate OUSG tokens to mint based on USD value (OUSG = UST-Bill = $5000)
-    pub fn calculate_ousg_to_mint(&self) -> u64 {
-        let ousg_tokens = self.usd_value / 5000.0; // Each OUSG = $5000
-                                                   // Convert to OUSG units (6 decimals)
-        (ousg_tokens * 1_000_000.0) as u64
+    // Calculate OUSG tokens to mint based on USD value, rounded down (None on overflow)
+    pub fn calculate_ousg_to_mint(&self) -> Option<u64> {
+        self.usd_value
+            .to_ousg(OUSG_UNIT_PRICE, Rounding::Down)
+            .map(|ousg| ousg.0)
     }
 }
 
-// ============= XRC HELPER FUNCTIONS =============
-// These functions are now handled directly in lib.rs using generated bindings
+// ============= REDEMPTION IMPLEMENTATIONS =============
 
-// Convert USD amount to ckBTC amount based on BTC price
-pub fn convert_usd_to_ckbtc(usd_amount: f64, btc_price_usd: f64) -> u64 {
-    let btc_amount = usd_amount / btc_price_usd;
-    // Convert to ckBTC units (8 decimals)
-    (btc_amount * 100_000_000.0) as u64
+impl Redemption {
+    pub fn new(
+        id: u64,
+        user_principal: Principal,
+        ousg_amount: u64,
+        ckbtc_amount: u64,
+        usd_value: UsdCents,
+        btc_price_usd: BtcUsdRate,
+    ) -> Self {
+        let now = ic_cdk::api::time();
+        let current_time = now / 1_000_000_000;
+        Self {
+            id,
+            user_principal,
+            ousg_amount,
+            ckbtc_amount,
+            usd_value,
+            btc_price_usd,
+            status: RedemptionStatus::Requested,
+            burn_block_index: None,
+            payout_block_index: None,
+            refund_block_index: None,
+            payout_created_at: now,
+            payout_attempts: 0,
+            last_error: None,
+            created_at: current_time,
+            updated_at: current_time,
+            quote_id: None,
+            refund_created_at: None,
+        }
+    }
+
+    pub fn mark_burned(&mut self, burn_block_index: u64) {
+        self.status = RedemptionStatus::Burned;
+        self.burn_block_index = Some(burn_block_index);
+        self.updated_at = ic_cdk::api::time() / 1_000_000_000;
+    }
+
+    pub fn mark_paid_out(&mut self, payout_block_index: u64) {
+        self.status = RedemptionStatus::PaidOut;
+        self.payout_block_index = Some(payout_block_index);
+        self.last_error = None;
+        self.updated_at = ic_cdk::api::time() / 1_000_000_000;
+    }
+
+    pub fn mark_failed(&mut self, error: String) {
+        self.status = RedemptionStatus::Failed;
+        self.last_error = Some(error);
+        self.updated_at = ic_cdk::api::time() / 1_000_000_000;
+    }
+
+    pub fn mark_refunded(&mut self, refund_block_index: u64) {
+        self.status = RedemptionStatus::Refunded;
+        self.refund_block_index = Some(refund_block_index);
+        self.updated_at = ic_cdk::api::time() / 1_000_000_000;
+    }
+
+    // A failed redemption only needs compensation if the user's OUSG was already burned
+    pub fn needs_compensation(&self) -> bool {
+        self.status == RedemptionStatus::Failed && self.burn_block_index.is_some()
+    }
+}
+
+// Each OUSG = one UST-Bill = $5000
+pub const OUSG_UNIT_PRICE: UsdCents = UsdCents::from_dollars(5000);
+
+// USD value of an OUSG amount, rounded down
+pub fn convert_ousg_to_usd(ousg_amount: u64) -> Option<UsdCents> {
+    OusgE6s(ousg_amount).to_usd(OUSG_UNIT_PRICE, Rounding::Down)
+}
+
+// ============= LEGACY RECORD FORMATS =============
+// Deposits written before the fixed-point money types stored the USD value and BTC price as
+// f64. Storage decodes them with this and converts on read, see storage.rs.
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct LegacyDeposit {
+    pub id: u64,
+    pub user_principal: Principal,
+    pub ckbtc_amount: u64,
+    pub usd_value: f64,
+    pub btc_price_usd: f64,
+    pub deposit_time: u64,
+    pub block_index: u64,
+    pub status: DepositStatus,
+    pub ousg_minted: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
 }
 
-// Convert ckBTC amount to USD based on BTC price
-pub fn convert_ckbtc_to_usd(ckbtc_amount: u64, btc_price_usd: f64) -> f64 {
-    let btc_amount = ckbtc_amount as f64 / 100_000_000.0;
-    btc_amount * btc_price_usd
+impl From<LegacyDeposit> for Deposit {
+    fn from(legacy: LegacyDeposit) -> Self {
+        Self {
+            id: legacy.id,
+            user_principal: legacy.user_principal,
+            ckbtc_amount: legacy.ckbtc_amount,
+            usd_value: UsdCents::from_legacy_dollars(legacy.usd_value),
+            btc_price_usd: BtcUsdRate::from_legacy_dollars(legacy.btc_price_usd),
+            deposit_time: legacy.deposit_time,
+            block_index: legacy.block_index,
+            status: legacy.status,
+            ousg_minted: legacy.ousg_minted,
+            created_at: legacy.created_at,
+            updated_at: legacy.updated_at,
+            mint_created_at: None,
+            mint_block_index: None,
+            last_error: None,
+            refund_created_at: None,
+            refund_block_index: None,
+            subaccount: None,
+            sweep_created_at: None,
+            sweep_block_index: None,
+            btc_price_timestamp: None,
+            quote_id: None,
+            locked_price: None,
+            sender: None,
+            sender_subaccount: None,
+        }
+    }
 }
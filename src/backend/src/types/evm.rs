@@ -0,0 +1,222 @@
+use candid::{CandidType, Principal};
+use serde::Deserialize;
+
+use crate::evm_rpc::{
+    ConsensusStrategy, EthMainnetService, EthSepoliaService, L2MainnetService, RpcConfig,
+    RpcService, RpcServices,
+};
+
+// ============= EVM RPC CONFIGURATION =============
+
+// Providers used for every EVM RPC canister call (admin-configurable)
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct EvmConfig {
+    pub ecdsa_key_name: String, // Threshold ECDSA key, "key_1" on mainnet, "test_key_1" on testnet
+    pub chain_id: u64,          // EIP-155 chain id signed into every transaction
+    pub rpc_services: RpcServices,
+    pub response_consensus: Option<ConsensusStrategy>, // None = all providers must agree
+    pub required_confirmations: u64, // Blocks on top of the receipt before a tx is final
+    pub gas_policy: GasPolicy,
+}
+
+// How EIP-1559 fees and gas limits are derived from the network (all fees in wei)
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct GasPolicy {
+    pub fee_history_blocks: u64, // Recent blocks sampled through eth_feeHistory
+    pub priority_fee_percentile: u8, // Reward percentile used as the priority fee
+    pub base_fee_multiplier_percent: u64, // Headroom over the next base fee, 200 = 2x
+    pub min_priority_fee_per_gas: u64,
+    pub max_priority_fee_per_gas_cap: u64,
+    pub max_fee_per_gas_cap: u64, // Transfers are refused while the network is above it
+    pub gas_limit_buffer_percent: u64, // Added on top of eth_estimateGas
+    pub gas_limit_cap: u64,
+}
+
+impl Default for GasPolicy {
+    fn default() -> Self {
+        Self {
+            fee_history_blocks: 10,
+            priority_fee_percentile: 50,
+            base_fee_multiplier_percent: 200,
+            min_priority_fee_per_gas: 100_000_000, // 0.1 gwei
+            max_priority_fee_per_gas_cap: 5_000_000_000, // 5 gwei
+            max_fee_per_gas_cap: 200_000_000_000,  // 200 gwei
+            gas_limit_buffer_percent: 20,
+            gas_limit_cap: 200_000,
+        }
+    }
+}
+
+impl Default for EvmConfig {
+    fn default() -> Self {
+        Self {
+            ecdsa_key_name: "key_1".to_string(),
+            chain_id: 1,
+            rpc_services: RpcServices::EthMainnet(Some(vec![
+                EthMainnetService::Alchemy,
+                EthMainnetService::Ankr,
+                EthMainnetService::PublicNode,
+            ])),
+            // 2-of-3 so a single lagging provider doesn't block submissions
+            response_consensus: Some(ConsensusStrategy::Threshold {
+                min: 2,
+                total: Some(3),
+            }),
+            required_confirmations: 12,
+            gas_policy: GasPolicy::default(),
+        }
+    }
+}
+
+// Network selection passed at init/upgrade or through a SetEvmNetwork treasury proposal,
+// unset fields keep their current value
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct EvmNetworkArgs {
+    pub ecdsa_key_name: Option<String>,
+    pub chain_id: Option<u64>, // Defaults to the chain of rpc_services when they change
+    pub rpc_services: Option<RpcServices>,
+    pub response_consensus: Option<ConsensusStrategy>, // Only read together with rpc_services
+}
+
+impl EvmConfig {
+    // Switching providers also replaces the consensus strategy, a 2-of-3 threshold
+    // can never be met by a single Custom provider
+    pub fn apply_network_args(&mut self, args: EvmNetworkArgs) -> Result<(), String> {
+        let mut updated = self.clone();
+        if let Some(key_name) = args.ecdsa_key_name {
+            updated.ecdsa_key_name = key_name;
+        }
+        if let Some(rpc_services) = args.rpc_services {
+            updated.chain_id = network_chain_id(&rpc_services).unwrap_or(updated.chain_id);
+            updated.rpc_services = rpc_services;
+            updated.response_consensus = args.response_consensus;
+        }
+        if let Some(chain_id) = args.chain_id {
+            updated.chain_id = chain_id;
+        }
+
+        updated.validate()?;
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ecdsa_key_name.trim().is_empty() {
+            return Err("ECDSA key name cannot be empty".to_string());
+        }
+        if self.chain_id == 0 {
+            return Err("Chain id cannot be zero".to_string());
+        }
+        if let Some(expected) = network_chain_id(&self.rpc_services) {
+            if expected != self.chain_id {
+                return Err(format!(
+                    "Chain id {} does not match the RPC services network (chain id {})",
+                    self.chain_id, expected
+                ));
+            }
+        }
+        if let RpcServices::Custom { services, .. } = &self.rpc_services {
+            if services.is_empty() {
+                return Err("Custom RPC services need at least one provider".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // Single provider for calls that go through the raw `request` endpoint
+    pub fn primary_rpc_service(&self) -> Option<RpcService> {
+        fn first_or<T: Clone>(providers: &Option<Vec<T>>, default: T) -> T {
+            providers
+                .as_ref()
+                .and_then(|providers| providers.first().cloned())
+                .unwrap_or(default)
+        }
+
+        Some(match &self.rpc_services {
+            RpcServices::EthMainnet(providers) => {
+                RpcService::EthMainnet(first_or(providers, EthMainnetService::PublicNode))
+            }
+            RpcServices::EthSepolia(providers) => {
+                RpcService::EthSepolia(first_or(providers, EthSepoliaService::PublicNode))
+            }
+            RpcServices::ArbitrumOne(providers) => {
+                RpcService::ArbitrumOne(first_or(providers, L2MainnetService::PublicNode))
+            }
+            RpcServices::BaseMainnet(providers) => {
+                RpcService::BaseMainnet(first_or(providers, L2MainnetService::PublicNode))
+            }
+            RpcServices::OptimismMainnet(providers) => {
+                RpcService::OptimismMainnet(first_or(providers, L2MainnetService::PublicNode))
+            }
+            RpcServices::Custom { services, .. } => RpcService::Custom(services.first()?.clone()),
+        })
+    }
+
+    pub fn rpc_config(&self) -> Option<RpcConfig> {
+        self.response_consensus.clone().map(|consensus| RpcConfig {
+            responseConsensus: Some(consensus),
+            responseSizeEstimate: None,
+        })
+    }
+}
+
+// Chain id served by the EVM RPC canister for a given provider set
+fn network_chain_id(rpc_services: &RpcServices) -> Option<u64> {
+    match rpc_services {
+        RpcServices::EthMainnet(_) => Some(1),
+        RpcServices::EthSepolia(_) => Some(11_155_111),
+        RpcServices::ArbitrumOne(_) => Some(42_161),
+        RpcServices::BaseMainnet(_) => Some(8_453),
+        RpcServices::OptimismMainnet(_) => Some(10),
+        RpcServices::Custom { chainId, .. } => Some(*chainId),
+    }
+}
+
+// ============= EVM TRANSACTION SUBMISSION =============
+
+// Outcome of eth_sendRawTransaction, mirroring SendRawTransactionStatus
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub enum RawTransactionSubmission {
+    Submitted { transaction_hash: String },
+    NonceTooLow,
+    NonceTooHigh,
+    InsufficientFunds,
+}
+
+// ============= OUTBOUND EVM TRANSACTION TRACKING =============
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct EvmTransaction {
+    pub id: u64,
+    pub requested_by: Principal,
+    pub transaction_hash: String,
+    pub from_address: String,
+    pub contract_address: String,
+    pub recipient: String,
+    pub amount: String, // Token amount in base units
+    pub nonce: u64,
+    pub gas_limit: String, // Gas values as decimal strings (wei)
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    pub status: EvmTransactionStatus,
+    pub block_number: Option<u64>, // Block that included the transaction
+    pub confirmations: u64,
+    pub gas_used: Option<String>,
+    pub effective_gas_price: Option<String>,
+    pub submitted_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub enum EvmTransactionStatus {
+    Pending,   // Submitted, waiting for a receipt and enough confirmations
+    Confirmed, // Mined successfully with the required confirmations
+    Reverted,  // Mined but execution failed (receipt status 0)
+    Dropped,   // Never mined, nonce consumed by another finalized transaction
+}
+
+impl EvmTransaction {
+    pub fn is_final(&self) -> bool {
+        self.status != EvmTransactionStatus::Pending
+    }
+}
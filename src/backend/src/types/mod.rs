@@ -2,16 +2,29 @@
 // Refactored types module with clear organization
 
 pub mod api;
+pub mod audit;
 pub mod core;
 pub mod document;
+pub mod evm;
 pub mod external;
 pub mod kyc;
+pub mod money;
+pub mod oracle;
 pub mod platform;
+pub mod quote;
 pub mod trading;
+pub mod treasury;
 
 // Re-export only used types
 pub use api::*;
+pub use audit::*;
 pub use core::*;
 pub use document::*;
+pub use evm::*;
 pub use kyc::*;
+pub use money::*;
+pub use oracle::*;
+pub use platform::*;
+pub use quote::*;
 pub use trading::*;
+pub use treasury::*;
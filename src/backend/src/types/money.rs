@@ -0,0 +1,271 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+// ============= FIXED-POINT MONEY TYPES =============
+// Amounts are integers in the smallest unit. Conversions multiply before dividing in u128,
+// fail on overflow instead of wrapping, and take an explicit rounding mode: callers always
+// pick the mode that favors the platform (credit and pay out rounded down).
+
+const E8: u128 = 100_000_000;
+const E6: u128 = 1_000_000;
+const CENTS_PER_USD: u128 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// USD amount in cents
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, CandidType, Deserialize, Serialize,
+)]
+pub struct UsdCents(pub u64);
+
+/// ckBTC amount in e8s (1 ckBTC = 100_000_000 e8s)
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, CandidType, Deserialize, Serialize,
+)]
+pub struct CkbtcE8s(pub u64);
+
+/// OUSG amount in e6s (1 OUSG = 1_000_000 e6s)
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, CandidType, Deserialize, Serialize,
+)]
+pub struct OusgE6s(pub u64);
+
+/// Price of one BTC in USD with 8 decimals
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, CandidType, Deserialize, Serialize,
+)]
+pub struct BtcUsdRate(pub u64);
+
+// value * mul / div without intermediate overflow, None if the result does not fit
+fn mul_div(value: u64, mul: u128, div: u128, rounding: Rounding) -> Option<u64> {
+    if div == 0 {
+        return None;
+    }
+    let product = (value as u128).checked_mul(mul)?;
+    let quotient = match rounding {
+        Rounding::Down => product / div,
+        Rounding::Up => product.div_ceil(div),
+    };
+    u64::try_from(quotient).ok()
+}
+
+impl UsdCents {
+    pub const fn from_dollars(dollars: u64) -> Self {
+        UsdCents(dollars * CENTS_PER_USD as u64)
+    }
+
+    /// ckBTC worth this many cents at `rate`
+    pub fn to_ckbtc(self, rate: BtcUsdRate, rounding: Rounding) -> Option<CkbtcE8s> {
+        mul_div(self.0, E8 * E8, CENTS_PER_USD * rate.0 as u128, rounding).map(CkbtcE8s)
+    }
+
+    /// OUSG worth this many cents when one OUSG costs `unit_price`
+    pub fn to_ousg(self, unit_price: UsdCents, rounding: Rounding) -> Option<OusgE6s> {
+        mul_div(self.0, E6, unit_price.0 as u128, rounding).map(OusgE6s)
+    }
+
+    // Records written before the fixed-point types stored dollars as f64
+    pub fn from_legacy_dollars(dollars: f64) -> Self {
+        UsdCents((dollars * CENTS_PER_USD as f64).floor() as u64)
+    }
+}
+
+impl std::fmt::Display for UsdCents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${}.{:02}", self.0 / 100, self.0 % 100)
+    }
+}
+
+impl CkbtcE8s {
+    /// USD value of this amount at `rate`
+    pub fn to_usd(self, rate: BtcUsdRate, rounding: Rounding) -> Option<UsdCents> {
+        mul_div(self.0, rate.0 as u128 * CENTS_PER_USD, E8 * E8, rounding).map(UsdCents)
+    }
+}
+
+impl OusgE6s {
+    /// USD value of this amount when one OUSG costs `unit_price`
+    pub fn to_usd(self, unit_price: UsdCents, rounding: Rounding) -> Option<UsdCents> {
+        mul_div(self.0, unit_price.0 as u128, E6, rounding).map(UsdCents)
+    }
+}
+
+impl BtcUsdRate {
+    pub const fn from_dollars(dollars: u64) -> Self {
+        BtcUsdRate(dollars * E8 as u64)
+    }
+
+    // Records written before the fixed-point types stored the price as f64
+    pub fn from_legacy_dollars(dollars: f64) -> Self {
+        BtcUsdRate((dollars * E8 as f64).round() as u64)
+    }
+
+    /// Converts a rate with `decimals` decimal places (as reported by the XRC)
+    pub fn from_scaled(rate: u64, decimals: u32, rounding: Rounding) -> Option<Self> {
+        const DECIMALS: u32 = 8;
+        let scaled = if decimals >= DECIMALS {
+            let divisor = 10u128.checked_pow(decimals - DECIMALS)?;
+            mul_div(rate, 1, divisor, rounding)?
+        } else {
+            rate.checked_mul(10u64.checked_pow(DECIMALS - decimals)?)?
+        };
+        Some(BtcUsdRate(scaled))
+    }
+
+    /// This rate scaled by `bps` basis points, e.g. 9_950 takes 0.5% off
+    pub fn scale_bps(self, bps: u64, rounding: Rounding) -> Option<Self> {
+        mul_div(self.0, bps as u128, 10_000, rounding).map(BtcUsdRate)
+    }
+}
+
+impl std::fmt::Display for BtcUsdRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${}.{:08}", self.0 / E8 as u64, self.0 % E8 as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BTC_AT_100K: BtcUsdRate = BtcUsdRate::from_dollars(100_000);
+
+    #[test]
+    fn mul_div_rounds_as_asked() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down), Some(3));
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up), Some(4));
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up), Some(3));
+        assert_eq!(mul_div(0, 5, 7, Rounding::Up), Some(0));
+    }
+
+    #[test]
+    fn mul_div_fails_instead_of_overflowing() {
+        assert_eq!(mul_div(1, 1, 0, Rounding::Down), None);
+        assert_eq!(mul_div(u64::MAX, u128::MAX, 1, Rounding::Down), None);
+        assert_eq!(mul_div(u64::MAX, 2, 1, Rounding::Down), None);
+        // The intermediate product may exceed u64 as long as the result fits
+        assert_eq!(mul_div(u64::MAX, 4, 4, Rounding::Down), Some(u64::MAX));
+    }
+
+    #[test]
+    fn ckbtc_to_usd() {
+        assert_eq!(
+            CkbtcE8s(100_000_000).to_usd(BTC_AT_100K, Rounding::Down),
+            Some(UsdCents::from_dollars(100_000))
+        );
+        // 1 e8s is worth 0.1 cents at $100k
+        assert_eq!(
+            CkbtcE8s(1).to_usd(BTC_AT_100K, Rounding::Down),
+            Some(UsdCents(0))
+        );
+        assert_eq!(
+            CkbtcE8s(1).to_usd(BTC_AT_100K, Rounding::Up),
+            Some(UsdCents(1))
+        );
+        assert_eq!(
+            CkbtcE8s(u64::MAX).to_usd(BtcUsdRate(u64::MAX), Rounding::Down),
+            None
+        );
+    }
+
+    #[test]
+    fn usd_to_ckbtc() {
+        assert_eq!(
+            UsdCents::from_dollars(5_000).to_ckbtc(BTC_AT_100K, Rounding::Down),
+            Some(CkbtcE8s(5_000_000))
+        );
+        // One cent is 10 e8s at $100k, a third of a dollar is not a whole number of e8s
+        assert_eq!(
+            UsdCents(1).to_ckbtc(BtcUsdRate::from_dollars(300_000), Rounding::Down),
+            Some(CkbtcE8s(3))
+        );
+        assert_eq!(
+            UsdCents(1).to_ckbtc(BtcUsdRate::from_dollars(300_000), Rounding::Up),
+            Some(CkbtcE8s(4))
+        );
+        assert_eq!(UsdCents(1).to_ckbtc(BtcUsdRate(0), Rounding::Down), None);
+        assert_eq!(
+            UsdCents(u64::MAX).to_ckbtc(BtcUsdRate(1), Rounding::Down),
+            None
+        );
+    }
+
+    #[test]
+    fn usd_and_ousg() {
+        let unit_price = UsdCents::from_dollars(100);
+        assert_eq!(
+            UsdCents::from_dollars(5_000).to_ousg(unit_price, Rounding::Down),
+            Some(OusgE6s(50_000_000))
+        );
+        assert_eq!(
+            UsdCents(1).to_ousg(UsdCents(3), Rounding::Down),
+            Some(OusgE6s(333_333))
+        );
+        assert_eq!(
+            UsdCents(1).to_ousg(UsdCents(3), Rounding::Up),
+            Some(OusgE6s(333_334))
+        );
+        assert_eq!(UsdCents(1).to_ousg(UsdCents(0), Rounding::Down), None);
+
+        assert_eq!(
+            OusgE6s(50_000_000).to_usd(unit_price, Rounding::Down),
+            Some(UsdCents::from_dollars(5_000))
+        );
+        assert_eq!(
+            OusgE6s(1).to_usd(unit_price, Rounding::Down),
+            Some(UsdCents(0))
+        );
+        assert_eq!(
+            OusgE6s(1).to_usd(unit_price, Rounding::Up),
+            Some(UsdCents(1))
+        );
+        assert_eq!(
+            OusgE6s(u64::MAX).to_usd(UsdCents(u64::MAX), Rounding::Down),
+            None
+        );
+    }
+
+    #[test]
+    fn rate_from_scaled() {
+        // Fewer decimals than ours are scaled up exactly
+        assert_eq!(
+            BtcUsdRate::from_scaled(100_000, 0, Rounding::Down),
+            Some(BTC_AT_100K)
+        );
+        assert_eq!(
+            BtcUsdRate::from_scaled(10_000_000_000_000, 8, Rounding::Down),
+            Some(BTC_AT_100K)
+        );
+        // More decimals are divided down with the requested rounding
+        assert_eq!(
+            BtcUsdRate::from_scaled(123_456_789, 9, Rounding::Down),
+            Some(BtcUsdRate(12_345_678))
+        );
+        assert_eq!(
+            BtcUsdRate::from_scaled(123_456_789, 9, Rounding::Up),
+            Some(BtcUsdRate(12_345_679))
+        );
+        assert_eq!(BtcUsdRate::from_scaled(u64::MAX, 0, Rounding::Down), None);
+        assert_eq!(BtcUsdRate::from_scaled(1, 100, Rounding::Down), None);
+    }
+
+    #[test]
+    fn rate_scale_bps() {
+        assert_eq!(
+            BTC_AT_100K.scale_bps(9_950, Rounding::Down),
+            Some(BtcUsdRate::from_dollars(99_500))
+        );
+        assert_eq!(
+            BtcUsdRate(3).scale_bps(5_000, Rounding::Down),
+            Some(BtcUsdRate(1))
+        );
+        assert_eq!(
+            BtcUsdRate(3).scale_bps(5_000, Rounding::Up),
+            Some(BtcUsdRate(2))
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use super::money::BtcUsdRate;
+
+// ============= PRICE ORACLE TYPES =============
+
+// A TWAP window has to span a few history samples and fit inside the retained history
+pub const MIN_TWAP_WINDOW_SECONDS: u64 = 10 * 60;
+pub const MAX_TWAP_WINDOW_SECONDS: u64 = 24 * 60 * 60;
+
+/// Quality bar an XRC rate has to clear before it is used for minting or payouts
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct PriceOracleConfig {
+    pub min_base_sources: u64, // Minimum number of exchanges that returned a BTC rate
+    pub min_received_percent: u64, // Share of queried sources that must have answered
+    pub max_std_dev_bps: u64,  // Maximum standard deviation relative to the rate, in basis points
+    pub max_rate_age_seconds: u64, // Older rates (fetched or cached) are rejected
+    pub test_mode: bool,       // Allows the fixed test rate when no acceptable rate exists
+    pub test_rate: Option<BtcUsdRate>,
+    pub twap_window_seconds: Option<u64>, // Price deposits and redemptions at a TWAP, None for spot
+}
+
+impl Default for PriceOracleConfig {
+    fn default() -> Self {
+        Self {
+            min_base_sources: 3,
+            min_received_percent: 50,
+            max_std_dev_bps: 100,
+            max_rate_age_seconds: 10 * 60,
+            test_mode: false,
+            test_rate: None,
+            twap_window_seconds: None,
+        }
+    }
+}
+
+// Quality bar changes passed through a SetPriceOracleConfig treasury proposal, unset fields
+// keep their current value. Test mode is not part of it, it can only be set at install.
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct PriceOracleArgs {
+    pub min_base_sources: Option<u64>,
+    pub min_received_percent: Option<u64>,
+    pub max_std_dev_bps: Option<u64>,
+    pub max_rate_age_seconds: Option<u64>,
+    pub twap_window_seconds: Option<Option<u64>>, // Some(None) switches back to spot pricing
+}
+
+impl PriceOracleConfig {
+    pub fn apply_args(&mut self, args: PriceOracleArgs) -> Result<(), String> {
+        let mut updated = self.clone();
+        if let Some(min_base_sources) = args.min_base_sources {
+            updated.min_base_sources = min_base_sources;
+        }
+        if let Some(min_received_percent) = args.min_received_percent {
+            updated.min_received_percent = min_received_percent;
+        }
+        if let Some(max_std_dev_bps) = args.max_std_dev_bps {
+            updated.max_std_dev_bps = max_std_dev_bps;
+        }
+        if let Some(max_rate_age_seconds) = args.max_rate_age_seconds {
+            updated.max_rate_age_seconds = max_rate_age_seconds;
+        }
+        if let Some(twap_window_seconds) = args.twap_window_seconds {
+            updated.twap_window_seconds = twap_window_seconds;
+        }
+
+        updated.validate()?;
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_base_sources == 0 {
+            return Err("min_base_sources must be at least 1".to_string());
+        }
+        if self.min_received_percent > 100 {
+            return Err("min_received_percent cannot exceed 100".to_string());
+        }
+        if self.max_std_dev_bps == 0 || self.max_rate_age_seconds == 0 {
+            return Err("max_std_dev_bps and max_rate_age_seconds must be positive".to_string());
+        }
+        if self.test_rate.is_some_and(|rate| rate.0 == 0) {
+            return Err("test_rate must be positive".to_string());
+        }
+        if let Some(window) = self.twap_window_seconds {
+            if !(MIN_TWAP_WINDOW_SECONDS..=MAX_TWAP_WINDOW_SECONDS).contains(&window) {
+                return Err(format!(
+                    "twap_window_seconds must be between {} and {}",
+                    MIN_TWAP_WINDOW_SECONDS, MAX_TWAP_WINDOW_SECONDS
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Last rate that passed the quality checks
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct CachedRate {
+    pub rate: BtcUsdRate,
+    pub rate_timestamp: u64, // XRC timestamp of the rate (seconds)
+    pub fetched_at: u64,
+}
+
+/// Where a rate handed out by the oracle came from
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub enum RateSource {
+    Xrc,
+    Cache,
+    TestFallback,
+    Twap, // Time-weighted average over the price history
+}
+
+/// A rate handed out by the oracle and the time (seconds) it applies to
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct PriceObservation {
+    pub rate: BtcUsdRate,
+    pub timestamp: u64,
+    pub source: RateSource,
+}
+
+/// BTC/USD rate recorded by the price history timer, keyed by its XRC timestamp
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct PriceSample {
+    pub timestamp: u64, // XRC timestamp of the rate (seconds)
+    pub rate: BtcUsdRate,
+    pub fetched_at: u64,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct PriceOracleState {
+    pub config: PriceOracleConfig,
+    pub last_good_rate: Option<CachedRate>,
+    pub xrc_error_counts: Vec<(String, u64)>, // Failed XRC calls by error code
+    pub latest_sample: Option<PriceSample>,
+    pub sample_count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_args_keep_the_current_settings() {
+        let mut config = PriceOracleConfig {
+            test_mode: true,
+            ..PriceOracleConfig::default()
+        };
+        config
+            .apply_args(PriceOracleArgs {
+                max_rate_age_seconds: Some(300),
+                ..PriceOracleArgs::default()
+            })
+            .unwrap();
+
+        assert_eq!(config.max_rate_age_seconds, 300);
+        assert_eq!(config.min_base_sources, 3);
+        assert!(config.test_mode);
+    }
+
+    #[test]
+    fn invalid_args_leave_the_config_unchanged() {
+        let mut config = PriceOracleConfig::default();
+        let result = config.apply_args(PriceOracleArgs {
+            min_base_sources: Some(5),
+            min_received_percent: Some(101),
+            ..PriceOracleArgs::default()
+        });
+
+        assert!(result.is_err());
+        assert_eq!(config.min_base_sources, 3);
+        assert_eq!(config.min_received_percent, 50);
+    }
+
+    #[test]
+    fn sets_and_clears_the_twap_window() {
+        let mut config = PriceOracleConfig::default();
+        let window = |twap_window_seconds| PriceOracleArgs {
+            twap_window_seconds,
+            ..PriceOracleArgs::default()
+        };
+
+        config.apply_args(window(Some(Some(30 * 60)))).unwrap();
+        assert_eq!(config.twap_window_seconds, Some(30 * 60));
+
+        config.apply_args(window(None)).unwrap();
+        assert_eq!(config.twap_window_seconds, Some(30 * 60));
+
+        assert!(config.apply_args(window(Some(Some(60)))).is_err());
+        assert_eq!(config.twap_window_seconds, Some(30 * 60));
+
+        config.apply_args(window(Some(None))).unwrap();
+        assert_eq!(config.twap_window_seconds, None);
+    }
+}
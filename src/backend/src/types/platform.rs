@@ -1,2 +1,83 @@
-// This file is currently empty as PlatformConfig is not used
-// Keep this file for future platform configuration implementation
\ No newline at end of file
+use candid::{CandidType, Principal};
+use serde::Deserialize;
+
+// ============= EXTERNAL CANISTER CONFIGURATION =============
+
+// Canisters the backend calls, set at install time so each environment only differs by its init args
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct CanisterIds {
+    pub ousg_ledger: Principal,
+    pub ckbtc_ledger: Principal,
+    pub xrc: Principal,
+    pub file_store_bucket: Principal,
+    pub evm_rpc: Principal,
+}
+
+// Local dfx deployment, used when the install arguments leave an id unset
+impl Default for CanisterIds {
+    fn default() -> Self {
+        Self {
+            ousg_ledger: Principal::from_text("ucwa4-rx777-77774-qaada-cai").unwrap(),
+            ckbtc_ledger: Principal::from_text("mxzaz-hqaaa-aaaar-qaada-cai").unwrap(),
+            xrc: Principal::from_text("uf6dk-hyaaa-aaaaq-qaaaq-cai").unwrap(),
+            file_store_bucket: Principal::from_text("uzt4z-lp777-77774-qaabq-cai").unwrap(),
+            evm_rpc: Principal::from_text("uxrrr-q7777-77774-qaaaq-cai").unwrap(),
+        }
+    }
+}
+
+// Partial update of the external canister ids, unset fields keep their current value
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct CanisterIdsArgs {
+    pub ousg_ledger: Option<Principal>,
+    pub ckbtc_ledger: Option<Principal>,
+    pub xrc: Option<Principal>,
+    pub file_store_bucket: Option<Principal>,
+    pub evm_rpc: Option<Principal>,
+}
+
+impl CanisterIds {
+    pub fn apply(&mut self, args: CanisterIdsArgs) {
+        if let Some(id) = args.ousg_ledger {
+            self.ousg_ledger = id;
+        }
+        if let Some(id) = args.ckbtc_ledger {
+            self.ckbtc_ledger = id;
+        }
+        if let Some(id) = args.xrc {
+            self.xrc = id;
+        }
+        if let Some(id) = args.file_store_bucket {
+            self.file_store_bucket = id;
+        }
+        if let Some(id) = args.evm_rpc {
+            self.evm_rpc = id;
+        }
+    }
+}
+
+// ============= ACCESS CONTROL =============
+
+// SuperAdmin passes every role check
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    SuperAdmin,       // Manages roles and platform configuration
+    KycReviewer,      // Reviews KYC submissions
+    TreasuryOperator, // Moves funds: payouts, refunds, ERC-20 transfers, gas policy
+    Auditor,          // Read-only access to admin views
+}
+
+impl Role {
+    // Holders of these roles are counted as treasury approvers
+    pub fn approves_treasury(&self) -> bool {
+        matches!(self, Role::SuperAdmin | Role::TreasuryOperator)
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct RoleAssignment {
+    pub principal: Principal,
+    pub roles: Vec<Role>,
+    pub granted_at: u64,
+    pub updated_at: u64,
+}
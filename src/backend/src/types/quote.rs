@@ -0,0 +1,30 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use super::money::{BtcUsdRate, UsdCents};
+
+// ============= PRICE QUOTE TYPES =============
+
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, Serialize, PartialEq)]
+pub enum QuoteSide {
+    Deposit,    // ckBTC in, OUSG out
+    Redemption, // OUSG in, ckBTC out
+}
+
+/// Price locked in for one deposit or redemption until `expires_at`
+#[derive(Clone, Debug, CandidType, Deserialize, Serialize)]
+pub struct Quote {
+    pub id: String, // "<sequence>-<signature>", see quotes::sign
+    pub user_principal: Principal,
+    pub side: QuoteSide,
+    pub btc_price_usd: BtcUsdRate, // Price the trade settles at, spread included
+    pub mid_price_usd: BtcUsdRate, // Oracle rate before the spread
+    pub price_timestamp: u64,      // Oracle timestamp (seconds) the mid price applies to
+    pub spread_bps: u64,
+    pub ckbtc_amount: u64, // ckBTC deposited or paid out (8 decimals)
+    pub ousg_amount: u64,  // OUSG minted or redeemed (6 decimals)
+    pub usd_value: UsdCents,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub used_at: Option<u64>, // Set once a deposit or redemption claimed the quote
+}
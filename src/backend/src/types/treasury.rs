@@ -0,0 +1,117 @@
+use candid::{CandidType, Principal};
+use serde::Deserialize;
+
+use super::{CanisterIdsArgs, EvmConfig, EvmNetworkArgs, GasPolicy, PriceOracleArgs, Role};
+
+// ============= MULTI-SIGNATURE TREASURY OPERATIONS =============
+
+// Actions that move treasury funds or change how the canister talks to the outside world,
+// they only run once enough approvers signed off on a proposal
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum TreasuryOperation {
+    MintOusg {
+        recipient: Principal,
+        ousg_amount: u64,
+    },
+    WithdrawCkbtc {
+        recipient: Principal,
+        ckbtc_amount: u64,
+    },
+    Erc20Transfer {
+        contract_address: String,
+        recipient: String,
+        amount: String,
+    },
+    SetEvmConfig(EvmConfig),
+    SetEvmNetwork(EvmNetworkArgs),
+    SetGasPolicy(GasPolicy),
+    SetCanisterIds(CanisterIdsArgs),
+    SetMultisigPolicy(MultisigPolicy),
+    SetPriceOracleConfig(PriceOracleArgs),
+    // Only for approver roles, the others are granted directly by a SuperAdmin
+    GrantRole {
+        principal: Principal,
+        role: Role,
+    },
+    RevokeRole {
+        principal: Principal,
+        role: Role,
+    },
+}
+
+impl TreasuryOperation {
+    // Configuration changes stay with SuperAdmins, fund movements with treasury operators
+    pub fn proposer_role(&self) -> Role {
+        match self {
+            TreasuryOperation::MintOusg { .. }
+            | TreasuryOperation::WithdrawCkbtc { .. }
+            | TreasuryOperation::Erc20Transfer { .. }
+            | TreasuryOperation::SetGasPolicy(_) => Role::TreasuryOperator,
+            TreasuryOperation::SetEvmConfig(_)
+            | TreasuryOperation::SetEvmNetwork(_)
+            | TreasuryOperation::SetCanisterIds(_)
+            | TreasuryOperation::SetMultisigPolicy(_)
+            | TreasuryOperation::SetPriceOracleConfig(_)
+            | TreasuryOperation::GrantRole { .. }
+            | TreasuryOperation::RevokeRole { .. } => Role::SuperAdmin,
+        }
+    }
+
+    // Ledger transfers reuse the proposal's created_at_time, the ledger deduplicates a retry
+    // of one that went through after all
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            TreasuryOperation::MintOusg { .. } | TreasuryOperation::WithdrawCkbtc { .. }
+        )
+    }
+}
+
+// M of the N treasury approvers (TreasuryOperator and SuperAdmin holders) must approve
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct MultisigPolicy {
+    pub required_approvals: u32,
+    pub proposal_ttl_seconds: u64,
+}
+
+impl Default for MultisigPolicy {
+    fn default() -> Self {
+        Self {
+            required_approvals: 2,
+            proposal_ttl_seconds: 3 * 24 * 60 * 60, // 3 days
+        }
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq)]
+pub enum ProposalStatus {
+    Open,
+    Executing, // Set before the first await so the operation cannot run twice
+    Executed,
+    Failed, // Mints and ckBTC withdrawals can be executed again
+    Expired,
+    Cancelled,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct TreasuryProposal {
+    pub id: u64,
+    pub operation: TreasuryOperation,
+    pub proposer: Principal,
+    pub approvals: Vec<Principal>, // The proposer approves implicitly
+    pub required_approvals: u32,   // Policy at creation time
+    pub status: ProposalStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub executed_by: Option<Principal>,
+    pub executed_at: Option<u64>,
+    pub result: Option<String>, // Block index, transaction hash or error of the execution
+    pub updated_at: u64,
+    pub execution_created_at: Option<u64>, // Ledger created_at_time (ns) of a mint or withdrawal, reused on retries
+}
+
+impl TreasuryProposal {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.status == ProposalStatus::Open && now >= self.expires_at
+    }
+}
@@ -48,6 +48,19 @@ pub fn get_current_timestamp() -> u64 {
     time() / 1_000_000_000
 }
 
+/// Converts a ledger `Nat` (block index or token amount) into a u64, rejecting values that don't fit
+pub fn nat_to_u64(value: &candid::Nat) -> Result<u64> {
+    let digits = value.0.to_u64_digits();
+    match digits.len() {
+        0 => Ok(0),
+        1 => Ok(digits[0]),
+        _ => Err(BitcoinUSTBillsError::validation_error(format!(
+            "Value {} does not fit into u64",
+            value
+        ))),
+    }
+}
+
 // All unused utility functions removed - not needed for current KYC/registration implementation
 
 // Tests removed - only keeping essential functions for current implementation
@@ -2,11 +2,64 @@ import type { Principal } from '@dfinity/principal';
 import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 
+export interface Account {
+  'owner' : Principal,
+  'subaccount' : [] | [Uint8Array | number[]],
+}
+export type AuditAction = { 'RoleRevoked' : null } |
+  { 'ProposalApproved' : null } |
+  { 'DepositRetryForced' : null } |
+  { 'PriceOracleConfigUpdated' : null } |
+  { 'ProposalExecuted' : null } |
+  { 'RedemptionRefunded' : null } |
+  { 'MultisigPolicyUpdated' : null } |
+  { 'RedemptionPayoutRetried' : null } |
+  { 'ProposalCreated' : null } |
+  { 'RoleGranted' : null } |
+  { 'CkbtcWithdrawn' : null } |
+  { 'KycApproved' : null } |
+  { 'OusgMinted' : null } |
+  { 'EvmNetworkUpdated' : null } |
+  { 'GasPolicyUpdated' : null } |
+  { 'KycRejected' : null } |
+  { 'TreasuryErc20Transfer' : null } |
+  { 'CanisterIdsUpdated' : null } |
+  { 'ProposalCancelled' : null } |
+  { 'DepositRefunded' : null } |
+  { 'EvmConfigUpdated' : null };
+export interface AuditEntry {
+  'id' : bigint,
+  'action' : AuditAction,
+  'actor' : Principal,
+  'after' : [] | [string],
+  'target' : string,
+  'before' : [] | [string],
+  'timestamp' : bigint,
+}
+export interface AuditLogFilter {
+  'from_timestamp' : [] | [bigint],
+  'action' : [] | [AuditAction],
+  'actor' : [] | [Principal],
+  'target' : [] | [string],
+  'to_timestamp' : [] | [bigint],
+}
+export interface AuditLogPage {
+  'entries' : Array<AuditEntry>,
+  'next_before_id' : [] | [bigint],
+}
+export interface BackendInitArgs {
+  'evm' : [] | [EvmNetworkArgs],
+  'initial_admins' : [] | [Array<Principal>],
+  'canister_ids' : [] | [CanisterIdsArgs],
+  'price_oracle' : [] | [PriceOracleConfig],
+}
 export type BitcoinUSTBillsError = { 'UserAlreadyExists' : null } |
   { 'AccessDenied' : null } |
+  { 'PriceUnavailable' : string } |
   { 'InvalidPrincipal' : null } |
   { 'FileStoreBucketError' : string } |
   { 'ValidationError' : string } |
+  { 'LedgerError' : string } |
   { 'Unauthorized' : null } |
   { 'KYCExpired' : null } |
   { 'KYCNotVerified' : null } |
@@ -14,21 +67,56 @@ export type BitcoinUSTBillsError = { 'UserAlreadyExists' : null } |
   { 'StorageError' : string } |
   { 'UserNotFound' : null } |
   { 'AnonymousCaller' : null };
+export interface CachedRate {
+  'rate' : bigint,
+  'rate_timestamp' : bigint,
+  'fetched_at' : bigint,
+}
+export interface CanisterIds {
+  'xrc' : Principal,
+  'file_store_bucket' : Principal,
+  'ousg_ledger' : Principal,
+  'evm_rpc' : Principal,
+  'ckbtc_ledger' : Principal,
+}
+export interface CanisterIdsArgs {
+  'xrc' : [] | [Principal],
+  'file_store_bucket' : [] | [Principal],
+  'ousg_ledger' : [] | [Principal],
+  'evm_rpc' : [] | [Principal],
+  'ckbtc_ledger' : [] | [Principal],
+}
+export type ConsensusStrategy = { 'Equality' : null } |
+  { 'Threshold' : { 'min' : number, 'total' : [] | [number] } };
 export interface Deposit {
   'id' : bigint,
+  'last_error' : [] | [string],
   'status' : DepositStatus,
+  'sweep_created_at' : [] | [bigint],
   'user_principal' : Principal,
+  'sweep_block_index' : [] | [bigint],
   'updated_at' : bigint,
+  'refund_created_at' : [] | [bigint],
   'block_index' : bigint,
   'deposit_time' : bigint,
+  'mint_created_at' : [] | [bigint],
   'ousg_minted' : bigint,
+  'subaccount' : [] | [Uint8Array | number[]],
+  'sender_subaccount' : [] | [Uint8Array | number[]],
   'created_at' : bigint,
-  'btc_price_usd' : number,
-  'usd_value' : number,
+  'sender' : [] | [Principal],
+  'btc_price_usd' : bigint,
+  'usd_value' : bigint,
+  'btc_price_timestamp' : [] | [bigint],
+  'quote_id' : [] | [string],
+  'mint_block_index' : [] | [bigint],
+  'locked_price' : [] | [PriceObservation],
+  'refund_block_index' : [] | [bigint],
   'ckbtc_amount' : bigint,
 }
 export interface DepositRequest {
   'block_index' : bigint,
+  'quote_id' : [] | [string],
   'ckbtc_amount' : bigint,
 }
 export interface DepositResponse {
@@ -37,10 +125,74 @@ export interface DepositResponse {
   'error_message' : [] | [string],
   'success' : boolean,
 }
-export type DepositStatus = { 'Failed' : null } |
+export interface DepositRetryState {
+  'deposit_id' : bigint,
+  'next_attempt_at' : bigint,
+  'attempts' : number,
+  'last_attempt_at' : [] | [bigint],
+}
+export interface DepositScanState {
+  'last_scan_at' : [] | [bigint],
+  'next_block' : [] | [bigint],
+  'deposits_found' : bigint,
+}
+export type DepositStatus = { 'ManualReview' : null } |
+  { 'Failed' : null } |
+  { 'Minting' : null } |
+  { 'Reserved' : null } |
+  { 'Refunded' : null } |
   { 'Processed' : null } |
   { 'Validated' : null } |
   { 'Pending' : null };
+export type EthMainnetService = { 'Alchemy' : null } |
+  { 'Llama' : null } |
+  { 'BlockPi' : null } |
+  { 'Cloudflare' : null } |
+  { 'PublicNode' : null } |
+  { 'Ankr' : null };
+export type EthSepoliaService = { 'Alchemy' : null } |
+  { 'BlockPi' : null } |
+  { 'PublicNode' : null } |
+  { 'Ankr' : null } |
+  { 'Sepolia' : null };
+export interface EvmConfig {
+  'ecdsa_key_name' : string,
+  'rpc_services' : RpcServices,
+  'response_consensus' : [] | [ConsensusStrategy],
+  'gas_policy' : GasPolicy,
+  'required_confirmations' : bigint,
+  'chain_id' : bigint,
+}
+export interface EvmNetworkArgs {
+  'ecdsa_key_name' : [] | [string],
+  'rpc_services' : [] | [RpcServices],
+  'response_consensus' : [] | [ConsensusStrategy],
+  'chain_id' : [] | [bigint],
+}
+export interface EvmTransaction {
+  'id' : bigint,
+  'confirmations' : bigint,
+  'effective_gas_price' : [] | [string],
+  'status' : EvmTransactionStatus,
+  'updated_at' : bigint,
+  'transaction_hash' : string,
+  'max_priority_fee_per_gas' : string,
+  'recipient' : string,
+  'max_fee_per_gas' : string,
+  'requested_by' : Principal,
+  'block_number' : [] | [bigint],
+  'from_address' : string,
+  'nonce' : bigint,
+  'gas_limit' : string,
+  'gas_used' : [] | [string],
+  'contract_address' : string,
+  'amount' : string,
+  'submitted_at' : bigint,
+}
+export type EvmTransactionStatus = { 'Reverted' : null } |
+  { 'Confirmed' : null } |
+  { 'Dropped' : null } |
+  { 'Pending' : null };
 export interface FreeKYCSession {
   'status' : FreeKYCStatus,
   'document_front_page' : string,
@@ -58,36 +210,186 @@ export type FreeKYCStatus = { 'PendingReview' : null } |
   { 'AutoApproved' : null } |
   { 'Processing' : null } |
   { 'Expired' : null };
+export interface GasPolicy {
+  'gas_limit_cap' : bigint,
+  'max_priority_fee_per_gas_cap' : bigint,
+  'min_priority_fee_per_gas' : bigint,
+  'priority_fee_percentile' : number,
+  'gas_limit_buffer_percent' : bigint,
+  'base_fee_multiplier_percent' : bigint,
+  'max_fee_per_gas_cap' : bigint,
+  'fee_history_blocks' : bigint,
+}
+export interface HttpHeader { 'value' : string, 'name' : string }
 export type KYCStatus = { 'Rejected' : null } |
   { 'Verified' : null } |
   { 'Expired' : null } |
   { 'Pending' : null };
+export type L2MainnetService = { 'Alchemy' : null } |
+  { 'Llama' : null } |
+  { 'BlockPi' : null } |
+  { 'PublicNode' : null } |
+  { 'Ankr' : null };
+export interface MultisigPolicy {
+  'proposal_ttl_seconds' : bigint,
+  'required_approvals' : number,
+}
+export interface PriceObservation {
+  'source' : RateSource,
+  'rate' : bigint,
+  'timestamp' : bigint,
+}
+export interface PriceOracleArgs {
+  'min_base_sources' : [] | [bigint],
+  'max_rate_age_seconds' : [] | [bigint],
+  'twap_window_seconds' : [] | [[] | [bigint]],
+  'max_std_dev_bps' : [] | [bigint],
+  'min_received_percent' : [] | [bigint],
+}
+export interface PriceOracleConfig {
+  'min_base_sources' : bigint,
+  'max_rate_age_seconds' : bigint,
+  'test_mode' : boolean,
+  'test_rate' : [] | [bigint],
+  'twap_window_seconds' : [] | [bigint],
+  'max_std_dev_bps' : bigint,
+  'min_received_percent' : bigint,
+}
+export interface PriceOracleState {
+  'xrc_error_counts' : Array<[string, bigint]>,
+  'sample_count' : bigint,
+  'last_good_rate' : [] | [CachedRate],
+  'config' : PriceOracleConfig,
+  'latest_sample' : [] | [PriceSample],
+}
+export interface PriceSample {
+  'rate' : bigint,
+  'timestamp' : bigint,
+  'fetched_at' : bigint,
+}
+export type ProposalStatus = { 'Failed' : null } |
+  { 'Open' : null } |
+  { 'Executing' : null } |
+  { 'Executed' : null } |
+  { 'Cancelled' : null } |
+  { 'Expired' : null };
 export interface PublicKeyReply {
   'eth_address' : string,
   'public_key_hex' : string,
 }
-export type Result = { 'Ok' : Array<UserAndFreeKYCSession> } |
+export interface Quote {
+  'id' : string,
+  'user_principal' : Principal,
+  'spread_bps' : bigint,
+  'mid_price_usd' : bigint,
+  'side' : QuoteSide,
+  'used_at' : [] | [bigint],
+  'created_at' : bigint,
+  'btc_price_usd' : bigint,
+  'usd_value' : bigint,
+  'price_timestamp' : bigint,
+  'ousg_amount' : bigint,
+  'expires_at' : bigint,
+  'ckbtc_amount' : bigint,
+}
+export type QuoteSide = { 'Deposit' : null } |
+  { 'Redemption' : null };
+export type RateSource = { 'Xrc' : null } |
+  { 'Twap' : null } |
+  { 'TestFallback' : null } |
+  { 'Cache' : null };
+export interface Redemption {
+  'id' : bigint,
+  'last_error' : [] | [string],
+  'status' : RedemptionStatus,
+  'payout_attempts' : number,
+  'user_principal' : Principal,
+  'updated_at' : bigint,
+  'refund_created_at' : [] | [bigint],
+  'created_at' : bigint,
+  'btc_price_usd' : bigint,
+  'payout_created_at' : bigint,
+  'usd_value' : bigint,
+  'payout_block_index' : [] | [bigint],
+  'burn_block_index' : [] | [bigint],
+  'quote_id' : [] | [string],
+  'refund_block_index' : [] | [bigint],
+  'ousg_amount' : bigint,
+  'ckbtc_amount' : bigint,
+}
+export type RedemptionStatus = { 'Burned' : null } |
+  { 'Failed' : null } |
+  { 'Refunded' : null } |
+  { 'PaidOut' : null } |
+  { 'Requested' : null };
+export type Result = { 'Ok' : Deposit } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_1 = { 'Ok' : AuditEntry } |
   { 'Err' : BitcoinUSTBillsError };
-export type Result_1 = { 'Ok' : null } |
+export type Result_10 = { 'Ok' : Array<RoleAssignment> } |
   { 'Err' : BitcoinUSTBillsError };
-export type Result_2 = { 'Ok' : bigint } |
+export type Result_11 = { 'Ok' : null } |
   { 'Err' : BitcoinUSTBillsError };
-export type Result_3 = { 'Ok' : number } |
+export type Result_12 = { 'Ok' : Redemption } |
   { 'Err' : BitcoinUSTBillsError };
-export type Result_4 = { 'Ok' : Deposit } |
+export type Result_13 = { 'Ok' : bigint } |
   { 'Err' : BitcoinUSTBillsError };
-export type Result_5 = { 'Ok' : PublicKeyReply } |
+export type Result_14 = { 'Ok' : TreasuryProposal } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_15 = { 'Ok' : string } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_16 = { 'Ok' : PriceObservation } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_17 = { 'Ok' : PublicKeyReply } |
   { 'Err' : string };
-export type Result_6 = { 'Ok' : FreeKYCSession } |
+export type Result_18 = { 'Ok' : EvmTransaction } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_19 = { 'Ok' : FreeKYCSession } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_2 = { 'Ok' : AuditLogPage } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_20 = { 'Ok' : Account } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_21 = { 'Ok' : Quote } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_22 = { 'Ok' : Array<TreasuryProposal> } |
   { 'Err' : BitcoinUSTBillsError };
-export type Result_7 = { 'Ok' : Array<Deposit> } |
+export type Result_23 = { 'Ok' : User } |
   { 'Err' : BitcoinUSTBillsError };
-export type Result_8 = { 'Ok' : User } |
+export type Result_3 = { 'Ok' : Array<DepositRetryState> } |
   { 'Err' : BitcoinUSTBillsError };
-export type Result_9 = { 'Ok' : string } |
+export type Result_4 = { 'Ok' : DepositScanState } |
   { 'Err' : BitcoinUSTBillsError };
+export type Result_5 = { 'Ok' : Array<Deposit> } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_6 = { 'Ok' : Array<EvmTransaction> } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_7 = { 'Ok' : Array<Redemption> } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_8 = { 'Ok' : Array<UserAndFreeKYCSession> } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Result_9 = { 'Ok' : PriceOracleState } |
+  { 'Err' : BitcoinUSTBillsError };
+export type Role = { 'Auditor' : null } |
+  { 'SuperAdmin' : null } |
+  { 'TreasuryOperator' : null } |
+  { 'KycReviewer' : null };
+export interface RoleAssignment {
+  'updated_at' : bigint,
+  'principal' : Principal,
+  'granted_at' : bigint,
+  'roles' : Array<Role>,
+}
+export interface RpcApi { 'url' : string, 'headers' : [] | [Array<HttpHeader>] }
+export type RpcServices = { 'EthSepolia' : [] | [Array<EthSepoliaService>] } |
+  { 'BaseMainnet' : [] | [Array<L2MainnetService>] } |
+  { 'Custom' : { 'chainId' : bigint, 'services' : Array<RpcApi> } } |
+  { 'OptimismMainnet' : [] | [Array<L2MainnetService>] } |
+  { 'ArbitrumOne' : [] | [Array<L2MainnetService>] } |
+  { 'EthMainnet' : [] | [Array<EthMainnetService>] };
 export interface TransferRequest {
   'recipient' : string,
+  'from_caller_address' : [] | [boolean],
   'contract_address' : string,
   'amount' : string,
 }
@@ -95,6 +397,39 @@ export interface TransferResponse {
   'transaction_hash' : [] | [string],
   'error_message' : [] | [string],
   'success' : boolean,
+  'evm_transaction_id' : [] | [bigint],
+}
+export type TreasuryOperation = { 'SetMultisigPolicy' : MultisigPolicy } |
+  { 'SetEvmConfig' : EvmConfig } |
+  { 'MintOusg' : { 'recipient' : Principal, 'ousg_amount' : bigint } } |
+  { 'WithdrawCkbtc' : { 'recipient' : Principal, 'ckbtc_amount' : bigint } } |
+  { 'SetGasPolicy' : GasPolicy } |
+  { 'RevokeRole' : { 'principal' : Principal, 'role' : Role } } |
+  { 'SetEvmNetwork' : EvmNetworkArgs } |
+  { 'SetCanisterIds' : CanisterIdsArgs } |
+  { 'SetPriceOracleConfig' : PriceOracleArgs } |
+  { 'GrantRole' : { 'principal' : Principal, 'role' : Role } } |
+  {
+    'Erc20Transfer' : {
+      'recipient' : string,
+      'contract_address' : string,
+      'amount' : string,
+    }
+  };
+export interface TreasuryProposal {
+  'id' : bigint,
+  'status' : ProposalStatus,
+  'result' : [] | [string],
+  'updated_at' : bigint,
+  'executed_at' : [] | [bigint],
+  'executed_by' : [] | [Principal],
+  'required_approvals' : number,
+  'created_at' : bigint,
+  'operation' : TreasuryOperation,
+  'proposer' : Principal,
+  'execution_created_at' : [] | [bigint],
+  'expires_at' : bigint,
+  'approvals' : Array<Principal>,
 }
 export interface User {
   'updated_at' : bigint,
@@ -126,31 +461,77 @@ export interface UserRegistrationRequest {
   'phone_number' : [] | [string],
 }
 export interface _SERVICE {
-  'admin_get_pending_reviews' : ActorMethod<[], Result>,
+  'admin_force_retry_deposit' : ActorMethod<[bigint], Result>,
+  'admin_get_audit_entry' : ActorMethod<[bigint], Result_1>,
+  'admin_get_audit_log' : ActorMethod<
+    [[] | [AuditLogFilter], [] | [bigint], bigint],
+    Result_2
+  >,
+  'admin_get_deposit_retry_states' : ActorMethod<[], Result_3>,
+  'admin_get_deposit_scan_state' : ActorMethod<[], Result_4>,
+  'admin_get_deposits_for_review' : ActorMethod<[], Result_5>,
+  'admin_get_evm_transactions' : ActorMethod<
+    [[] | [EvmTransactionStatus]],
+    Result_6
+  >,
+  'admin_get_failed_redemptions' : ActorMethod<[], Result_7>,
+  'admin_get_pending_reviews' : ActorMethod<[], Result_8>,
+  'admin_get_price_oracle_state' : ActorMethod<[], Result_9>,
+  'admin_get_role_assignments' : ActorMethod<[], Result_10>,
+  'admin_grant_role' : ActorMethod<[Principal, Role], Result_11>,
+  'admin_refund_deposit' : ActorMethod<[bigint], Result>,
+  'admin_refund_redemption' : ActorMethod<[bigint], Result_12>,
+  'admin_retry_redemption_payout' : ActorMethod<[bigint], Result_12>,
   'admin_review_free_kyc' : ActorMethod<
     [string, boolean, [] | [string]],
-    Result_1
+    Result_11
   >,
-  'approve_ousg_for_redemption' : ActorMethod<[bigint], Result_2>,
-  'calculate_ckbtc_usd_value' : ActorMethod<[bigint, number], number>,
-  'calculate_ousg_for_usd' : ActorMethod<[number], bigint>,
+  'admin_revoke_role' : ActorMethod<[Principal, Role], Result_11>,
+  'approve_ousg_for_redemption' : ActorMethod<[bigint], Result_13>,
+  'approve_treasury_proposal' : ActorMethod<[bigint], Result_14>,
+  'calculate_ckbtc_usd_value' : ActorMethod<[bigint, bigint], Result_13>,
+  'calculate_ousg_for_usd' : ActorMethod<[bigint], Result_13>,
+  'cancel_treasury_proposal' : ActorMethod<[bigint], Result_14>,
+  'deposit_with_allowance' : ActorMethod<[bigint], DepositResponse>,
+  'derive_my_eth_address' : ActorMethod<[], Result_15>,
+  'execute_treasury_proposal' : ActorMethod<[bigint], Result_14>,
   'get_authorized_principals' : ActorMethod<[], Array<Principal>>,
-  'get_current_btc_price' : ActorMethod<[], Result_3>,
-  'get_deposit' : ActorMethod<[bigint], Result_4>,
+  'get_btc_price_history' : ActorMethod<
+    [bigint, bigint, bigint],
+    Array<PriceSample>
+  >,
+  'get_btc_twap' : ActorMethod<[bigint], Result_16>,
+  'get_canister_ids' : ActorMethod<[], CanisterIds>,
+  'get_current_btc_price' : ActorMethod<[], Result_13>,
+  'get_deposit' : ActorMethod<[bigint], Result>,
   'get_deposit_stats' : ActorMethod<[], Array<[string, bigint]>>,
-  'get_eth_address' : ActorMethod<[], Result_5>,
-  'get_free_kyc_status' : ActorMethod<[string], Result_6>,
+  'get_eth_address' : ActorMethod<[], Result_17>,
+  'get_evm_config' : ActorMethod<[], EvmConfig>,
+  'get_evm_transaction' : ActorMethod<[bigint], Result_18>,
+  'get_free_kyc_status' : ActorMethod<[string], Result_19>,
   'get_latest_block_number' : ActorMethod<[], string>,
-  'get_ousg_balance' : ActorMethod<[], Result_2>,
-  'get_user_deposits' : ActorMethod<[], Result_7>,
-  'get_user_profile' : ActorMethod<[], Result_8>,
+  'get_multisig_policy' : ActorMethod<[], MultisigPolicy>,
+  'get_my_deposit_account' : ActorMethod<[], Result_20>,
+  'get_my_eth_address' : ActorMethod<[], Result_15>,
+  'get_my_evm_transactions' : ActorMethod<[], Array<EvmTransaction>>,
+  'get_my_roles' : ActorMethod<[], Array<Role>>,
+  'get_ousg_balance' : ActorMethod<[], Result_13>,
+  'get_quote' : ActorMethod<[string], Result_21>,
+  'get_redemption' : ActorMethod<[bigint], Result_12>,
+  'get_treasury_proposal' : ActorMethod<[bigint], Result_14>,
+  'get_treasury_proposals' : ActorMethod<[[] | [ProposalStatus]], Result_22>,
+  'get_user_deposits' : ActorMethod<[], Result_5>,
+  'get_user_profile' : ActorMethod<[], Result_23>,
+  'get_user_redemptions' : ActorMethod<[], Result_7>,
   'is_user_registered' : ActorMethod<[], boolean>,
   'notify_deposit' : ActorMethod<[DepositRequest], DepositResponse>,
-  'redeem_ousg_tokens' : ActorMethod<[bigint], Result_2>,
-  'register_user' : ActorMethod<[UserRegistrationRequest], Result_8>,
+  'propose_treasury_operation' : ActorMethod<[TreasuryOperation], Result_14>,
+  'redeem_ousg_tokens' : ActorMethod<[bigint, [] | [string]], Result_13>,
+  'register_user' : ActorMethod<[UserRegistrationRequest], Result_23>,
+  'request_quote' : ActorMethod<[QuoteSide, bigint], Result_21>,
   'test_erc20_transfer' : ActorMethod<[], TransferResponse>,
   'transfer_erc20_tokens' : ActorMethod<[TransferRequest], TransferResponse>,
-  'upload_document_free_kyc' : ActorMethod<[string, string, string], Result_9>,
+  'upload_document_free_kyc' : ActorMethod<[string, string, string], Result_15>,
 }
 export declare const idlFactory: IDL.InterfaceFactory;
 export declare const init: (args: { IDL: typeof IDL }) => IDL.Type[];
@@ -1,4 +1,272 @@
 export const idlFactory = ({ IDL }) => {
+  const EthSepoliaService = IDL.Variant({
+    'Alchemy' : IDL.Null,
+    'BlockPi' : IDL.Null,
+    'PublicNode' : IDL.Null,
+    'Ankr' : IDL.Null,
+    'Sepolia' : IDL.Null,
+  });
+  const L2MainnetService = IDL.Variant({
+    'Alchemy' : IDL.Null,
+    'Llama' : IDL.Null,
+    'BlockPi' : IDL.Null,
+    'PublicNode' : IDL.Null,
+    'Ankr' : IDL.Null,
+  });
+  const HttpHeader = IDL.Record({ 'value' : IDL.Text, 'name' : IDL.Text });
+  const RpcApi = IDL.Record({
+    'url' : IDL.Text,
+    'headers' : IDL.Opt(IDL.Vec(HttpHeader)),
+  });
+  const EthMainnetService = IDL.Variant({
+    'Alchemy' : IDL.Null,
+    'Llama' : IDL.Null,
+    'BlockPi' : IDL.Null,
+    'Cloudflare' : IDL.Null,
+    'PublicNode' : IDL.Null,
+    'Ankr' : IDL.Null,
+  });
+  const RpcServices = IDL.Variant({
+    'EthSepolia' : IDL.Opt(IDL.Vec(EthSepoliaService)),
+    'BaseMainnet' : IDL.Opt(IDL.Vec(L2MainnetService)),
+    'Custom' : IDL.Record({
+      'chainId' : IDL.Nat64,
+      'services' : IDL.Vec(RpcApi),
+    }),
+    'OptimismMainnet' : IDL.Opt(IDL.Vec(L2MainnetService)),
+    'ArbitrumOne' : IDL.Opt(IDL.Vec(L2MainnetService)),
+    'EthMainnet' : IDL.Opt(IDL.Vec(EthMainnetService)),
+  });
+  const ConsensusStrategy = IDL.Variant({
+    'Equality' : IDL.Null,
+    'Threshold' : IDL.Record({ 'min' : IDL.Nat8, 'total' : IDL.Opt(IDL.Nat8) }),
+  });
+  const EvmNetworkArgs = IDL.Record({
+    'ecdsa_key_name' : IDL.Opt(IDL.Text),
+    'rpc_services' : IDL.Opt(RpcServices),
+    'response_consensus' : IDL.Opt(ConsensusStrategy),
+    'chain_id' : IDL.Opt(IDL.Nat64),
+  });
+  const CanisterIdsArgs = IDL.Record({
+    'xrc' : IDL.Opt(IDL.Principal),
+    'file_store_bucket' : IDL.Opt(IDL.Principal),
+    'ousg_ledger' : IDL.Opt(IDL.Principal),
+    'evm_rpc' : IDL.Opt(IDL.Principal),
+    'ckbtc_ledger' : IDL.Opt(IDL.Principal),
+  });
+  const PriceOracleConfig = IDL.Record({
+    'min_base_sources' : IDL.Nat64,
+    'max_rate_age_seconds' : IDL.Nat64,
+    'test_mode' : IDL.Bool,
+    'test_rate' : IDL.Opt(IDL.Nat64),
+    'twap_window_seconds' : IDL.Opt(IDL.Nat64),
+    'max_std_dev_bps' : IDL.Nat64,
+    'min_received_percent' : IDL.Nat64,
+  });
+  const BackendInitArgs = IDL.Record({
+    'evm' : IDL.Opt(EvmNetworkArgs),
+    'initial_admins' : IDL.Opt(IDL.Vec(IDL.Principal)),
+    'canister_ids' : IDL.Opt(CanisterIdsArgs),
+    'price_oracle' : IDL.Opt(PriceOracleConfig),
+  });
+  const DepositStatus = IDL.Variant({
+    'ManualReview' : IDL.Null,
+    'Failed' : IDL.Null,
+    'Minting' : IDL.Null,
+    'Reserved' : IDL.Null,
+    'Refunded' : IDL.Null,
+    'Processed' : IDL.Null,
+    'Validated' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const RateSource = IDL.Variant({
+    'Xrc' : IDL.Null,
+    'Twap' : IDL.Null,
+    'TestFallback' : IDL.Null,
+    'Cache' : IDL.Null,
+  });
+  const PriceObservation = IDL.Record({
+    'source' : RateSource,
+    'rate' : IDL.Nat64,
+    'timestamp' : IDL.Nat64,
+  });
+  const Deposit = IDL.Record({
+    'id' : IDL.Nat64,
+    'last_error' : IDL.Opt(IDL.Text),
+    'status' : DepositStatus,
+    'sweep_created_at' : IDL.Opt(IDL.Nat64),
+    'user_principal' : IDL.Principal,
+    'sweep_block_index' : IDL.Opt(IDL.Nat64),
+    'updated_at' : IDL.Nat64,
+    'refund_created_at' : IDL.Opt(IDL.Nat64),
+    'block_index' : IDL.Nat64,
+    'deposit_time' : IDL.Nat64,
+    'mint_created_at' : IDL.Opt(IDL.Nat64),
+    'ousg_minted' : IDL.Nat64,
+    'subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'sender_subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'created_at' : IDL.Nat64,
+    'sender' : IDL.Opt(IDL.Principal),
+    'btc_price_usd' : IDL.Nat64,
+    'usd_value' : IDL.Nat64,
+    'btc_price_timestamp' : IDL.Opt(IDL.Nat64),
+    'quote_id' : IDL.Opt(IDL.Text),
+    'mint_block_index' : IDL.Opt(IDL.Nat64),
+    'locked_price' : IDL.Opt(PriceObservation),
+    'refund_block_index' : IDL.Opt(IDL.Nat64),
+    'ckbtc_amount' : IDL.Nat64,
+  });
+  const BitcoinUSTBillsError = IDL.Variant({
+    'UserAlreadyExists' : IDL.Null,
+    'AccessDenied' : IDL.Null,
+    'PriceUnavailable' : IDL.Text,
+    'InvalidPrincipal' : IDL.Null,
+    'FileStoreBucketError' : IDL.Text,
+    'ValidationError' : IDL.Text,
+    'LedgerError' : IDL.Text,
+    'Unauthorized' : IDL.Null,
+    'KYCExpired' : IDL.Null,
+    'KYCNotVerified' : IDL.Null,
+    'InvalidUserData' : IDL.Null,
+    'StorageError' : IDL.Text,
+    'UserNotFound' : IDL.Null,
+    'AnonymousCaller' : IDL.Null,
+  });
+  const Result = IDL.Variant({ 'Ok' : Deposit, 'Err' : BitcoinUSTBillsError });
+  const AuditAction = IDL.Variant({
+    'RoleRevoked' : IDL.Null,
+    'ProposalApproved' : IDL.Null,
+    'DepositRetryForced' : IDL.Null,
+    'PriceOracleConfigUpdated' : IDL.Null,
+    'ProposalExecuted' : IDL.Null,
+    'RedemptionRefunded' : IDL.Null,
+    'MultisigPolicyUpdated' : IDL.Null,
+    'RedemptionPayoutRetried' : IDL.Null,
+    'ProposalCreated' : IDL.Null,
+    'RoleGranted' : IDL.Null,
+    'CkbtcWithdrawn' : IDL.Null,
+    'KycApproved' : IDL.Null,
+    'OusgMinted' : IDL.Null,
+    'EvmNetworkUpdated' : IDL.Null,
+    'GasPolicyUpdated' : IDL.Null,
+    'KycRejected' : IDL.Null,
+    'TreasuryErc20Transfer' : IDL.Null,
+    'CanisterIdsUpdated' : IDL.Null,
+    'ProposalCancelled' : IDL.Null,
+    'DepositRefunded' : IDL.Null,
+    'EvmConfigUpdated' : IDL.Null,
+  });
+  const AuditEntry = IDL.Record({
+    'id' : IDL.Nat64,
+    'action' : AuditAction,
+    'actor' : IDL.Principal,
+    'after' : IDL.Opt(IDL.Text),
+    'target' : IDL.Text,
+    'before' : IDL.Opt(IDL.Text),
+    'timestamp' : IDL.Nat64,
+  });
+  const Result_1 = IDL.Variant({
+    'Ok' : AuditEntry,
+    'Err' : BitcoinUSTBillsError,
+  });
+  const AuditLogFilter = IDL.Record({
+    'from_timestamp' : IDL.Opt(IDL.Nat64),
+    'action' : IDL.Opt(AuditAction),
+    'actor' : IDL.Opt(IDL.Principal),
+    'target' : IDL.Opt(IDL.Text),
+    'to_timestamp' : IDL.Opt(IDL.Nat64),
+  });
+  const AuditLogPage = IDL.Record({
+    'entries' : IDL.Vec(AuditEntry),
+    'next_before_id' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_2 = IDL.Variant({
+    'Ok' : AuditLogPage,
+    'Err' : BitcoinUSTBillsError,
+  });
+  const DepositRetryState = IDL.Record({
+    'deposit_id' : IDL.Nat64,
+    'next_attempt_at' : IDL.Nat64,
+    'attempts' : IDL.Nat32,
+    'last_attempt_at' : IDL.Opt(IDL.Nat64),
+  });
+  const Result_3 = IDL.Variant({
+    'Ok' : IDL.Vec(DepositRetryState),
+    'Err' : BitcoinUSTBillsError,
+  });
+  const DepositScanState = IDL.Record({
+    'last_scan_at' : IDL.Opt(IDL.Nat64),
+    'next_block' : IDL.Opt(IDL.Nat64),
+    'deposits_found' : IDL.Nat64,
+  });
+  const Result_4 = IDL.Variant({
+    'Ok' : DepositScanState,
+    'Err' : BitcoinUSTBillsError,
+  });
+  const Result_5 = IDL.Variant({
+    'Ok' : IDL.Vec(Deposit),
+    'Err' : BitcoinUSTBillsError,
+  });
+  const EvmTransactionStatus = IDL.Variant({
+    'Reverted' : IDL.Null,
+    'Confirmed' : IDL.Null,
+    'Dropped' : IDL.Null,
+    'Pending' : IDL.Null,
+  });
+  const EvmTransaction = IDL.Record({
+    'id' : IDL.Nat64,
+    'confirmations' : IDL.Nat64,
+    'effective_gas_price' : IDL.Opt(IDL.Text),
+    'status' : EvmTransactionStatus,
+    'updated_at' : IDL.Nat64,
+    'transaction_hash' : IDL.Text,
+    'max_priority_fee_per_gas' : IDL.Text,
+    'recipient' : IDL.Text,
+    'max_fee_per_gas' : IDL.Text,
+    'requested_by' : IDL.Principal,
+    'block_number' : IDL.Opt(IDL.Nat64),
+    'from_address' : IDL.Text,
+    'nonce' : IDL.Nat64,
+    'gas_limit' : IDL.Text,
+    'gas_used' : IDL.Opt(IDL.Text),
+    'contract_address' : IDL.Text,
+    'amount' : IDL.Text,
+    'submitted_at' : IDL.Nat64,
+  });
+  const Result_6 = IDL.Variant({
+    'Ok' : IDL.Vec(EvmTransaction),
+    'Err' : BitcoinUSTBillsError,
+  });
+  const RedemptionStatus = IDL.Variant({
+    'Burned' : IDL.Null,
+    'Failed' : IDL.Null,
+    'Refunded' : IDL.Null,
+    'PaidOut' : IDL.Null,
+    'Requested' : IDL.Null,
+  });
+  const Redemption = IDL.Record({
+    'id' : IDL.Nat64,
+    'last_error' : IDL.Opt(IDL.Text),
+    'status' : RedemptionStatus,
+    'payout_attempts' : IDL.Nat32,
+    'user_principal' : IDL.Principal,
+    'updated_at' : IDL.Nat64,
+    'refund_created_at' : IDL.Opt(IDL.Nat64),
+    'created_at' : IDL.Nat64,
+    'btc_price_usd' : IDL.Nat64,
+    'payout_created_at' : IDL.Nat64,
+    'usd_value' : IDL.Nat64,
+    'payout_block_index' : IDL.Opt(IDL.Nat64),
+    'burn_block_index' : IDL.Opt(IDL.Nat64),
+    'quote_id' : IDL.Opt(IDL.Text),
+    'refund_block_index' : IDL.Opt(IDL.Nat64),
+    'ousg_amount' : IDL.Nat64,
+    'ckbtc_amount' : IDL.Nat64,
+  });
+  const Result_7 = IDL.Variant({
+    'Ok' : IDL.Vec(Redemption),
+    'Err' : BitcoinUSTBillsError,
+  });
   const FreeKYCStatus = IDL.Variant({
     'PendingReview' : IDL.Null,
     'ManualApproved' : IDL.Null,
@@ -48,83 +316,210 @@ export const idlFactory = ({ IDL }) => {
     'kyc_session' : FreeKYCSession,
     'user' : User,
   });
-  const BitcoinUSTBillsError = IDL.Variant({
-    'UserAlreadyExists' : IDL.Null,
-    'AccessDenied' : IDL.Null,
-    'InvalidPrincipal' : IDL.Null,
-    'FileStoreBucketError' : IDL.Text,
-    'ValidationError' : IDL.Text,
-    'Unauthorized' : IDL.Null,
-    'KYCExpired' : IDL.Null,
-    'KYCNotVerified' : IDL.Null,
-    'InvalidUserData' : IDL.Null,
-    'StorageError' : IDL.Text,
-    'UserNotFound' : IDL.Null,
-    'AnonymousCaller' : IDL.Null,
-  });
-  const Result = IDL.Variant({
+  const Result_8 = IDL.Variant({
     'Ok' : IDL.Vec(UserAndFreeKYCSession),
     'Err' : BitcoinUSTBillsError,
   });
-  const Result_1 = IDL.Variant({
+  const CachedRate = IDL.Record({
+    'rate' : IDL.Nat64,
+    'rate_timestamp' : IDL.Nat64,
+    'fetched_at' : IDL.Nat64,
+  });
+  const PriceSample = IDL.Record({
+    'rate' : IDL.Nat64,
+    'timestamp' : IDL.Nat64,
+    'fetched_at' : IDL.Nat64,
+  });
+  const PriceOracleState = IDL.Record({
+    'xrc_error_counts' : IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64)),
+    'sample_count' : IDL.Nat64,
+    'last_good_rate' : IDL.Opt(CachedRate),
+    'config' : PriceOracleConfig,
+    'latest_sample' : IDL.Opt(PriceSample),
+  });
+  const Result_9 = IDL.Variant({
+    'Ok' : PriceOracleState,
+    'Err' : BitcoinUSTBillsError,
+  });
+  const Role = IDL.Variant({
+    'Auditor' : IDL.Null,
+    'SuperAdmin' : IDL.Null,
+    'TreasuryOperator' : IDL.Null,
+    'KycReviewer' : IDL.Null,
+  });
+  const RoleAssignment = IDL.Record({
+    'updated_at' : IDL.Nat64,
+    'principal' : IDL.Principal,
+    'granted_at' : IDL.Nat64,
+    'roles' : IDL.Vec(Role),
+  });
+  const Result_10 = IDL.Variant({
+    'Ok' : IDL.Vec(RoleAssignment),
+    'Err' : BitcoinUSTBillsError,
+  });
+  const Result_11 = IDL.Variant({
     'Ok' : IDL.Null,
     'Err' : BitcoinUSTBillsError,
   });
-  const Result_2 = IDL.Variant({
-    'Ok' : IDL.Nat64,
+  const Result_12 = IDL.Variant({
+    'Ok' : Redemption,
     'Err' : BitcoinUSTBillsError,
   });
-  const Result_3 = IDL.Variant({
-    'Ok' : IDL.Float64,
+  const Result_13 = IDL.Variant({
+    'Ok' : IDL.Nat64,
     'Err' : BitcoinUSTBillsError,
   });
-  const DepositStatus = IDL.Variant({
+  const ProposalStatus = IDL.Variant({
     'Failed' : IDL.Null,
-    'Processed' : IDL.Null,
-    'Validated' : IDL.Null,
-    'Pending' : IDL.Null,
+    'Open' : IDL.Null,
+    'Executing' : IDL.Null,
+    'Executed' : IDL.Null,
+    'Cancelled' : IDL.Null,
+    'Expired' : IDL.Null,
   });
-  const Deposit = IDL.Record({
+  const MultisigPolicy = IDL.Record({
+    'proposal_ttl_seconds' : IDL.Nat64,
+    'required_approvals' : IDL.Nat32,
+  });
+  const GasPolicy = IDL.Record({
+    'gas_limit_cap' : IDL.Nat64,
+    'max_priority_fee_per_gas_cap' : IDL.Nat64,
+    'min_priority_fee_per_gas' : IDL.Nat64,
+    'priority_fee_percentile' : IDL.Nat8,
+    'gas_limit_buffer_percent' : IDL.Nat64,
+    'base_fee_multiplier_percent' : IDL.Nat64,
+    'max_fee_per_gas_cap' : IDL.Nat64,
+    'fee_history_blocks' : IDL.Nat64,
+  });
+  const EvmConfig = IDL.Record({
+    'ecdsa_key_name' : IDL.Text,
+    'rpc_services' : RpcServices,
+    'response_consensus' : IDL.Opt(ConsensusStrategy),
+    'gas_policy' : GasPolicy,
+    'required_confirmations' : IDL.Nat64,
+    'chain_id' : IDL.Nat64,
+  });
+  const PriceOracleArgs = IDL.Record({
+    'min_base_sources' : IDL.Opt(IDL.Nat64),
+    'max_rate_age_seconds' : IDL.Opt(IDL.Nat64),
+    'twap_window_seconds' : IDL.Opt(IDL.Opt(IDL.Nat64)),
+    'max_std_dev_bps' : IDL.Opt(IDL.Nat64),
+    'min_received_percent' : IDL.Opt(IDL.Nat64),
+  });
+  const TreasuryOperation = IDL.Variant({
+    'SetMultisigPolicy' : MultisigPolicy,
+    'SetEvmConfig' : EvmConfig,
+    'MintOusg' : IDL.Record({
+      'recipient' : IDL.Principal,
+      'ousg_amount' : IDL.Nat64,
+    }),
+    'WithdrawCkbtc' : IDL.Record({
+      'recipient' : IDL.Principal,
+      'ckbtc_amount' : IDL.Nat64,
+    }),
+    'SetGasPolicy' : GasPolicy,
+    'RevokeRole' : IDL.Record({ 'principal' : IDL.Principal, 'role' : Role }),
+    'SetEvmNetwork' : EvmNetworkArgs,
+    'SetCanisterIds' : CanisterIdsArgs,
+    'SetPriceOracleConfig' : PriceOracleArgs,
+    'GrantRole' : IDL.Record({ 'principal' : IDL.Principal, 'role' : Role }),
+    'Erc20Transfer' : IDL.Record({
+      'recipient' : IDL.Text,
+      'contract_address' : IDL.Text,
+      'amount' : IDL.Text,
+    }),
+  });
+  const TreasuryProposal = IDL.Record({
     'id' : IDL.Nat64,
-    'status' : DepositStatus,
-    'user_principal' : IDL.Principal,
+    'status' : ProposalStatus,
+    'result' : IDL.Opt(IDL.Text),
     'updated_at' : IDL.Nat64,
-    'block_index' : IDL.Nat64,
-    'deposit_time' : IDL.Nat64,
-    'ousg_minted' : IDL.Nat64,
+    'executed_at' : IDL.Opt(IDL.Nat64),
+    'executed_by' : IDL.Opt(IDL.Principal),
+    'required_approvals' : IDL.Nat32,
     'created_at' : IDL.Nat64,
-    'btc_price_usd' : IDL.Float64,
-    'usd_value' : IDL.Float64,
-    'ckbtc_amount' : IDL.Nat64,
+    'operation' : TreasuryOperation,
+    'proposer' : IDL.Principal,
+    'execution_created_at' : IDL.Opt(IDL.Nat64),
+    'expires_at' : IDL.Nat64,
+    'approvals' : IDL.Vec(IDL.Principal),
   });
-  const Result_4 = IDL.Variant({
-    'Ok' : Deposit,
+  const Result_14 = IDL.Variant({
+    'Ok' : TreasuryProposal,
     'Err' : BitcoinUSTBillsError,
   });
+  const DepositResponse = IDL.Record({
+    'deposit_id' : IDL.Opt(IDL.Nat64),
+    'ousg_minted' : IDL.Opt(IDL.Nat64),
+    'error_message' : IDL.Opt(IDL.Text),
+    'success' : IDL.Bool,
+  });
+  const Result_15 = IDL.Variant({
+    'Ok' : IDL.Text,
+    'Err' : BitcoinUSTBillsError,
+  });
+  const Result_16 = IDL.Variant({
+    'Ok' : PriceObservation,
+    'Err' : BitcoinUSTBillsError,
+  });
+  const CanisterIds = IDL.Record({
+    'xrc' : IDL.Principal,
+    'file_store_bucket' : IDL.Principal,
+    'ousg_ledger' : IDL.Principal,
+    'evm_rpc' : IDL.Principal,
+    'ckbtc_ledger' : IDL.Principal,
+  });
   const PublicKeyReply = IDL.Record({
     'eth_address' : IDL.Text,
     'public_key_hex' : IDL.Text,
   });
-  const Result_5 = IDL.Variant({ 'Ok' : PublicKeyReply, 'Err' : IDL.Text });
-  const Result_6 = IDL.Variant({
+  const Result_17 = IDL.Variant({ 'Ok' : PublicKeyReply, 'Err' : IDL.Text });
+  const Result_18 = IDL.Variant({
+    'Ok' : EvmTransaction,
+    'Err' : BitcoinUSTBillsError,
+  });
+  const Result_19 = IDL.Variant({
     'Ok' : FreeKYCSession,
     'Err' : BitcoinUSTBillsError,
   });
-  const Result_7 = IDL.Variant({
-    'Ok' : IDL.Vec(Deposit),
+  const Account = IDL.Record({
+    'owner' : IDL.Principal,
+    'subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+  });
+  const Result_20 = IDL.Variant({
+    'Ok' : Account,
+    'Err' : BitcoinUSTBillsError,
+  });
+  const QuoteSide = IDL.Variant({
+    'Deposit' : IDL.Null,
+    'Redemption' : IDL.Null,
+  });
+  const Quote = IDL.Record({
+    'id' : IDL.Text,
+    'user_principal' : IDL.Principal,
+    'spread_bps' : IDL.Nat64,
+    'mid_price_usd' : IDL.Nat64,
+    'side' : QuoteSide,
+    'used_at' : IDL.Opt(IDL.Nat64),
+    'created_at' : IDL.Nat64,
+    'btc_price_usd' : IDL.Nat64,
+    'usd_value' : IDL.Nat64,
+    'price_timestamp' : IDL.Nat64,
+    'ousg_amount' : IDL.Nat64,
+    'expires_at' : IDL.Nat64,
+    'ckbtc_amount' : IDL.Nat64,
+  });
+  const Result_21 = IDL.Variant({ 'Ok' : Quote, 'Err' : BitcoinUSTBillsError });
+  const Result_22 = IDL.Variant({
+    'Ok' : IDL.Vec(TreasuryProposal),
     'Err' : BitcoinUSTBillsError,
   });
-  const Result_8 = IDL.Variant({ 'Ok' : User, 'Err' : BitcoinUSTBillsError });
+  const Result_23 = IDL.Variant({ 'Ok' : User, 'Err' : BitcoinUSTBillsError });
   const DepositRequest = IDL.Record({
     'block_index' : IDL.Nat64,
+    'quote_id' : IDL.Opt(IDL.Text),
     'ckbtc_amount' : IDL.Nat64,
   });
-  const DepositResponse = IDL.Record({
-    'deposit_id' : IDL.Opt(IDL.Nat64),
-    'ousg_minted' : IDL.Opt(IDL.Nat64),
-    'error_message' : IDL.Opt(IDL.Text),
-    'success' : IDL.Bool,
-  });
   const UserRegistrationRequest = IDL.Record({
     'country' : IDL.Text,
     'email' : IDL.Text,
@@ -134,52 +529,115 @@ export const idlFactory = ({ IDL }) => {
     'transaction_hash' : IDL.Opt(IDL.Text),
     'error_message' : IDL.Opt(IDL.Text),
     'success' : IDL.Bool,
+    'evm_transaction_id' : IDL.Opt(IDL.Nat64),
   });
   const TransferRequest = IDL.Record({
     'recipient' : IDL.Text,
+    'from_caller_address' : IDL.Opt(IDL.Bool),
     'contract_address' : IDL.Text,
     'amount' : IDL.Text,
   });
-  const Result_9 = IDL.Variant({
-    'Ok' : IDL.Text,
-    'Err' : BitcoinUSTBillsError,
-  });
   return IDL.Service({
-    'admin_get_pending_reviews' : IDL.Func([], [Result], ['query']),
+    'admin_force_retry_deposit' : IDL.Func([IDL.Nat64], [Result], []),
+    'admin_get_audit_entry' : IDL.Func([IDL.Nat64], [Result_1], ['query']),
+    'admin_get_audit_log' : IDL.Func(
+        [IDL.Opt(AuditLogFilter), IDL.Opt(IDL.Nat64), IDL.Nat64],
+        [Result_2],
+        ['query'],
+      ),
+    'admin_get_deposit_retry_states' : IDL.Func([], [Result_3], ['query']),
+    'admin_get_deposit_scan_state' : IDL.Func([], [Result_4], ['query']),
+    'admin_get_deposits_for_review' : IDL.Func([], [Result_5], ['query']),
+    'admin_get_evm_transactions' : IDL.Func(
+        [IDL.Opt(EvmTransactionStatus)],
+        [Result_6],
+        ['query'],
+      ),
+    'admin_get_failed_redemptions' : IDL.Func([], [Result_7], ['query']),
+    'admin_get_pending_reviews' : IDL.Func([], [Result_8], ['query']),
+    'admin_get_price_oracle_state' : IDL.Func([], [Result_9], ['query']),
+    'admin_get_role_assignments' : IDL.Func([], [Result_10], ['query']),
+    'admin_grant_role' : IDL.Func([IDL.Principal, Role], [Result_11], []),
+    'admin_refund_deposit' : IDL.Func([IDL.Nat64], [Result], []),
+    'admin_refund_redemption' : IDL.Func([IDL.Nat64], [Result_12], []),
+    'admin_retry_redemption_payout' : IDL.Func([IDL.Nat64], [Result_12], []),
     'admin_review_free_kyc' : IDL.Func(
         [IDL.Text, IDL.Bool, IDL.Opt(IDL.Text)],
-        [Result_1],
+        [Result_11],
         [],
       ),
-    'approve_ousg_for_redemption' : IDL.Func([IDL.Nat64], [Result_2], []),
+    'admin_revoke_role' : IDL.Func([IDL.Principal, Role], [Result_11], []),
+    'approve_ousg_for_redemption' : IDL.Func([IDL.Nat64], [Result_13], []),
+    'approve_treasury_proposal' : IDL.Func([IDL.Nat64], [Result_14], []),
     'calculate_ckbtc_usd_value' : IDL.Func(
-        [IDL.Nat64, IDL.Float64],
-        [IDL.Float64],
+        [IDL.Nat64, IDL.Nat64],
+        [Result_13],
         ['query'],
       ),
-    'calculate_ousg_for_usd' : IDL.Func([IDL.Float64], [IDL.Nat64], ['query']),
+    'calculate_ousg_for_usd' : IDL.Func([IDL.Nat64], [Result_13], ['query']),
+    'cancel_treasury_proposal' : IDL.Func([IDL.Nat64], [Result_14], []),
+    'deposit_with_allowance' : IDL.Func([IDL.Nat64], [DepositResponse], []),
+    'derive_my_eth_address' : IDL.Func([], [Result_15], []),
+    'execute_treasury_proposal' : IDL.Func([IDL.Nat64], [Result_14], []),
     'get_authorized_principals' : IDL.Func(
         [],
         [IDL.Vec(IDL.Principal)],
         ['query'],
       ),
-    'get_current_btc_price' : IDL.Func([], [Result_3], []),
-    'get_deposit' : IDL.Func([IDL.Nat64], [Result_4], ['query']),
+    'get_btc_price_history' : IDL.Func(
+        [IDL.Nat64, IDL.Nat64, IDL.Nat64],
+        [IDL.Vec(PriceSample)],
+        ['query'],
+      ),
+    'get_btc_twap' : IDL.Func([IDL.Nat64], [Result_16], ['query']),
+    'get_canister_ids' : IDL.Func([], [CanisterIds], ['query']),
+    'get_current_btc_price' : IDL.Func([], [Result_13], ['query']),
+    'get_deposit' : IDL.Func([IDL.Nat64], [Result], ['query']),
     'get_deposit_stats' : IDL.Func(
         [],
         [IDL.Vec(IDL.Tuple(IDL.Text, IDL.Nat64))],
         ['query'],
       ),
-    'get_eth_address' : IDL.Func([], [Result_5], []),
-    'get_free_kyc_status' : IDL.Func([IDL.Text], [Result_6], ['query']),
+    'get_eth_address' : IDL.Func([], [Result_17], []),
+    'get_evm_config' : IDL.Func([], [EvmConfig], ['query']),
+    'get_evm_transaction' : IDL.Func([IDL.Nat64], [Result_18], ['query']),
+    'get_free_kyc_status' : IDL.Func([IDL.Text], [Result_19], ['query']),
     'get_latest_block_number' : IDL.Func([], [IDL.Text], []),
-    'get_ousg_balance' : IDL.Func([], [Result_2], []),
-    'get_user_deposits' : IDL.Func([], [Result_7], ['query']),
-    'get_user_profile' : IDL.Func([], [Result_8], ['query']),
+    'get_multisig_policy' : IDL.Func([], [MultisigPolicy], ['query']),
+    'get_my_deposit_account' : IDL.Func([], [Result_20], ['query']),
+    'get_my_eth_address' : IDL.Func([], [Result_15], ['query']),
+    'get_my_evm_transactions' : IDL.Func(
+        [],
+        [IDL.Vec(EvmTransaction)],
+        ['query'],
+      ),
+    'get_my_roles' : IDL.Func([], [IDL.Vec(Role)], ['query']),
+    'get_ousg_balance' : IDL.Func([], [Result_13], []),
+    'get_quote' : IDL.Func([IDL.Text], [Result_21], ['query']),
+    'get_redemption' : IDL.Func([IDL.Nat64], [Result_12], ['query']),
+    'get_treasury_proposal' : IDL.Func([IDL.Nat64], [Result_14], ['query']),
+    'get_treasury_proposals' : IDL.Func(
+        [IDL.Opt(ProposalStatus)],
+        [Result_22],
+        ['query'],
+      ),
+    'get_user_deposits' : IDL.Func([], [Result_5], ['query']),
+    'get_user_profile' : IDL.Func([], [Result_23], ['query']),
+    'get_user_redemptions' : IDL.Func([], [Result_7], ['query']),
     'is_user_registered' : IDL.Func([], [IDL.Bool], ['query']),
     'notify_deposit' : IDL.Func([DepositRequest], [DepositResponse], []),
-    'redeem_ousg_tokens' : IDL.Func([IDL.Nat64], [Result_2], []),
-    'register_user' : IDL.Func([UserRegistrationRequest], [Result_8], []),
+    'propose_treasury_operation' : IDL.Func(
+        [TreasuryOperation],
+        [Result_14],
+        [],
+      ),
+    'redeem_ousg_tokens' : IDL.Func(
+        [IDL.Nat64, IDL.Opt(IDL.Text)],
+        [Result_13],
+        [],
+      ),
+    'register_user' : IDL.Func([UserRegistrationRequest], [Result_23], []),
+    'request_quote' : IDL.Func([QuoteSide, IDL.Nat64], [Result_21], []),
     'test_erc20_transfer' : IDL.Func([], [TransferResponse], []),
     'transfer_erc20_tokens' : IDL.Func(
         [TransferRequest],
@@ -188,9 +646,81 @@ export const idlFactory = ({ IDL }) => {
       ),
     'upload_document_free_kyc' : IDL.Func(
         [IDL.Text, IDL.Text, IDL.Text],
-        [Result_9],
+        [Result_15],
         [],
       ),
   });
 };
-export const init = ({ IDL }) => { return []; };
+export const init = ({ IDL }) => {
+  const EthSepoliaService = IDL.Variant({
+    'Alchemy' : IDL.Null,
+    'BlockPi' : IDL.Null,
+    'PublicNode' : IDL.Null,
+    'Ankr' : IDL.Null,
+    'Sepolia' : IDL.Null,
+  });
+  const L2MainnetService = IDL.Variant({
+    'Alchemy' : IDL.Null,
+    'Llama' : IDL.Null,
+    'BlockPi' : IDL.Null,
+    'PublicNode' : IDL.Null,
+    'Ankr' : IDL.Null,
+  });
+  const HttpHeader = IDL.Record({ 'value' : IDL.Text, 'name' : IDL.Text });
+  const RpcApi = IDL.Record({
+    'url' : IDL.Text,
+    'headers' : IDL.Opt(IDL.Vec(HttpHeader)),
+  });
+  const EthMainnetService = IDL.Variant({
+    'Alchemy' : IDL.Null,
+    'Llama' : IDL.Null,
+    'BlockPi' : IDL.Null,
+    'Cloudflare' : IDL.Null,
+    'PublicNode' : IDL.Null,
+    'Ankr' : IDL.Null,
+  });
+  const RpcServices = IDL.Variant({
+    'EthSepolia' : IDL.Opt(IDL.Vec(EthSepoliaService)),
+    'BaseMainnet' : IDL.Opt(IDL.Vec(L2MainnetService)),
+    'Custom' : IDL.Record({
+      'chainId' : IDL.Nat64,
+      'services' : IDL.Vec(RpcApi),
+    }),
+    'OptimismMainnet' : IDL.Opt(IDL.Vec(L2MainnetService)),
+    'ArbitrumOne' : IDL.Opt(IDL.Vec(L2MainnetService)),
+    'EthMainnet' : IDL.Opt(IDL.Vec(EthMainnetService)),
+  });
+  const ConsensusStrategy = IDL.Variant({
+    'Equality' : IDL.Null,
+    'Threshold' : IDL.Record({ 'min' : IDL.Nat8, 'total' : IDL.Opt(IDL.Nat8) }),
+  });
+  const EvmNetworkArgs = IDL.Record({
+    'ecdsa_key_name' : IDL.Opt(IDL.Text),
+    'rpc_services' : IDL.Opt(RpcServices),
+    'response_consensus' : IDL.Opt(ConsensusStrategy),
+    'chain_id' : IDL.Opt(IDL.Nat64),
+  });
+  const CanisterIdsArgs = IDL.Record({
+    'xrc' : IDL.Opt(IDL.Principal),
+    'file_store_bucket' : IDL.Opt(IDL.Principal),
+    'ousg_ledger' : IDL.Opt(IDL.Principal),
+    'evm_rpc' : IDL.Opt(IDL.Principal),
+    'ckbtc_ledger' : IDL.Opt(IDL.Principal),
+  });
+  const PriceOracleConfig = IDL.Record({
+    'min_base_sources' : IDL.Nat64,
+    'max_rate_age_seconds' : IDL.Nat64,
+    'test_mode' : IDL.Bool,
+    'test_rate' : IDL.Opt(IDL.Nat64),
+    'twap_window_seconds' : IDL.Opt(IDL.Nat64),
+    'max_std_dev_bps' : IDL.Nat64,
+    'min_received_percent' : IDL.Nat64,
+  });
+  const BackendInitArgs = IDL.Record({
+    'evm' : IDL.Opt(EvmNetworkArgs),
+    'initial_admins' : IDL.Opt(IDL.Vec(IDL.Principal)),
+    'canister_ids' : IDL.Opt(CanisterIdsArgs),
+    'price_oracle' : IDL.Opt(PriceOracleConfig),
+  });
+  return [IDL.Opt(BackendInitArgs)];
+};
@@ -108,7 +108,8 @@ const notifyBackendForMinting = async (blockIndex: BlockIndex, ckbtcAmount: bigi
 
         const notifyResult = await backend.notify_deposit({
             block_index: blockIndex,
-            ckbtc_amount: ckbtcAmount
+            ckbtc_amount: ckbtcAmount,
+            quote_id: []
         });
 
         if (notifyResult.success) {
@@ -160,7 +161,8 @@ export const mintOUSG = async (ckbtcAmount: bigint, blockIndex: bigint): Promise
         // Call the backend minting function
         const response = await backend.notify_deposit({
             block_index: blockIndex,
-            ckbtc_amount: ckbtcAmount
+            ckbtc_amount: ckbtcAmount,
+            quote_id: []
         });
 
         if (response.success) {
@@ -210,7 +212,7 @@ export const getCurrentBTCPrice = async (): Promise<number | null> => {
         const response = await backend.get_current_btc_price();
 
         if ('Ok' in response) {
-            return response.Ok;
+            return Number(response.Ok) / 100_000_000; // Rate has 8 decimals
         } else {
             console.error('Error fetching BTC price:', response.Err);
             return null;
@@ -229,8 +231,14 @@ export const calculateOUSGForUSD = async (usdAmount: number): Promise<bigint | n
     }
 
     try {
-        const response = await backend.calculate_ousg_for_usd(usdAmount);
-        return response;
+        // The backend takes the USD amount in cents
+        const response = await backend.calculate_ousg_for_usd(BigInt(Math.floor(usdAmount * 100)));
+        if ('Ok' in response) {
+            return response.Ok;
+        } else {
+            console.error('Error calculating OUSG for USD:', response.Err);
+            return null;
+        }
     } catch (error) {
         console.error('Error calculating OUSG for USD:', error);
         return null;
@@ -63,8 +63,8 @@ export const redeemOUSG = async (ousgAmount: bigint): Promise<ResultSuccess> =>
             duration: 8000
         });
 
-        // Call the backend redeem function
-        const response = await backend.redeem_ousg_tokens(ousgAmount);
+        // Call the backend redeem function, without a quote it pays out at the oracle rate
+        const response = await backend.redeem_ousg_tokens(ousgAmount, []);
 
         if ('Ok' in response) {
             const ckbtcAmount = response.Ok;
@@ -44,7 +44,7 @@ export const fetchTransactions = async () => {
                 description: `Minted ${formatOUSGAmount(deposit.ousg_minted)} OUSG tokens`,
                 ousgAmount: deposit.ousg_minted,
                 ckbtcAmount: deposit.ckbtc_amount,
-                usdValue: deposit.usd_value
+                usdValue: Number(deposit.usd_value) / 100 // Cents to dollars
             }));
 
             transactions.list = depositTransactions.sort((a, b) => b.timestamp - a.timestamp);